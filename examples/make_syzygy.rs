@@ -0,0 +1,793 @@
+//! Writes the Syzygy tables for the tablebase tests to a directory: king and queen, rook, bishop, knight or pawn against
+//! king, solved by going over all positions until no result changes with a move generator of their own, and king and
+//! two bishops against king, taken from the distances to mate of `rust_chess::retrograde`.
+//!
+//! The values are compressed like the format intends: the most frequent pairs of symbols are replaced by new symbols
+//! until no pair is frequent any more, and the symbols get canonical Huffman codes. The DTZ tables map their values to
+//! symbols, in 16-bit maps for KBBvK, and hold Black to move for KBBvK and for the pawn on the c- and d-files, so that
+//! every part of the prober is used by the tests.
+//!
+//! Usage: `cargo run --release --example make_syzygy -- DIRECTORY [TABLE...]`, all tables by default. The tests keep
+//! the three-piece tables in tests/data/syzygy, written with `-- tests/data/syzygy KQvK KRvK KBvK KNvK KPvK`, and
+//! write all of them to a directory of their own for the tests of KBBvK.
+
+use rust_chess::core::{Board, Color, Figure, Piece, Square};
+use rust_chess::retrograde::{Dtm, DtmTable};
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::process::ExitCode;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+// the flags of a table, see src/tablebase.rs
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+const BLOCK_SIZE_BITS: u8 = 9;
+const SPAN_BITS: u8 = 10;
+// the limits of the compression: the prober reads codes of up to 32 bits, and block lengths and offsets in 16 bits
+const MAX_SYMBOLS: usize = 256;
+const MIN_PAIR_COUNT: usize = 8;
+const MAX_SYMBOL_VALUES: usize = 256;
+const MAX_CODE_LENGTH: u8 = 24;
+const MAX_BLOCK_VALUES: usize = 32768;
+
+// the squares of the triangle a1-d1-d4 and below the diagonal a1-h8, as numbered by the format
+const TRIANGLE: [usize; 64] = [
+    6, 0, 1, 2, 2, 1, 0, 6,
+    0, 7, 3, 4, 4, 3, 7, 0,
+    1, 3, 8, 5, 5, 8, 3, 1,
+    2, 4, 5, 9, 9, 5, 4, 2,
+    2, 4, 5, 9, 9, 5, 4, 2,
+    1, 3, 8, 5, 5, 8, 3, 1,
+    0, 7, 3, 4, 4, 3, 7, 0,
+    6, 0, 1, 2, 2, 1, 0, 6,
+];
+
+const LOWER: [usize; 64] = [
+    28, 0, 1, 2, 3, 4, 5, 6,
+    0, 29, 7, 8, 9, 10, 11, 12,
+    1, 7, 30, 13, 14, 15, 16, 17,
+    2, 8, 13, 31, 18, 19, 20, 21,
+    3, 9, 14, 18, 32, 22, 23, 24,
+    4, 10, 15, 19, 22, 33, 25, 26,
+    5, 11, 16, 20, 23, 25, 34, 27,
+    6, 12, 17, 21, 24, 26, 27, 35,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind { Queen, Rook, Bishop, Knight, Pawn }
+
+impl Kind {
+    fn letter(self) -> char {
+        match self {
+            Kind::Queen => 'Q',
+            Kind::Rook => 'R',
+            Kind::Bishop => 'B',
+            Kind::Knight => 'N',
+            Kind::Pawn => 'P',
+        }
+    }
+
+    // as the format numbers the white pieces
+    fn code(self) -> u8 {
+        match self {
+            Kind::Pawn => 1,
+            Kind::Knight => 2,
+            Kind::Bishop => 3,
+            Kind::Rook => 4,
+            Kind::Queen => 5,
+        }
+    }
+}
+
+// The solution of one ending: results and distances to zero by position and side to move.
+struct Ending {
+    kind: Kind,
+    win: Vec<bool>, // White to move wins
+    lost: Vec<bool>, // Black to move loses
+    dtz: Vec<u16>, // White to move, for wins
+    black_dtz: Vec<u16>, // Black to move, for losses
+}
+
+fn main() -> ExitCode {
+    let Some(directory) = std::env::args().nth(1) else {
+        eprintln!("usage: make_syzygy DIRECTORY [TABLE...]");
+        return ExitCode::FAILURE;
+    };
+    let names: Vec<String> = std::env::args().skip(2).collect();
+    let wanted = |name: &str| names.is_empty() || names.iter().any(|wanted| wanted == name);
+    let mut endings: Vec<Ending> = vec! [];
+    let mut tables = vec! [];
+    for kind in [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight, Kind::Pawn] {
+        let ending = solve(kind, &endings);
+        let name = format!("K{}vK", kind.letter());
+        let longest = ending.dtz.iter().max().unwrap();
+        println!("{}: {} wins, longest distance to zero {} plies", name, ending.win.iter().filter(|&&win| win).count(), longest);
+        // the endings with a pawn need the solutions of the others, which are solved either way
+        if wanted(&name) {
+            tables.push((name, write_wdl(&ending), write_dtz(&ending)));
+        }
+        endings.push(ending);
+    }
+    if wanted("KBBvK") {
+        let (wdl, dtz) = two_bishops();
+        tables.push((String::from("KBBvK"), wdl, dtz));
+    }
+
+    for (name, wdl, dtz) in tables {
+        for (extension, data) in [("rtbw", wdl), ("rtbz", dtz)] {
+            let path = format!("{}/{}.{}", directory, name, extension);
+            if let Err(e) = std::fs::write(&path, data) {
+                eprintln!("make_syzygy: {}: {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn index(white_king: usize, piece: usize, black_king: usize) -> usize {
+    (white_king * 64 + piece) * 64 + black_king
+}
+
+fn unpack(index: usize) -> (usize, usize, usize) {
+    (index / 4096, index / 64 % 64, index % 64)
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8))
+}
+
+fn neighbours(square: usize) -> impl Iterator<Item = usize> {
+    (0..64).filter(move |&other| distance(square, other) == 1)
+}
+
+// The squares the piece goes to, stopped by the squares in the way.
+fn targets(kind: Kind, from: usize, blockers: &[usize]) -> Vec<usize> {
+    let (directions, slides): (&[(i32, i32)], bool) = match kind {
+        Kind::Queen => (&[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)], true),
+        Kind::Rook => (&[(1, 0), (-1, 0), (0, 1), (0, -1)], true),
+        Kind::Bishop => (&[(1, 1), (1, -1), (-1, 1), (-1, -1)], true),
+        Kind::Knight => (&[(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)], false),
+        Kind::Pawn => (&[(1, 1), (-1, 1)], false), // the squares it attacks
+    };
+    let mut squares = vec! [];
+    for &(df, dr) in directions {
+        let (mut file, mut rank) = ((from % 8) as i32, (from / 8) as i32);
+        loop {
+            file += df;
+            rank += dr;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                break;
+            }
+            let square = (rank * 8 + file) as usize;
+            squares.push(square);
+            if !slides || blockers.contains(&square) {
+                break;
+            }
+        }
+    }
+    squares
+}
+
+fn legal(kind: Kind, white_king: usize, piece: usize, black_king: usize, white_to_move: bool) -> bool {
+    if white_king == piece || piece == black_king || distance(white_king, black_king) <= 1 {
+        return false;
+    }
+    if kind == Kind::Pawn && !(8..56).contains(&piece) {
+        return false;
+    }
+    !white_to_move || !targets(kind, piece, &[white_king, black_king]).contains(&black_king)
+}
+
+// A position after a move of White, with Black to move.
+enum WhiteMove {
+    Quiet(usize),
+    PawnMove(usize),
+    Promotion(usize, usize), // the index of the ending promoted to, and the position there
+}
+
+fn white_moves(kind: Kind, position: usize, promotions: &[Ending]) -> Vec<WhiteMove> {
+    let (white_king, piece, black_king) = unpack(position);
+    let mut moves = vec! [];
+    for target in neighbours(white_king).filter(|&target| target != piece && distance(target, black_king) > 1) {
+        moves.push(WhiteMove::Quiet(index(target, piece, black_king)));
+    }
+    if kind == Kind::Pawn {
+        let pushes = if piece < 16 { vec! [piece + 8, piece + 16] } else { vec! [piece + 8] };
+        for target in pushes {
+            if target == white_king || target == black_king {
+                break;
+            }
+            if target >= 56 {
+                for (i, ending) in promotions.iter().enumerate() {
+                    if legal(ending.kind, white_king, target, black_king, false) {
+                        moves.push(WhiteMove::Promotion(i, index(white_king, target, black_king)));
+                    }
+                }
+            } else {
+                moves.push(WhiteMove::PawnMove(index(white_king, target, black_king)));
+            }
+        }
+    } else {
+        for target in targets(kind, piece, &[white_king, black_king]).into_iter().filter(|&target| target != white_king && target != black_king) {
+            moves.push(WhiteMove::Quiet(index(white_king, target, black_king)));
+        }
+    }
+    moves
+}
+
+// The positions after the moves of Black, with White to move; None for taking the piece, which draws.
+fn black_moves(kind: Kind, position: usize) -> Vec<Option<usize>> {
+    let (white_king, piece, black_king) = unpack(position);
+    let mut moves = vec! [];
+    for target in neighbours(black_king).filter(|&target| distance(target, white_king) > 1) {
+        if target == piece {
+            moves.push(None);
+        } else if !targets(kind, piece, &[white_king, target]).contains(&target) {
+            moves.push(Some(index(white_king, piece, target)));
+        }
+    }
+    moves
+}
+
+fn solve(kind: Kind, promotions: &[Ending]) -> Ending {
+    let size = 64 * 64 * 64;
+    let white_legal = (0..size).map(|i| { let (a, b, c) = unpack(i); legal(kind, a, b, c, true) }).collect::<Vec<bool>>();
+    let black_legal = (0..size).map(|i| { let (a, b, c) = unpack(i); legal(kind, a, b, c, false) }).collect::<Vec<bool>>();
+    let white_children = (0..size).map(|i| if white_legal[i] { white_moves(kind, i, promotions) } else { vec! [] }).collect::<Vec<_>>();
+    let black_children = (0..size).map(|i| if black_legal[i] { black_moves(kind, i) } else { vec! [] }).collect::<Vec<_>>();
+
+    let mut win = vec! [false; size];
+    let mut lost = vec! [false; size];
+    let in_check = |i: usize| {
+        let (white_king, piece, black_king) = unpack(i);
+        targets(kind, piece, &[white_king, black_king]).contains(&black_king)
+    };
+    for i in 0..size {
+        lost[i] = black_legal[i] && black_children[i].is_empty() && in_check(i);
+    }
+    let wins = |moves: &WhiteMove, lost: &[bool]| match *moves {
+        WhiteMove::Quiet(child) | WhiteMove::PawnMove(child) => lost[child],
+        WhiteMove::Promotion(ending, child) => promotions[ending].lost[child],
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..size {
+            if white_legal[i] && !win[i] && white_children[i].iter().any(|child| wins(child, &lost)) {
+                win[i] = true;
+                changed = true;
+            }
+            if black_legal[i] && !lost[i] && !black_children[i].is_empty()
+                && black_children[i].iter().all(|child| child.is_some_and(|child| win[child])) {
+                lost[i] = true;
+                changed = true;
+            }
+        }
+    }
+
+    // distances to zero in plies: 1 for a winning pawn move or mate, else one more than the longest defence
+    let mut dtz = vec! [0u16; size];
+    let mut black_dtz = vec! [0u16; size];
+    for i in 0..size {
+        if lost[i] && black_children[i].is_empty() {
+            black_dtz[i] = 1;
+        }
+        if win[i] && white_children[i].iter().any(|child| match *child {
+                WhiteMove::Quiet(child) => lost[child] && black_children[child].is_empty(),
+                _ => wins(child, &lost),
+            }) {
+            dtz[i] = 1;
+        }
+    }
+    let mut ply = 2;
+    while (0..size).any(|i| (win[i] && dtz[i] == 0) || (lost[i] && black_dtz[i] == 0)) {
+        for i in 0..size {
+            if lost[i] && black_dtz[i] == 0 {
+                let longest = black_children[i].iter().map(|child| dtz[child.unwrap()]).collect::<Vec<u16>>();
+                if longest.iter().all(|&d| d > 0) && longest.iter().max() == Some(&(ply - 1)) {
+                    black_dtz[i] = ply;
+                }
+            }
+            if win[i] && dtz[i] == 0 && white_children[i].iter()
+                    .any(|child| matches!(*child, WhiteMove::Quiet(child) if lost[child] && black_dtz[child] == ply - 1)) {
+                dtz[i] = ply;
+            }
+        }
+        ply += 1;
+        assert!(ply < 200, "unsolved positions");
+    }
+    Ending { kind, win, lost, dtz, black_dtz }
+}
+
+// The index of a position in the table, and the file of the pawn.
+fn encode(kind: Kind, position: usize) -> (usize, usize) {
+    let (white_king, piece, black_king) = unpack(position);
+    if kind == Kind::Pawn {
+        let mut squares = [piece, white_king, black_king];
+        if squares[0] % 8 > 3 {
+            squares = squares.map(|square| square ^ 7);
+        }
+        let [pawn, white_king, black_king] = squares;
+        let white_king_index = white_king - (pawn < white_king) as usize;
+        let black_king_index = black_king - (pawn < black_king) as usize - (white_king < black_king) as usize;
+        return (pawn % 8, pawn / 8 - 1 + 6 * white_king_index + 6 * 63 * black_king_index);
+    }
+
+    let mut squares = [white_king, piece, black_king];
+    if squares[0] % 8 > 3 {
+        squares = squares.map(|square| square ^ 7);
+    }
+    if squares[0] / 8 > 3 {
+        squares = squares.map(|square| square ^ 56);
+    }
+    let off_diagonal = |square: usize| (square / 8) as i32 - (square % 8) as i32;
+    if let Some(&first) = squares.iter().find(|&&square| off_diagonal(square) != 0) {
+        if off_diagonal(first) > 0 {
+            squares = squares.map(|square| ((square >> 3) | (square << 3)) & 63);
+        }
+    }
+    let [a, b, c] = squares;
+    let i = (b > a) as usize;
+    let j = (c > a) as usize + (c > b) as usize;
+    let diagonal = |square: usize| square / 8;
+    let index = if off_diagonal(a) != 0 {
+        TRIANGLE[a] * 63 * 62 + (b - i) * 62 + (c - j)
+    } else if off_diagonal(b) != 0 {
+        6 * 63 * 62 + diagonal(a) * 28 * 62 + LOWER[b] * 62 + c - j
+    } else if off_diagonal(c) != 0 {
+        6 * 63 * 62 + 4 * 28 * 62 + diagonal(a) * 7 * 28 + (diagonal(b) - i) * 28 + LOWER[c]
+    } else {
+        6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + diagonal(a) * 7 * 6 + (diagonal(b) - i) * 6 + (diagonal(c) - j)
+    };
+    (0, index)
+}
+
+
+// The table of the values of the index: None where no position has the index, or the prober never looks it up.
+fn collect(size: usize, entries: impl IntoIterator<Item = (usize, u8)>) -> Vec<Option<u8>> {
+    let mut values = vec! [None; size];
+    for (index, value) in entries {
+        let stored = values[index].get_or_insert(value);
+        assert_eq!(*stored, value, "positions of the same index differ");
+    }
+    values
+}
+
+// The values of the table for each file of the pawn, taken from the legal positions.
+fn table_values(kind: Kind, white_to_move: bool, value: impl Fn(usize) -> Option<u8>) -> Vec<Vec<Option<u8>>> {
+    let (files, size) = if kind == Kind::Pawn { (4, 6 * 63 * 62) } else { (1, 31332) };
+    let mut entries = vec! [vec! []; files];
+    for position in 0..64 * 64 * 64 {
+        let (white_king, piece, black_king) = unpack(position);
+        if !legal(kind, white_king, piece, black_king, white_to_move) {
+            continue;
+        }
+        if let Some(value) = value(position) {
+            let (file, index) = encode(kind, position);
+            entries[file].push((index, value));
+        }
+    }
+    entries.into_iter().map(|entries| collect(size, entries)).collect()
+}
+
+// The pieces in the order of the table, the leading group first.
+fn pieces(kind: Kind) -> [u8; 3] {
+    if kind == Kind::Pawn { [kind.code(), 6, 14] } else { [6, kind.code(), 14] }
+}
+
+fn write_wdl(ending: &Ending) -> Vec<u8> {
+    let white = table_values(ending.kind, true, |i| Some(if ending.win[i] { 4 } else { 2 }));
+    let black = table_values(ending.kind, false, |i| Some(if ending.lost[i] { 0 } else { 2 }));
+    let files = white.into_iter().zip(black)
+        .map(|(white, black)| vec! [Side::wdl(white), Side::wdl(black)])
+        .collect();
+    write_table(WDL_MAGIC, ending.kind == Kind::Pawn, &pieces(ending.kind), files)
+}
+
+fn write_dtz(ending: &Ending) -> Vec<u8> {
+    // the distances less one, a checkmate counts one ply; draws are not looked up
+    let white = table_values(ending.kind, true, |i| ending.win[i].then(|| (ending.dtz[i] - 1) as u8));
+    let black = table_values(ending.kind, false, |i| ending.lost[i].then(|| (ending.black_dtz[i] - 1) as u8));
+    let files = white.into_iter().zip(black).enumerate()
+        .map(|(file, (white, black))| match file {
+            2 | 3 => vec! [Side::dtz(true, false, black)],
+            _ => vec! [Side::dtz(false, false, white)],
+        })
+        .collect();
+    write_table(DTZ_MAGIC, ending.kind == Kind::Pawn, &pieces(ending.kind), files)
+}
+
+// KBBvK from the distances to mate of the retrograde solver. They are the distances to zero as well, as White has
+// nothing to capture and Black does not capture a bishop when it loses.
+fn two_bishops() -> (Vec<u8>, Vec<u8>) {
+    let table = DtmTable::generate("KBBvK").unwrap();
+    println!("KBBvK: longest distance to zero {} plies", table.longest_mate());
+    let king_pairs = king_pairs();
+    let square = |index: usize| Square { pos: ((b'a' + (index % 8) as u8) as char, (b'1' + (index / 8) as u8) as char) };
+    let wdl = |dtm: Dtm| match dtm {
+        Dtm::Win(_) => 4,
+        Dtm::Draw => 2,
+        Dtm::Loss(_) => 0,
+    };
+
+    let (mut white, mut black, mut dtz) = (vec! [], vec! [], vec! []);
+    // every index has a position with the white king in the triangle
+    for white_king in [0, 1, 2, 3, 9, 10, 11, 18, 19, 27] {
+        for black_king in (0..64).filter(|&black_king| distance(white_king, black_king) > 1) {
+            for first in 0..64 {
+                for second in first + 1..64 {
+                    if [white_king, black_king].contains(&first) || [white_king, black_king].contains(&second) {
+                        continue;
+                    }
+                    let mut board = Board::new();
+                    for (color, figure, on) in [(Color::White, Figure::King, white_king), (Color::Black, Figure::King, black_king),
+                        (Color::White, Figure::Bishop, first), (Color::White, Figure::Bishop, second)] {
+                        board.add_piece(Piece { color, figure, square: square(on) });
+                    }
+                    let index = encode_kings_first(&king_pairs, [white_king, black_king, first, second]);
+                    if let Some(dtm) = table.probe(&board, Color::White) {
+                        white.push((index, wdl(dtm)));
+                    }
+                    if let Some(dtm) = table.probe(&board, Color::Black) {
+                        black.push((index, wdl(dtm)));
+                        if let Dtm::Loss(plies) = dtm {
+                            dtz.push((index, plies.max(1) as u8 - 1));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let size = 462 * 1891;
+    let pieces = [6, 14, 3, 3];
+    let wdl = write_table(WDL_MAGIC, false, &pieces, vec! [vec! [Side::wdl(collect(size, white)), Side::wdl(collect(size, black))]]);
+    let dtz = write_table(DTZ_MAGIC, false, &pieces, vec! [vec! [Side::dtz(true, true, collect(size, dtz))]]);
+    (wdl, dtz)
+}
+
+// The index of the two kings for each square of the white king in the triangle, None if they can not stand there.
+// With the white king on the diagonal the black king is not above it, and positions with both on it come last.
+fn king_pairs() -> Vec<[Option<usize>; 64]> {
+    let off_diagonal = |square: usize| (square / 8) as i32 - (square % 8) as i32;
+    let mut triangle = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+    triangle.sort_by_key(|&square| TRIANGLE[square]);
+    let mut pairs = vec! [[None; 64]; 10];
+    let mut index = 0;
+    let mut both_on_diagonal = vec! [];
+    for (code, &white_king) in triangle.iter().enumerate() {
+        let apart = (0..64).filter(|&black_king| distance(white_king, black_king) > 1);
+        for black_king in apart.filter(|&black_king| off_diagonal(white_king) != 0 || off_diagonal(black_king) <= 0) {
+            if off_diagonal(white_king) == 0 && off_diagonal(black_king) == 0 {
+                both_on_diagonal.push((code, black_king));
+            } else {
+                pairs[code][black_king] = Some(index);
+                index += 1;
+            }
+        }
+    }
+    for (code, black_king) in both_on_diagonal {
+        pairs[code][black_king] = Some(index);
+        index += 1;
+    }
+    assert_eq!(462, index);
+    pairs
+}
+
+// The index of a position with both kings leading and two pieces of the same kind: the kings, then the pieces as a
+// set on the squares the kings leave free.
+fn encode_kings_first(king_pairs: &[[Option<usize>; 64]], mut squares: [usize; 4]) -> usize {
+    if squares[0] % 8 > 3 {
+        squares = squares.map(|square| square ^ 7);
+    }
+    if squares[0] / 8 > 3 {
+        squares = squares.map(|square| square ^ 56);
+    }
+    let off_diagonal = |square: usize| (square / 8) as i32 - (square % 8) as i32;
+    if let Some(&king) = squares[..2].iter().find(|&&square| off_diagonal(square) != 0) {
+        if off_diagonal(king) > 0 {
+            squares = squares.map(|square| ((square >> 3) | (square << 3)) & 63);
+        }
+    }
+    let [white_king, black_king, first, second] = squares;
+    let (first, second) = (first.min(second), first.max(second));
+    let free = |square: usize| square - [white_king, black_king].iter().filter(|&&king| king < square).count();
+    let pieces = free(first) + free(second) * (free(second) - 1) / 2;
+    king_pairs[TRIANGLE[white_king]][black_king].unwrap() + 462 * pieces
+}
+
+// One side to move of a table for one file of the pawn: the flags, the values and for DTZ tables the maps of wins,
+// losses, cursed wins and blessed losses.
+struct Side {
+    flags: u8,
+    values: Vec<Option<u8>>,
+    maps: Option<[Vec<u16>; 4]>,
+}
+
+impl Side {
+    // the results of the side to move from 0 for a loss to 4 for a win
+    fn wdl(values: Vec<Option<u8>>) -> Side {
+        Side { flags: 0, values, maps: None }
+    }
+
+    // the distances in plies less one, all wins with White to move, all losses with Black to move
+    fn dtz(black_to_move: bool, wide: bool, values: Vec<Option<u8>>) -> Side {
+        let flags = WIN_PLIES | LOSS_PLIES | if black_to_move { STM } else { 0 };
+        if values.iter().all(Option::is_none) {
+            return Side { flags, values, maps: None };
+        }
+        // the most frequent distances get the lowest symbols
+        let mut counts: HashMap<u8, usize> = HashMap::new();
+        for value in values.iter().flatten() {
+            *counts.entry(*value).or_default() += 1;
+        }
+        let mut map = counts.keys().copied().collect::<Vec<u8>>();
+        map.sort_by_key(|value| (Reverse(counts[value]), *value));
+        let values = values.iter()
+            .map(|value| value.map(|value| map.iter().position(|&mapped| mapped == value).unwrap() as u8))
+            .collect();
+        let mut maps: [Vec<u16>; 4] = Default::default();
+        maps[black_to_move as usize] = map.into_iter().map(u16::from).collect();
+        Side { flags: flags | MAPPED | if wide { WIDE } else { 0 }, values, maps: Some(maps) }
+    }
+}
+
+// Writes the sides of each file of the pawn: the pieces, the sizes and codes of the compressed values, the maps,
+// the sparse indices, the block lengths and the blocks.
+fn write_table(magic: [u8; 4], pawns: bool, pieces: &[u8], files: Vec<Vec<Side>>) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.push((files[0].len() == 2) as u8 | if pawns { 2 } else { 0 });
+    for _ in files.iter() {
+        out.push(0); // the leading group comes first
+        out.extend(pieces.iter().map(|&piece| piece | piece << 4));
+    }
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    let compressed = files.iter()
+        .flat_map(|sides| sides.iter().map(|side| compress(side.flags, &side.values)))
+        .collect::<Vec<Compressed>>();
+    for table in compressed.iter() {
+        out.extend(&table.header);
+    }
+    if magic == DTZ_MAGIC {
+        for side in files.iter().map(|sides| &sides[0]) {
+            let Some(maps) = &side.maps else {
+                continue;
+            };
+            if side.flags & WIDE != 0 {
+                if out.len() % 2 == 1 {
+                    out.push(0);
+                }
+                for map in maps.iter() {
+                    out.extend((map.len() as u16).to_le_bytes());
+                    out.extend(map.iter().flat_map(|value| value.to_le_bytes()));
+                }
+            } else {
+                for map in maps.iter() {
+                    out.push(map.len() as u8);
+                    out.extend(map.iter().map(|&value| value as u8));
+                }
+            }
+        }
+        if out.len() % 2 == 1 {
+            out.push(0);
+        }
+    }
+    for table in compressed.iter() {
+        out.extend(&table.sparse_index);
+    }
+    for table in compressed.iter() {
+        out.extend(&table.block_lengths);
+    }
+    for table in compressed.iter() {
+        while !out.len().is_multiple_of(64) {
+            out.push(0);
+        }
+        out.extend(&table.blocks);
+    }
+    out
+}
+
+struct Compressed {
+    header: Vec<u8>,
+    sparse_index: Vec<u8>,
+    block_lengths: Vec<u8>,
+    blocks: Vec<u8>,
+}
+
+// A symbol of the compressed values.
+#[derive(Clone, Copy)]
+enum Symbol {
+    Value(u8),
+    Pair(usize, usize),
+}
+
+fn compress(flags: u8, values: &[Option<u8>]) -> Compressed {
+    // the indices which are never looked up take the value before them, which makes for long runs
+    let first = values.iter().flatten().next().copied().unwrap_or(0);
+    let values = values.iter()
+        .scan(first, |last, value| {
+            *last = value.unwrap_or(*last);
+            Some(*last)
+        })
+        .collect::<Vec<u8>>();
+    let mut distinct = values.clone();
+    distinct.sort();
+    distinct.dedup();
+    if distinct.len() == 1 {
+        let header = vec! [flags | SINGLE_VALUE, distinct[0]];
+        return Compressed { header, sparse_index: vec! [], block_lengths: vec! [], blocks: vec! [] };
+    }
+
+    // replace the most frequent pair of symbols by a new one, as long as that pays
+    let mut symbols = distinct.iter().map(|&value| Symbol::Value(value)).collect::<Vec<Symbol>>();
+    let mut lengths = vec! [1; symbols.len()]; // the values a symbol stands for
+    let mut sequence = values.iter().map(|value| distinct.binary_search(value).unwrap()).collect::<Vec<usize>>();
+    while symbols.len() < MAX_SYMBOLS {
+        let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+        for pair in sequence.windows(2) {
+            *counts.entry((pair[0], pair[1])).or_default() += 1;
+        }
+        let best = counts.into_iter()
+            .filter(|&((left, right), _)| lengths[left] + lengths[right] <= MAX_SYMBOL_VALUES)
+            .max_by_key(|&(pair, count)| (count, Reverse(pair)));
+        let Some((pair, count)) = best else {
+            break;
+        };
+        if count < MIN_PAIR_COUNT {
+            break;
+        }
+        let symbol = symbols.len();
+        symbols.push(Symbol::Pair(pair.0, pair.1));
+        lengths.push(lengths[pair.0] + lengths[pair.1]);
+        let mut replaced = Vec::with_capacity(sequence.len());
+        let mut i = 0;
+        while i < sequence.len() {
+            if i + 1 < sequence.len() && (sequence[i], sequence[i + 1]) == pair {
+                replaced.push(symbol);
+                i += 2;
+            } else {
+                replaced.push(sequence[i]);
+                i += 1;
+            }
+        }
+        sequence = replaced;
+    }
+
+    // canonical codes: the symbols with the longest codes come first and get the lowest codes, the symbols which only
+    // occur in pairs come last and get no code
+    let mut frequencies = vec! [0u64; symbols.len()];
+    for &symbol in sequence.iter() {
+        frequencies[symbol] += 1;
+    }
+    let code_lengths = huffman_lengths(&frequencies);
+    let mut order = (0..symbols.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&symbol| (code_lengths[symbol] == 0, Reverse(code_lengths[symbol]), symbol));
+    let mut renumbered = vec! [0; symbols.len()];
+    for (index, &symbol) in order.iter().enumerate() {
+        renumbered[symbol] = index;
+    }
+    let max_len = *code_lengths.iter().max().unwrap() as usize;
+    let min_len = *code_lengths.iter().filter(|&&len| len > 0).min().unwrap() as usize;
+    let mut count = vec! [0usize; max_len + 2];
+    for &len in code_lengths.iter().filter(|&&len| len > 0) {
+        count[len as usize] += 1;
+    }
+    let mut lowest_symbol = vec! [0usize; max_len + 2];
+    let mut lowest_code = vec! [0u64; max_len + 2];
+    for len in (min_len..max_len).rev() {
+        lowest_symbol[len] = lowest_symbol[len + 1] + count[len + 1];
+        lowest_code[len] = (lowest_code[len + 1] + count[len + 1] as u64) / 2;
+    }
+    let code = |symbol: usize| {
+        let len = code_lengths[symbol] as usize;
+        (lowest_code[len] + (renumbered[symbol] - lowest_symbol[len]) as u64, len)
+    };
+
+    // fill the blocks with whole symbols
+    let block_size = 1 << BLOCK_SIZE_BITS;
+    let mut blocks = vec! [0u8; block_size];
+    let mut block_values = vec! [0usize];
+    let mut bit = 0;
+    for &symbol in sequence.iter() {
+        let (code, len) = code(symbol);
+        if bit + len > 8 * block_size || block_values.last().unwrap() + lengths[symbol] > MAX_BLOCK_VALUES {
+            blocks.resize(blocks.len() + block_size, 0);
+            block_values.push(0);
+            bit = 0;
+        }
+        let start = (block_values.len() - 1) * block_size * 8 + bit;
+        for i in 0..len {
+            if code >> (len - 1 - i) & 1 == 1 {
+                blocks[(start + i) / 8] |= 0x80 >> ((start + i) % 8);
+            }
+        }
+        bit += len;
+        *block_values.last_mut().unwrap() += lengths[symbol];
+    }
+
+    let mut header = vec! [flags, BLOCK_SIZE_BITS, SPAN_BITS, 0];
+    header.extend((block_values.len() as u32).to_le_bytes());
+    header.extend([max_len as u8, min_len as u8]);
+    for &symbol in lowest_symbol[min_len..=max_len].iter() {
+        header.extend((symbol as u16).to_le_bytes());
+    }
+    header.extend((symbols.len() as u16).to_le_bytes());
+    for &symbol in order.iter() {
+        // 12 bits for each half, a value has 0xFFF as its right half
+        let (left, right) = match symbols[symbol] {
+            Symbol::Value(value) => (value as usize, 0xFFF),
+            Symbol::Pair(left, right) => (renumbered[left], renumbered[right]),
+        };
+        header.extend([left as u8, (left >> 8) as u8 & 0xF | (right as u8 & 0xF) << 4, (right >> 4) as u8]);
+    }
+    if symbols.len() % 2 == 1 {
+        header.push(0);
+    }
+
+    // the block and the offset in it of the value in the middle of each span
+    let span = 1 << SPAN_BITS;
+    let starts = block_values.iter()
+        .scan(0, |start, &values| {
+            *start += values;
+            Some(*start - values)
+        })
+        .collect::<Vec<usize>>();
+    let mut sparse_index = vec! [];
+    for k in 0..values.len().div_ceil(span) {
+        let idx = k * span + span / 2;
+        let block = starts.partition_point(|&start| start <= idx) - 1;
+        sparse_index.extend((block as u32).to_le_bytes());
+        sparse_index.extend(u16::try_from(idx - starts[block]).unwrap().to_le_bytes());
+    }
+    let block_lengths = block_values.iter().flat_map(|&values| (values as u16 - 1).to_le_bytes()).collect();
+    Compressed { header, sparse_index, block_lengths, blocks }
+}
+
+// The lengths of the Huffman codes of the symbols, 0 for the ones which do not occur. Rare symbols get the counts
+// of more frequent ones until no code is too long.
+fn huffman_lengths(frequencies: &[u64]) -> Vec<u8> {
+    let mut frequencies = frequencies.to_vec();
+    let used = (0..frequencies.len()).filter(|&symbol| frequencies[symbol] > 0).collect::<Vec<usize>>();
+    let mut lengths = vec! [0u8; frequencies.len()];
+    if used.len() == 1 {
+        lengths[used[0]] = 1;
+        return lengths;
+    }
+    loop {
+        let mut parents = vec! [None; frequencies.len()];
+        let mut heap = used.iter().map(|&symbol| Reverse((frequencies[symbol], symbol))).collect::<BinaryHeap<_>>();
+        while let (Some(Reverse((first, a))), Some(Reverse((second, b)))) = (heap.pop(), heap.pop()) {
+            let node = parents.len();
+            parents.push(None);
+            parents[a] = Some(node);
+            parents[b] = Some(node);
+            heap.push(Reverse((first + second, node)));
+        }
+        for &symbol in used.iter() {
+            let mut depth = 0;
+            let mut node = symbol;
+            while let Some(parent) = parents[node] {
+                depth += 1;
+                node = parent;
+            }
+            lengths[symbol] = depth;
+        }
+        if lengths.iter().all(|&len| len <= MAX_CODE_LENGTH) {
+            return lengths;
+        }
+        for frequency in frequencies.iter_mut().filter(|frequency| **frequency > 0) {
+            *frequency = *frequency / 2 + 1;
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Builds a Polyglot opening book from PGN files.
+//!
+//! Usage: `make_book [--ply N] [--min-games N] [--min-rating N] [--dump TREE.txt] -o BOOK.bin GAMES.pgn...`
+
+use rust_chess::book::BookBuilder;
+
+use std::process::ExitCode;
+
+const DEFAULT_PLY: usize = 20;
+
+fn main() -> ExitCode {
+    match run(&std::env::args().skip(1).collect::<Vec<String>>()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("make_book: {}", e);
+            eprintln!("usage: make_book [--ply N] [--min-games N] [--min-rating N] [--dump TREE.txt] -o BOOK.bin GAMES.pgn...");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let mut builder = BookBuilder::new(DEFAULT_PLY);
+    let mut output = None;
+    let mut dump = None;
+    let mut inputs = vec! [];
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--ply" => builder.max_ply = parse_number(value()?)?,
+            "--min-games" => builder.min_games = parse_number(value()?)?,
+            "--min-rating" => builder.min_rating = Some(parse_number(value()?)?),
+            "--dump" => dump = Some(value()?.clone()),
+            "-o" | "--output" => output = Some(value()?.clone()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => inputs.push(arg.clone()),
+        }
+    }
+    let output = output.ok_or("no output file given")?;
+    if inputs.is_empty() {
+        return Err(String::from("no PGN files given"));
+    }
+
+    let mut games = 0;
+    for input in inputs.iter() {
+        let pgn = std::fs::read_to_string(input).map_err(|e| format!("{}: {}", input, e))?;
+        games += builder.add_pgn(&pgn);
+    }
+    let book = builder.build();
+    book.save(&output).map_err(|e| format!("{}: {}", output, e))?;
+    if let Some(dump) = dump {
+        std::fs::write(&dump, builder.tree_dump()).map_err(|e| format!("{}: {}", dump, e))?;
+    }
+    println!("{} games, {} book entries written to {}", games, book.len(), output);
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid number {}", value))
+}
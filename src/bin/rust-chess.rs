@@ -0,0 +1,405 @@
+//! Play chess in the terminal, against another human at the same keyboard or a simple built-in opponent.
+//!
+//! Usage: `rust-chess [--white PLAYER] [--black PLAYER] [--fen FEN] [--seed N] [--ascii] [--colors]`, where a player is
+//! `human`, `random` or `greedy`. Moves are entered in SAN, e.g. `Nf3`, or in coordinates, e.g. `g1f3` or `g1-f3`.
+//! Type `help` for the other commands.
+
+use rust_chess::book::Rng;
+use rust_chess::core::{Color, Square};
+use rust_chess::game::{Game, GameMeta, GameResult, Move};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::render::BoardRenderer;
+use rust_chess::search::evaluate;
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: rust-chess [--white human|random|greedy] [--black human|random|greedy] [--fen FEN] [--seed N] [--ascii] [--colors]";
+
+const HELP: &str = "\
+Enter a move in SAN (Nf3, exd5, O-O, e8=Q) or coordinates (g1f3, g1-f3, e7e8q), or one of the commands:
+  moves [SQUARE]    the legal moves, or those of the piece on the square
+  undo              takes back the last move, against the computer also its reply
+  flip              turns the board around
+  fen               the position in FEN
+  pgn               the game in PGN
+  pgn save FILE     writes the game to a PGN file
+  load FILE         continues the first game of a PGN file
+  new               starts a new game
+  resign            resigns the game for the side to move
+  draw              offers a draw to the opponent
+  help              this text
+  quit              leaves the program";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Player {
+    Human,
+    /// Plays any legal move.
+    Random,
+    /// Plays the move with the best static evaluation after it, and mates when it can.
+    Greedy,
+}
+
+struct Session {
+    game: Game,
+    start_fen: Option<String>,
+    players: [Player; 2],
+    renderer: BoardRenderer,
+    rng: Rng,
+    /// The side which offered a draw with the last command, until the opponent answers it.
+    draw_offer: Option<Color>,
+}
+
+fn main() -> ExitCode {
+    let mut session = match parse_args(&std::env::args().skip(1).collect::<Vec<String>>()) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("rust-chess: {}", e);
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        },
+    };
+    println!("Type `help` for the commands.");
+    session.run(io::stdin().lock());
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: &[String]) -> Result<Session, String> {
+    let mut players = [Player::Human, Player::Human];
+    let mut start_fen = None;
+    let mut seed = None;
+    let mut renderer = BoardRenderer::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--white" => players[0] = parse_player(value()?)?,
+            "--black" => players[1] = parse_player(value()?)?,
+            "--fen" => start_fen = Some(value()?.clone()),
+            "--seed" => {
+                let value = value()?;
+                seed = Some(value.parse().map_err(|_| format!("invalid number {}", value))?);
+            },
+            "--ascii" => renderer.unicode = false,
+            "--colors" => renderer.ansi_colors = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+
+    let game = new_game(&start_fen)?;
+    // a human playing Black alone wants to see the board from their side
+    renderer.flipped = players == [Player::Greedy, Player::Human] || players == [Player::Random, Player::Human];
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+    });
+    let mut session = Session { game, start_fen, players, renderer, rng: Rng::new(seed), draw_offer: None };
+    session.tag_players();
+    Ok(session)
+}
+
+// the game from the starting position, which may already be mate or stalemate
+fn new_game(start_fen: &Option<String>) -> Result<Game, String> {
+    let mut game = match start_fen {
+        Some(fen) => Game::from_fen(fen).map_err(|e| format!("{}: {}", fen, e))?,
+        None => Game::new(),
+    };
+    game.update_result();
+    Ok(game)
+}
+
+fn parse_player(value: &str) -> Result<Player, String> {
+    match value {
+        "human" => Ok(Player::Human),
+        "random" => Ok(Player::Random),
+        "greedy" => Ok(Player::Greedy),
+        _ => Err(format!("unknown player {}", value)),
+    }
+}
+
+impl Session {
+
+    fn run<R: BufRead>(&mut self, input: R) {
+        let mut lines = input.lines();
+        self.show_board();
+        // the starting position may be over already
+        self.show_result();
+        loop {
+            if self.game.result().is_none() && self.player() != Player::Human {
+                self.computer_move();
+                continue;
+            }
+            match self.draw_offer {
+                Some(color) => print!("{}, accept the draw? [accept/decline] ", color_name(color.opposite())),
+                None => print!("{}> ", color_name(self.game.current_color())),
+            }
+            let _ = io::stdout().flush();
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break,
+            };
+            if !self.command(line.trim()) {
+                break;
+            }
+        }
+        println!();
+    }
+
+    // runs a command or plays a move, returns false to quit
+    fn command(&mut self, line: &str) -> bool {
+        if let Some(color) = self.draw_offer.take() {
+            self.answer_draw(color, line == "accept");
+            return true;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {},
+            ["quit"] | ["exit"] => return false,
+            ["help"] => println!("{}", HELP),
+            ["moves"] => self.show_moves(None),
+            ["moves", square] => match self.game.board.get(square) {
+                Some(square) => self.show_moves(Some(*square)),
+                None => println!("Invalid square {}", square),
+            },
+            ["undo"] => self.undo(),
+            ["flip"] => {
+                self.renderer.flipped = !self.renderer.flipped;
+                self.show_board();
+            },
+            ["fen"] => println!("{}", self.game.to_fen()),
+            ["pgn"] => print!("{}", export_pgn(&self.game)),
+            ["pgn", "save", path] => match std::fs::write(path, export_pgn(&self.game)) {
+                Ok(()) => println!("Saved the game to {}", path),
+                Err(e) => println!("{}: {}", path, e),
+            },
+            ["load", path] => self.load(path),
+            ["new"] => {
+                self.game = new_game(&self.start_fen).unwrap();
+                self.tag_players();
+                self.show_board();
+                self.show_result();
+            },
+            ["resign"] if self.game.result().is_none() => {
+                let color = self.game.current_color();
+                self.game.set_result(Some(win_for(color.opposite())));
+                println!("{} resigns.", color_name(color));
+                self.show_result();
+            },
+            ["draw"] if self.game.result().is_none() => self.offer_draw(),
+            ["resign"] | ["draw"] => println!("The game is over."),
+            [input] => {
+                if self.game.result().is_some() {
+                    println!("The game is over, `undo` or start a `new` one.");
+                    return true;
+                }
+                match self.parse_move(input) {
+                    Some(move_) => self.play(move_),
+                    None => println!("Illegal or unknown move {}, `moves` lists the legal ones.", input),
+                }
+            },
+            _ => println!("Unknown command {}, `help` lists the commands.", line),
+        }
+        true
+    }
+
+    fn player(&self) -> Player {
+        self.players[index(self.game.current_color())]
+    }
+
+    // SAN first, as castling is also written with a dash
+    fn parse_move(&self, input: &str) -> Option<Move> {
+        self.game.parse_san(input).ok()
+            .or_else(|| self.game.parse_uci_move(&input.replace('-', "")).ok())
+    }
+
+    fn play(&mut self, move_: Move) {
+        let san = self.game.move_to_san(&move_);
+        let number = self.game.fullmove_number();
+        let dots = if self.game.current_color() == Color::White { "." } else { "..." };
+        self.game.make_move(move_);
+        self.game.update_result();
+        println!("{}{} {}", number, dots, san);
+        self.show_board();
+        if self.game.result().is_some() {
+            self.show_result();
+        }
+    }
+
+    fn computer_move(&mut self) {
+        let mut moves: Vec<Move> = self.game.get_legal_moves(self.game.current_color()).into_iter().collect();
+        // the moves come from a hash set, sort them so that a seed repeats the game
+        moves.sort_by_key(|move_| move_.to_uci());
+        if moves.is_empty() {
+            // only happens if the result was not known yet, which ends the game now
+            self.game.update_result();
+            self.show_result();
+            return;
+        }
+        let move_ = match self.player() {
+            Player::Greedy => {
+                let mut best = vec! [];
+                let mut best_score = i32::MIN;
+                for move_ in moves {
+                    let score = greedy_score(&mut self.game.simulate_move(move_));
+                    if score > best_score {
+                        best_score = score;
+                        best.clear();
+                    }
+                    if score == best_score {
+                        best.push(move_);
+                    }
+                }
+                best[self.rng.below(best.len() as u64) as usize]
+            },
+            _ => moves[self.rng.below(moves.len() as u64) as usize],
+        };
+        self.play(move_);
+    }
+
+    fn offer_draw(&mut self) {
+        let color = self.game.current_color();
+        if self.players[index(color.opposite())] == Player::Human {
+            println!("{} offers a draw.", color_name(color));
+            self.draw_offer = Some(color);
+        } else {
+            // the computer takes the draw unless it stands better
+            self.answer_draw(color, evaluate(&self.game) >= 0);
+        }
+    }
+
+    fn answer_draw(&mut self, offered_by: Color, accept: bool) {
+        if accept {
+            self.game.set_result(Some(GameResult::Draw));
+            println!("{} accepts the draw.", color_name(offered_by.opposite()));
+            self.show_result();
+        } else {
+            println!("{} declines the draw.", color_name(offered_by.opposite()));
+        }
+    }
+
+    fn undo(&mut self) {
+        if self.game.moves().is_empty() {
+            println!("There is no move to take back.");
+            return;
+        }
+        let mut moves = self.game.moves();
+        moves.pop();
+        // take back the computer's reply too, so that it is the human's turn again
+        let color = self.game.current_color();
+        if self.players[index(color)] == Player::Human && self.players[index(color.opposite())] != Player::Human && !moves.is_empty() {
+            moves.pop();
+        }
+        // replay the remaining moves rather than going back in the tree, which would keep the move as the mainline
+        let mut game = Game::from_fen(&self.game.start_fen).unwrap();
+        for move_ in moves {
+            game.make_move(move_);
+        }
+        game.meta = self.game.meta.take();
+        self.game = game;
+        self.show_board();
+    }
+
+    fn load(&mut self, path: &str) {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                println!("{}: {}", path, e);
+                return;
+            },
+        };
+        let pgn = rust_chess::parser::split_pgn(&text).first().copied().unwrap_or("");
+        match parse_pgn(pgn) {
+            Ok(game) if game.variant().name() == Game::new().variant().name() => {
+                self.game = game;
+                println!("Loaded {} moves from {}", self.game.moves().len(), path);
+                self.show_board();
+                if self.game.result().is_some() {
+                    self.show_result();
+                }
+            },
+            Ok(_) => println!("{}: only standard chess can be played", path),
+            Err(e) => println!("{}: {}", path, e),
+        }
+    }
+
+    fn show_moves(&self, square: Option<Square>) {
+        let moves: Vec<Move> = self.game.get_legal_moves(self.game.current_color()).into_iter()
+            .filter(|move_| square.is_none_or(|square| move_.piece().square == square))
+            .collect();
+        if let Some(square) = square {
+            let renderer = BoardRenderer { highlights: moves.iter().map(|move_| move_.target_square()).collect(), ..self.renderer.clone() };
+            print!("{}", renderer.render(&self.game.board));
+            if moves.is_empty() {
+                println!("No legal moves from {}", square);
+                return;
+            }
+        }
+        let mut sans: Vec<String> = moves.iter().map(|move_| self.game.move_to_san(move_)).collect();
+        sans.sort();
+        println!("{}", sans.join(" "));
+    }
+
+    fn show_board(&self) {
+        print!("{}", self.renderer.render(&self.game.board));
+        if self.game.result().is_none() && self.game.in_check() {
+            println!("{} is in check.", color_name(self.game.current_color()));
+        }
+    }
+
+    fn show_result(&self) {
+        let result = match self.game.result() {
+            Some(GameResult::WhiteWin) => "1-0",
+            Some(GameResult::BlackWin) => "0-1",
+            Some(GameResult::Draw) => "1/2-1/2",
+            None => return,
+        };
+        match self.game.termination() {
+            Some(termination) => println!("Game over: {} ({})", result, termination),
+            None => println!("Game over: {}", result),
+        }
+    }
+
+    // names the computer players in the PGN tags
+    fn tag_players(&mut self) {
+        let mut meta = GameMeta::new();
+        for (tag, player) in [("White", self.players[0]), ("Black", self.players[1])] {
+            match player {
+                Player::Human => {},
+                Player::Random => meta.set_tag(tag, "rust-chess (random)"),
+                Player::Greedy => meta.set_tag(tag, "rust-chess (greedy)"),
+            }
+        }
+        self.game.meta = Some(meta);
+    }
+
+}
+
+// the score of the position after a move for the side which made it, a mate beats everything
+fn greedy_score(game: &mut Game) -> i32 {
+    match game.update_result() {
+        Some(GameResult::Draw) => 0,
+        Some(_) => i32::MAX,
+        None => -evaluate(game),
+    }
+}
+
+fn win_for(color: Color) -> GameResult {
+    match color {
+        Color::White => GameResult::WhiteWin,
+        Color::Black => GameResult::BlackWin,
+    }
+}
+
+fn index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "White",
+        Color::Black => "Black",
+    }
+}
@@ -0,0 +1,183 @@
+//! A front-end speaking the Universal Chess Interface (UCI) on stdin/stdout.
+//! It drives the crate's own `Game` and move generation with the simple search from `rust_chess::search`.
+
+use rust_chess::core::Color;
+use rust_chess::game::Game;
+use rust_chess::search::{search, Score, SearchInfo, SearchLimits};
+
+use std::io::{self, BufRead};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const DEFAULT_DEPTH: u32 = 3;
+
+struct Engine {
+    game: Game,
+    depth: u32,
+    search: Option<RunningSearch>,
+}
+
+struct RunningSearch {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+}
+
+fn main() {
+    let mut engine = Engine { game: Game::new(), depth: DEFAULT_DEPTH, search: None };
+
+    for line in io::stdin().lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"uci") => {
+                println!("id name rust_chess {}", env!("CARGO_PKG_VERSION"));
+                println!("id author TimHal");
+                println!("option name Depth type spin default {} min 1 max 64", DEFAULT_DEPTH);
+                println!("uciok");
+            },
+            Some(&"isready") => println!("readyok"),
+            Some(&"ucinewgame") => {
+                engine.stop();
+                engine.game = Game::new();
+            },
+            Some(&"position") => {
+                engine.stop();
+                match parse_position(&tokens[1..]) {
+                    Ok(game) => engine.game = game,
+                    Err(e) => println!("info string {}", e),
+                }
+            },
+            Some(&"go") => {
+                engine.stop();
+                engine.go(parse_limits(&tokens[1..]));
+            },
+            Some(&"stop") => engine.stop(),
+            Some(&"setoption") => engine.set_option(&tokens[1..]),
+            Some(&"quit") => {
+                engine.stop();
+                return;
+            },
+            _ => {},
+        }
+    }
+
+    // stdin is closed: let a running search finish unless it would never end on its own
+    engine.finish();
+}
+
+impl Engine {
+
+    fn go(&mut self, mut limits: SearchLimits) {
+        // without a clock for the side to move the search would never end on its own
+        let time = match self.game.current_color() {
+            Color::White => limits.wtime,
+            Color::Black => limits.btime,
+        };
+        if limits.depth.is_none() && limits.movetime.is_none() && time.is_none() && !limits.infinite {
+            limits.depth = Some(self.depth);
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let infinite = limits.infinite;
+        let game = self.game.clone();
+        let handle = std::thread::spawn(move || {
+            let best = search(&game, &limits, thread_stop.clone(), |info| println!("{}", format_info(info)));
+            // in infinite mode the best move may only be sent after the GUI has sent stop
+            while limits.infinite && !thread_stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            match best {
+                Some(mv) => println!("bestmove {}", mv.to_uci()),
+                None => println!("bestmove 0000"),
+            }
+        });
+        self.search = Some(RunningSearch { handle, stop, infinite });
+    }
+
+    fn stop(&mut self) {
+        if let Some(running) = self.search.take() {
+            running.stop.store(true, Ordering::Relaxed);
+            let _ = running.handle.join();
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(running) = self.search.take() {
+            if running.infinite {
+                running.stop.store(true, Ordering::Relaxed);
+            }
+            let _ = running.handle.join();
+        }
+    }
+
+    fn set_option(&mut self, tokens: &[&str]) {
+        // setoption name <id> [value <x>]
+        let value_index = tokens.iter().position(|&t| t == "value").unwrap_or(tokens.len());
+        let name = tokens[1.min(tokens.len())..value_index].join(" ");
+        let value = tokens[(value_index + 1).min(tokens.len())..].join(" ");
+        match name.to_ascii_lowercase().as_str() {
+            "depth" => match value.parse::<u32>() {
+                Ok(depth) if (1..=64).contains(&depth) => self.depth = depth,
+                _ => println!("info string invalid value for option Depth: {}", value),
+            },
+            _ => println!("info string unknown option: {}", name),
+        }
+    }
+
+}
+
+fn parse_position(tokens: &[&str]) -> Result<Game, String> {
+    // position [startpos | fen <fenstring>] [moves <move1> ... <movei>]
+    let moves_index = tokens.iter().position(|&t| t == "moves").unwrap_or(tokens.len());
+    let mut game = match tokens.first() {
+        Some(&"startpos") => Game::new(),
+        Some(&"fen") => {
+            let fen = tokens[1..moves_index].join(" ");
+            Game::from_fen(&fen).map_err(|e| format!("{}: {}", e, fen))?
+        },
+        _ => return Err(String::from("expected startpos or fen")),
+    };
+
+    for token in tokens.iter().skip(moves_index + 1) {
+        let mv = game.parse_uci_move(token).map_err(|e| format!("{}: {}", e.to_lowercase(), token))?;
+        game.make_move(mv);
+    }
+    Ok(game)
+}
+
+fn parse_limits(tokens: &[&str]) -> SearchLimits {
+    let mut limits = SearchLimits::default();
+    let value = |i: usize| tokens.get(i + 1).and_then(|v| v.parse::<i64>().ok());
+    let millis = |i: usize| value(i).map(|v| Duration::from_millis(v.max(0) as u64));
+    for (i, &token) in tokens.iter().enumerate() {
+        match token {
+            "depth" => limits.depth = value(i).map(|v| v.max(1) as u32),
+            "movetime" => limits.movetime = millis(i),
+            "wtime" => limits.wtime = millis(i),
+            "btime" => limits.btime = millis(i),
+            "winc" => limits.winc = millis(i),
+            "binc" => limits.binc = millis(i),
+            "infinite" => limits.infinite = true,
+            _ => {},
+        }
+    }
+    limits
+}
+
+fn format_info(info: &SearchInfo) -> String {
+    let score = match info.score {
+        Score::Centipawns(cp) => format!("cp {}", cp),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let millis = info.time.as_millis().max(1);
+    let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_uci()).collect();
+    format!("info depth {} score {} nodes {} nps {} time {} pv {}",
+        info.depth, score, info.nodes, info.nodes as u128 * 1000 / millis, info.time.as_millis(), pv.join(" "))
+}
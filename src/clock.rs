@@ -0,0 +1,322 @@
+//! Chess clocks for timed games: the remaining time of both sides, increments and delays and time controls with
+//! several stages, e.g. 40 moves in 90 minutes followed by 30 minutes for the rest of the game.
+
+use crate::core::Color;
+use crate::game::{Game, GameResult, Move, Termination};
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where the clock gets the current time from. Only differences between two readings matter.
+pub trait TimeSource {
+    fn now(&self) -> Duration;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicTime {
+    start: Instant,
+}
+
+/// A time source which only moves when told to, for tests and replaying games. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualTime {
+    now: Arc<Mutex<Duration>>,
+}
+
+/// How a stage of a time control compensates for the time used on a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timing {
+    /// No compensation, sudden death.
+    None,
+    /// Fischer: the increment is added after every move.
+    Increment(Duration),
+    /// Bronstein: the time used is given back after the move, up to the delay.
+    Bronstein(Duration),
+    /// Simple or US delay: the clock only starts counting down once the delay has passed.
+    SimpleDelay(Duration),
+}
+
+/// A period of a time control. `moves` is the number of moves to be made in the stage, `None` for the rest of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stage {
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub timing: Timing,
+}
+
+/// The stages of a time control. Once a stage with a move count is completed, the time of the next stage is added.
+/// The last stage repeats if it has a move count itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Side {
+    remaining: Duration,
+    stage: usize,
+    moves_in_stage: u32,
+}
+
+/// A chess clock for two players. Pressing the clock ends the turn of the side to move and starts the opponent's time.
+pub struct Clock {
+    control: TimeControl,
+    sides: [Side; 2], // White, Black
+    turn: Color,
+    turn_started: Option<Duration>, // None while the clock is stopped
+    turn_used: Duration, // the time used on the current move before the clock was last stopped
+    flagged: Option<Color>,
+    source: Box<dyn TimeSource + Send>,
+}
+
+impl MonotonicTime {
+
+    pub fn new() -> Self {
+        MonotonicTime { start: Instant::now() }
+    }
+
+}
+
+impl Default for MonotonicTime {
+    fn default() -> Self {
+        MonotonicTime::new()
+    }
+}
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl ManualTime {
+
+    pub fn new() -> Self {
+        ManualTime::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+impl Stage {
+
+    /// A stage without increment or delay.
+    pub fn new(moves: Option<u32>, time: Duration) -> Self {
+        Stage { moves, time, timing: Timing::None }
+    }
+
+    pub fn with_timing(self, timing: Timing) -> Self {
+        Stage { timing, ..self }
+    }
+
+}
+
+impl TimeControl {
+
+    /// A single stage for the whole game with a Fischer increment, e.g. 5 minutes plus 3 seconds per move.
+    pub fn new(time: Duration, increment: Duration) -> Self {
+        let timing = if increment.is_zero() { Timing::None } else { Timing::Increment(increment) };
+        TimeControl { stages: vec! [Stage::new(None, time).with_timing(timing)] }
+    }
+
+    /// Parses the format of the PGN `TimeControl` tag: stages separated by colons, each made of an optional move count,
+    /// the time in seconds and an optional increment in seconds, e.g. `40/5400+30:1800+30` for 40 moves in 90 minutes
+    /// followed by 30 minutes for the rest of the game, with 30 seconds added per move from the first move on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let control = rust_chess::clock::TimeControl::from_pgn("300+2").unwrap();
+    /// assert_eq!(std::time::Duration::from_secs(300), control.stages[0].time);
+    /// ```
+    pub fn from_pgn(tag: &str) -> Result<Self, &str> {
+        let mut stages = vec! [];
+        for field in tag.trim().split(':') {
+            let (moves, rest) = match field.split_once('/') {
+                Some((moves, rest)) => (Some(moves.parse::<u32>().map_err(|_| "Invalid time control")?), rest),
+                None => (None, field),
+            };
+            let (time, increment) = match rest.split_once('+') {
+                Some((time, increment)) => (time, increment.parse::<u64>().map_err(|_| "Invalid time control")?),
+                None => (rest, 0),
+            };
+            let time = time.parse::<u64>().map_err(|_| "Invalid time control")?;
+            let timing = if increment == 0 { Timing::None } else { Timing::Increment(Duration::from_secs(increment)) };
+            stages.push(Stage::new(moves, Duration::from_secs(time)).with_timing(timing));
+        }
+        Ok(TimeControl { stages })
+    }
+
+}
+
+impl Clock {
+
+    /// A stopped clock using the system time, White to move.
+    pub fn new(control: TimeControl) -> Self {
+        Clock::with_time_source(control, MonotonicTime::new())
+    }
+
+    pub fn with_time_source(control: TimeControl, source: impl TimeSource + Send + 'static) -> Self {
+        assert!(!control.stages.is_empty(), "a time control needs at least one stage");
+        let side = Side { remaining: control.stages[0].time, stage: 0, moves_in_stage: 0 };
+        Clock { control, sides: [side, side], turn: Color::White, turn_started: None,
+            turn_used: Duration::ZERO, flagged: None, source: Box::new(source) }
+    }
+
+    /// Starts or resumes the time of the side to move.
+    pub fn start(&mut self) {
+        if self.turn_started.is_none() && self.flagged.is_none() {
+            self.turn_started = Some(self.source.now());
+        }
+    }
+
+    /// Stops the clock without ending the turn, e.g. to adjourn a game. The time used so far stays on the move.
+    pub fn stop(&mut self) {
+        self.turn_used = self.elapsed();
+        self.turn_started = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.turn_started.is_some()
+    }
+
+    /// The side whose time is running, or would be once the clock is started.
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// The time left for the color, including the time used on the current move.
+    pub fn remaining(&self, color: Color) -> Duration {
+        let side = self.side(color);
+        if color != self.turn {
+            return side.remaining;
+        }
+        let charged = match self.timing(color) {
+            Timing::SimpleDelay(delay) => self.elapsed().saturating_sub(delay),
+            _ => self.elapsed(),
+        };
+        side.remaining.saturating_sub(charged)
+    }
+
+    /// The color which ran out of time, if any.
+    pub fn flagged(&self) -> Option<Color> {
+        match self.flagged {
+            Some(color) => Some(color),
+            None if self.remaining(self.turn).is_zero() => Some(self.turn),
+            None => None,
+        }
+    }
+
+    /// Ends the turn of the side to move and starts the opponent's time. Returns the time used on the move.
+    /// Nothing happens once a side has run out of time.
+    pub fn press(&mut self) -> Duration {
+        if self.flagged().is_some() {
+            self.flagged = self.flagged();
+            self.turn_started = None;
+            return Duration::ZERO;
+        }
+
+        let elapsed = self.elapsed();
+        let remaining = self.remaining(self.turn);
+        let timing = self.timing(self.turn);
+        let stages = &self.control.stages;
+        let side = &mut self.sides[index(self.turn)];
+        side.remaining = match timing {
+            Timing::Increment(increment) => remaining + increment,
+            Timing::Bronstein(delay) => remaining + elapsed.min(delay),
+            Timing::None | Timing::SimpleDelay(_) => remaining,
+        };
+
+        // the time of the next stage is added once the moves of the current one are made
+        side.moves_in_stage += 1;
+        if stages[side.stage].moves == Some(side.moves_in_stage) {
+            side.stage = (side.stage + 1).min(stages.len() - 1);
+            side.moves_in_stage = 0;
+            side.remaining += stages[side.stage].time;
+        }
+
+        self.turn = self.turn.opposite();
+        self.turn_started = Some(self.source.now());
+        self.turn_used = Duration::ZERO;
+        elapsed
+    }
+
+    /// Presses the clock for a move and plays it. The time left and the time used are recorded in the annotations
+    /// of the move. If the side to move has run out of time, the move is not played and the game ends on time instead.
+    /// Once the game is over no more moves are played and the clock stops. Returns the result of the game, if any.
+    pub fn make_move(&mut self, game: &mut Game, move_: Move) -> Option<GameResult> {
+        if game.result().is_some() {
+            self.stop();
+            return game.result();
+        }
+        if self.flagged().is_some() {
+            return self.apply_flag(game);
+        }
+        let color = self.turn;
+        let elapsed = self.press();
+        game.make_move(move_);
+        let meta = game.move_meta_mut();
+        meta.clock = Some(self.remaining(color));
+        meta.elapsed = Some(elapsed);
+        let result = game.update_result();
+        if result.is_some() {
+            self.stop();
+        }
+        result
+    }
+
+    /// Ends the game if a side has run out of time: the opponent wins, unless it has not got the material to ever
+    /// checkmate, which makes it a draw. Returns the result of the game, if any.
+    pub fn apply_flag(&mut self, game: &mut Game) -> Option<GameResult> {
+        if let (Some(color), None) = (self.flagged(), game.result()) {
+            self.flagged = Some(color);
+            self.turn_started = None;
+            let result = if game.has_insufficient_material(color.opposite()) {
+                GameResult::Draw
+            } else if color == Color::White {
+                GameResult::BlackWin
+            } else {
+                GameResult::WhiteWin
+            };
+            game.set_result(Some(result));
+            game.set_termination(Some(Termination::TimeForfeit));
+        }
+        game.result()
+    }
+
+    /// The stage of the time control the color is in.
+    pub fn stage(&self, color: Color) -> &Stage {
+        &self.control.stages[self.side(color).stage]
+    }
+
+    fn timing(&self, color: Color) -> Timing {
+        self.stage(color).timing
+    }
+
+    // the time used on the current move so far
+    fn elapsed(&self) -> Duration {
+        self.turn_used + self.turn_started.map_or(Duration::ZERO, |started| self.source.now().saturating_sub(started))
+    }
+
+    fn side(&self, color: Color) -> &Side {
+        &self.sides[index(color)]
+    }
+
+}
+
+fn index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
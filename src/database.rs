@@ -0,0 +1,299 @@
+//! An index over the games of a PGN file, to find games by their tags, the positions reached in them and their
+//! material. The index keeps the byte offset of every game in the file, so that only the games found need to be read
+//! and parsed. It can be saved next to the PGN file and opened again without reading the games.
+//!
+//! ```
+//! use rust_chess::database::{GameQuery, PgnIndex};
+//!
+//! let pgn = "[White \"Anderssen\"]\n[Black \"Kieseritzky\"]\n[Result \"1-0\"]\n\n1. e4 e5 2. f4 exf4 1-0\n";
+//! let index = PgnIndex::build(pgn);
+//! let query = GameQuery {
+//!     fen: Some(String::from("rnbqkbnr/pppp1ppp/8/4p3/4PP2/8/PPPP2PP/RNBQKBNR b KQkq - 0 2")),
+//!     ..GameQuery::default()
+//! };
+//! let games = index.search(&query).unwrap();
+//! assert_eq!(0, games[0].offset);
+//! assert_eq!(Some("Kieseritzky"), games[0].tag("Black"));
+//! ```
+
+use crate::book::polyglot_key;
+use crate::core::FIGURE_LETTERS;
+use crate::game::Game;
+use crate::material::Material;
+use crate::parser::{parse_pgn, split_pgn};
+use crate::variant::{Standard, Variant};
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const MAGIC: [u8; 4] = *b"PGNX";
+const VERSION: u8 = 2;
+
+/// The games of a PGN file with what is known about them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnIndex {
+    games: Vec<IndexedGame>,
+}
+
+/// A game of the file: where to find it, its tags, and the positions and material signatures of its mainline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedGame {
+    /// The byte offset of the game in the file.
+    pub offset: u64,
+    /// The length of the game's text in bytes.
+    pub length: u64,
+    pub tags: Vec<(String, String)>,
+    // the Polyglot keys of the positions, sorted
+    keys: Vec<u64>,
+    // the material signatures of the positions, e.g. KRPvKR, in the order they occur
+    signatures: Vec<String>,
+}
+
+/// What to look for in the index. Games have to match all fields which are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameQuery {
+    /// A position reached in the mainline of the game, with the same side to move, castling privileges and en passant
+    /// captures. The move counters are ignored.
+    pub fen: Option<String>,
+    /// A part of the name of either player, ignoring case.
+    pub player: Option<String>,
+    /// A part of the name of the white player, ignoring case.
+    pub white: Option<String>,
+    /// A part of the name of the black player, ignoring case.
+    pub black: Option<String>,
+    /// The value of the `Result` tag, e.g. `1-0`.
+    pub result: Option<String>,
+    /// The material of a position reached in the game, e.g. `KRPvKR`, for either side. Letters of the same side may
+    /// come in any order.
+    pub material: Option<String>,
+}
+
+impl PgnIndex {
+
+    /// Indexes the games of the text of a PGN file. Games which can not be parsed are left out.
+    /// Positions are recorded for games of standard chess only, as the keys do not tell the variant.
+    pub fn build(text: &str) -> Self {
+        let games = split_pgn(text).into_iter()
+            .filter_map(|pgn| {
+                let game = parse_pgn(pgn).ok()?;
+                let offset = (pgn.as_ptr() as usize - text.as_ptr() as usize) as u64;
+                Some(IndexedGame::new(&game, offset, pgn.len() as u64))
+            })
+            .collect();
+        PgnIndex { games }
+    }
+
+    /// Indexes the games of a PGN file. Files which are not valid UTF-8, e.g. in Latin-1, are read with a `?` for each
+    /// byte which can not be decoded, so that the offsets of the games are the same as in the file.
+    pub fn build_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(PgnIndex::build(&decode(&std::fs::read(path)?)))
+    }
+
+    /// Reads an index saved by `save`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        PgnIndex::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        if reader.take(4)? != MAGIC || reader.u8()? != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a PGN index"));
+        }
+        let count = reader.u32()?;
+        let mut games = vec! [];
+        for _ in 0..count {
+            let offset = reader.u64()?;
+            let length = reader.u64()?;
+            let tags = (0..reader.u32()?).map(|_| Ok((reader.string()?, reader.string()?))).collect::<io::Result<_>>()?;
+            let keys = (0..reader.u32()?).map(|_| reader.u64()).collect::<io::Result<_>>()?;
+            let signatures = (0..reader.u32()?).map(|_| reader.string()).collect::<io::Result<_>>()?;
+            games.push(IndexedGame { offset, length, tags, keys, signatures });
+        }
+        Ok(PgnIndex { games })
+    }
+
+    /// The index in the file format: a header with the number of games, then for each game its offset and length, its
+    /// tags, the keys of its positions and its material signatures. Numbers are big-endian, counts and the lengths of
+    /// strings in bytes take 32 bits and strings follow their length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend((self.games.len() as u32).to_be_bytes());
+        let push_string = |bytes: &mut Vec<u8>, value: &str| {
+            bytes.extend((value.len() as u32).to_be_bytes());
+            bytes.extend(value.bytes());
+        };
+        for game in self.games.iter() {
+            bytes.extend(game.offset.to_be_bytes());
+            bytes.extend(game.length.to_be_bytes());
+            bytes.extend((game.tags.len() as u32).to_be_bytes());
+            for (name, value) in game.tags.iter() {
+                push_string(&mut bytes, name);
+                push_string(&mut bytes, value);
+            }
+            bytes.extend((game.keys.len() as u32).to_be_bytes());
+            for key in game.keys.iter() {
+                bytes.extend(key.to_be_bytes());
+            }
+            bytes.extend((game.signatures.len() as u32).to_be_bytes());
+            for signature in game.signatures.iter() {
+                push_string(&mut bytes, signature);
+            }
+        }
+        bytes
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn games(&self) -> &[IndexedGame] {
+        &self.games
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// The games matching the query, in the order of the file. Fails if the FEN or the material of the query are invalid.
+    pub fn search(&self, query: &GameQuery) -> Result<Vec<&IndexedGame>, String> {
+        let key = match &query.fen {
+            Some(fen) => Some(polyglot_key(&Game::from_fen(fen).map_err(|e| format!("{}: {}", fen, e))?)),
+            None => None,
+        };
+        // the signature with White's and with Black's pieces first
+        let material = match &query.material {
+            Some(material) => {
+                let (first, second) = normalize_signature(material)?;
+                Some([format!("{}v{}", first, second), format!("{}v{}", second, first)])
+            },
+            None => None,
+        };
+        let contains = |value: Option<&str>, part: &Option<String>| match part {
+            Some(part) => value.is_some_and(|value| value.to_lowercase().contains(&part.to_lowercase())),
+            None => true,
+        };
+
+        Ok(self.games.iter()
+            .filter(|game| key.is_none_or(|key| game.contains_position(key)))
+            .filter(|game| material.as_ref().is_none_or(|material| game.signatures.iter().any(|s| material.contains(s))))
+            .filter(|game| contains(game.tag("White"), &query.player) || contains(game.tag("Black"), &query.player))
+            .filter(|game| contains(game.tag("White"), &query.white) && contains(game.tag("Black"), &query.black))
+            .filter(|game| query.result.as_ref().is_none_or(|result| game.tag("Result") == Some(result)))
+            .collect())
+    }
+
+}
+
+impl IndexedGame {
+
+    fn new(game: &Game, offset: u64, length: u64) -> Self {
+        let tags = game.meta.as_ref().map_or(vec! [], |meta| meta.tags.clone());
+        let mut keys = vec! [];
+        let mut signatures: Vec<String> = vec! [];
+        if let (true, Ok(mut position)) = (game.variant().name() == Standard.name(), Game::from_fen(&game.start_fen)) {
+            for mv in game.tree().mainline().into_iter().map(Some).chain([None]) {
+                keys.push(polyglot_key(&position));
+                let signature = Material::from_board(&position.board).signature();
+                if !signatures.contains(&signature) {
+                    signatures.push(signature);
+                }
+                if let Some(mv) = mv {
+                    position.make_move(mv);
+                }
+            }
+        }
+        keys.sort_unstable();
+        keys.dedup();
+        IndexedGame { offset, length, tags, keys, signatures }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Whether the position with the Polyglot key, see `book::polyglot_key`, occurred in the mainline of the game.
+    pub fn contains_position(&self, key: u64) -> bool {
+        self.keys.binary_search(&key).is_ok()
+    }
+
+    /// The material of the positions of the mainline, e.g. `KRPvKR`, each once in the order they occur.
+    pub fn signatures(&self) -> &[String] {
+        &self.signatures
+    }
+
+    /// Reads the text of the game from the PGN file it was indexed from, decoded like in `PgnIndex::build_file`.
+    pub fn read_pgn<R: Read + Seek>(&self, file: &mut R) -> io::Result<String> {
+        let mut bytes = vec! [0; self.length as usize];
+        file.seek(SeekFrom::Start(self.offset))?;
+        file.read_exact(&mut bytes)?;
+        Ok(decode(&bytes))
+    }
+
+    /// Reads and parses the game from the PGN file it was indexed from.
+    pub fn read<R: Read + Seek>(&self, file: &mut R) -> io::Result<Game> {
+        parse_pgn(&self.read_pgn(file)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+}
+
+// the text of a PGN file with a '?' for each byte which is not valid UTF-8, which keeps the offsets of the bytes
+fn decode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        text.extend(std::iter::repeat_n('?', chunk.invalid().len()));
+    }
+    text
+}
+
+// the letters of both sides of a signature in the order of `Material::signature`
+fn normalize_signature(signature: &str) -> Result<(String, String), String> {
+    let (white, black) = signature.split_once('v').ok_or(format!("invalid material {}", signature))?;
+    let side = |letters: &str| {
+        let mut figures = letters.chars()
+            .map(|c| FIGURE_LETTERS.find(c).ok_or(format!("invalid material {}", signature)))
+            .collect::<Result<Vec<_>, _>>()?;
+        figures.sort_unstable();
+        Ok::<_, String>(figures.into_iter().map(|index| &FIGURE_LETTERS[index..index + 1]).collect::<String>())
+    };
+    Ok((side(white)?, side(black)?))
+}
+
+// reads the numbers and strings of the index file in turn
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let bytes = self.bytes.get(self.position..self.position + count)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "truncated PGN index"))?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let length = self.u32()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+}
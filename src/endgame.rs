@@ -0,0 +1,282 @@
+//! Knowledge about elementary endings, for correct play without tablebase files.
+//!
+//! King and pawn against king is decided by a bitbase which is computed by retrograde analysis the first time it is
+//! probed. A few more rules recognize the known draws and guide the mate with bishop and knight, see `adjust`.
+//!
+//! ```
+//! use rust_chess::core::Color;
+//! use rust_chess::endgame::probe_kpk;
+//! use rust_chess::parser::parse_fen;
+//! use rust_chess::tablebase::Wdl;
+//!
+//! // the defending king is in front of the pawn
+//! let board = parse_fen("8/8/4k3/8/8/4P3/4K3/8 w - - 0 1").unwrap();
+//! assert_eq!(Some(Wdl::Draw), probe_kpk(&board, Color::White));
+//! ```
+
+use crate::core::{king_distance, step, Board, Color, Figure, Piece, KING_STEPS};
+use crate::material::Material;
+use crate::tablebase::Wdl;
+
+use std::sync::OnceLock;
+
+// white to move, black to move, times the 64 squares of each king, times 24 pawn squares on the files a to d
+const KPK_SIZE: usize = 2 * 64 * 64 * 24;
+
+/// Probes king and pawn against king, with the result for the side to move: `Win` or `Loss` if the side with the
+/// pawn wins, otherwise `Draw`. Returns None if the board holds any other material.
+///
+/// The board does not know whose turn it is, so it has to be given. Underpromotions are not considered, which only
+/// matters in a handful of positions where the pawn has to become a rook to avoid stalemate.
+pub fn probe_kpk(board: &Board, to_move: Color) -> Option<Wdl> {
+    let pawn = kpk_pawn(board)?;
+    let strong = pawn.color;
+    let (Some(strong_king), Some(weak_king)) = (board.king(strong), board.king(strong.opposite())) else {
+        return None;
+    };
+
+    // look at the position as if White had the pawn on one of the files a to d
+    let normalize = |piece: &Piece| {
+        let rank = piece.square.rank_index();
+        let rank = if strong == Color::White { rank } else { 7 - rank };
+        let file = piece.square.file_index();
+        let file = if pawn.square.file_index() < 4 { file } else { 7 - file };
+        (file, rank)
+    };
+    let (pawn_file, pawn_rank) = normalize(&pawn);
+    if !(1..7).contains(&pawn_rank) {
+        return None;
+    }
+    let white_to_move = to_move == strong;
+    let index = kpk_index(white_to_move, square(normalize(strong_king)), square(normalize(weak_king)), square((pawn_file, pawn_rank)));
+
+    Some(match (kpk()[index / 64] >> (index % 64) & 1 == 1, white_to_move) {
+        (false, _) => Wdl::Draw,
+        (true, true) => Wdl::Win,
+        (true, false) => Wdl::Loss,
+    })
+}
+
+/// Adjusts a static evaluation in centipawns from White's point of view for the endings this module knows about, and
+/// returns it unchanged for all other positions.
+///
+/// - king and pawn against king is scored as a draw when the bitbase says so
+/// - a bishop with pawns on a single rook file can not win against a king in the corner, unless the bishop controls
+///   the promotion square
+/// - with bishop and knight against the bare king, the king is driven to a corner of the bishop's color
+/// - endings with bishops of opposite colors and pawns are drawish, and the score is halved
+pub fn adjust(board: &Board, to_move: Color, score: i32) -> i32 {
+    if kpk_pawn(board).is_some() {
+        return match probe_kpk(board, to_move) {
+            Some(Wdl::Draw) => 0,
+            _ => score,
+        };
+    }
+
+    for strong in [Color::White, Color::Black] {
+        let weak = strong.opposite();
+        if figures(board, weak).is_empty() {
+            let sign = if strong == Color::White { 1 } else { -1 };
+            if is_wrong_bishop_draw(board, strong) {
+                return 0;
+            }
+            if let Some(bonus) = bishop_knight_mate(board, strong) {
+                return sign * bonus;
+            }
+        }
+    }
+
+    if is_opposite_bishops(board) {
+        return score / 2;
+    }
+    score
+}
+
+/// The only pawn on the board, if the kings are alone with it.
+fn kpk_pawn(board: &Board) -> Option<Piece> {
+    let mut others = board.pieces.iter().filter(|p| p.figure != Figure::King);
+    match (others.next(), others.next(), board.pieces.len()) {
+        (Some(pawn), None, 3) if pawn.figure == Figure::Pawn => Some(*pawn),
+        _ => None,
+    }
+}
+
+/// The figures of one side besides the king.
+fn figures(board: &Board, color: Color) -> Vec<Figure> {
+    board.pieces.iter().filter(|p| p.color == color && p.figure != Figure::King).map(|p| p.figure).collect()
+}
+
+fn is_wrong_bishop_draw(board: &Board, strong: Color) -> bool {
+    let pieces: Vec<&Piece> = board.pieces.iter().filter(|p| p.color == strong && p.figure != Figure::King).collect();
+    let pawns: Vec<&Piece> = pieces.iter().copied().filter(|p| p.figure == Figure::Pawn).collect();
+    let Some(first) = pawns.first() else {
+        return false;
+    };
+    let file = first.square.file_index();
+    if !pieces.iter().all(|p| matches!(p.figure, Figure::Pawn | Figure::Bishop)) || pieces.len() == pawns.len()
+        || (file != 0 && file != 7)
+        || !pawns.iter().all(|p| p.square.file_index() == file) {
+        return false;
+    }
+
+    let promotion = (file, if strong == Color::White { 7 } else { 0 });
+    let promotion_color = (promotion.0 + promotion.1) % 2;
+    let Some(weak_king) = board.king(strong.opposite()) else {
+        return false;
+    };
+    pieces.iter().filter(|p| p.figure == Figure::Bishop).all(|b| light(b) != promotion_color)
+        && distance(coordinates(weak_king), promotion) <= 1
+}
+
+fn bishop_knight_mate(board: &Board, strong: Color) -> Option<i32> {
+    let mut figures = figures(board, strong);
+    figures.sort_by_key(|f| *f as u8);
+    if figures != [Figure::Knight, Figure::Bishop] {
+        return None;
+    }
+    let bishop = board.pieces.iter().find(|p| p.color == strong && p.figure == Figure::Bishop)?;
+    let strong_king = coordinates(board.king(strong)?);
+    let weak_king = coordinates(board.king(strong.opposite())?);
+
+    // a1 and h8 are dark, a8 and h1 light
+    let corners = if light(bishop) == 0 { [(0, 0), (7, 7)] } else { [(0, 7), (7, 0)] };
+    let to_corner = corners.iter()
+        .map(|&(file, rank): &(u8, u8)| file.abs_diff(weak_king.0) + rank.abs_diff(weak_king.1))
+        .min().unwrap_or(0) as i32;
+    // the mate is easier to find once the king is on the edge
+    let edge = ((2 * weak_king.0 as i32 - 7).abs().max((2 * weak_king.1 as i32 - 7).abs())) / 2;
+    let closeness = 7 - distance(strong_king, weak_king) as i32;
+    Some(crate::search::figure_value(Figure::Bishop) + crate::search::figure_value(Figure::Knight)
+        + 20 * (14 - to_corner) + 10 * edge + 10 * closeness)
+}
+
+fn is_opposite_bishops(board: &Board) -> bool {
+    let material = Material::from_board(board);
+    material.has_opposite_colored_bishops() && [Color::White, Color::Black].iter()
+        .all(|&color| [Figure::Knight, Figure::Rook, Figure::Queen].iter().all(|&figure| material.count(color, figure) == 0))
+}
+
+/// 1 for a bishop on a light square, 0 on a dark one.
+fn light(piece: &Piece) -> u8 {
+    (piece.square.file_index() + piece.square.rank_index()) % 2
+}
+
+fn coordinates(piece: &Piece) -> (u8, u8) {
+    (piece.square.file_index(), piece.square.rank_index())
+}
+
+fn distance(a: (u8, u8), b: (u8, u8)) -> u8 {
+    a.0.abs_diff(b.0).max(a.1.abs_diff(b.1))
+}
+
+fn square((file, rank): (u8, u8)) -> usize {
+    rank as usize * 8 + file as usize
+}
+
+fn kpk_index(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> usize {
+    // the pawn is on one of the files a to d and the ranks 2 to 7
+    let pawn = (pawn / 8 - 1) * 4 + pawn % 8;
+    ((pawn * 2 + white_to_move as usize) * 64 + black_king) * 64 + white_king
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kpk { Invalid, Unknown, Draw, Win }
+
+/// The bitbase with one bit per position, set if White wins.
+fn kpk() -> &'static [u64] {
+    static KPK: OnceLock<Vec<u64>> = OnceLock::new();
+    KPK.get_or_init(|| {
+        let mut results = vec! [Kpk::Invalid; KPK_SIZE];
+        let mut positions = vec! [];
+        for pawn in (8..56).filter(|sq| sq % 8 < 4) {
+            for black_king in 0..64 {
+                for white_king in 0..64 {
+                    for white_to_move in [true, false] {
+                        let index = kpk_index(white_to_move, white_king, black_king, pawn);
+                        results[index] = kpk_initial(white_to_move, white_king, black_king, pawn);
+                        if results[index] == Kpk::Unknown {
+                            positions.push((index, white_to_move, white_king, black_king, pawn));
+                        }
+                    }
+                }
+            }
+        }
+
+        // positions are decided from the ones a move away, until nothing changes anymore
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &(index, white_to_move, white_king, black_king, pawn) in positions.iter() {
+                if results[index] == Kpk::Unknown {
+                    results[index] = kpk_classify(&results, white_to_move, white_king, black_king, pawn);
+                    changed |= results[index] != Kpk::Unknown;
+                }
+            }
+        }
+
+        let mut bits = vec! [0u64; KPK_SIZE / 64];
+        for (index, result) in results.iter().enumerate() {
+            if *result == Kpk::Win {
+                bits[index / 64] |= 1 << (index % 64);
+            }
+        }
+        bits
+    })
+}
+
+fn pawn_attacks(pawn: usize, square: usize) -> bool {
+    [(-1, 1), (1, 1)].iter().any(|&d| step(pawn, d) == Some(square))
+}
+
+fn kpk_initial(white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> Kpk {
+    if king_distance(white_king, black_king) <= 1 || white_king == pawn || black_king == pawn
+        || (white_to_move && pawn_attacks(pawn, black_king)) {
+        return Kpk::Invalid;
+    }
+
+    let push = pawn + 8;
+    if white_to_move && pawn / 8 == 6 && white_king != push && black_king != push
+        && (king_distance(black_king, push) > 1 || king_distance(white_king, push) == 1) {
+        // the pawn promotes and the queen can not be taken
+        return Kpk::Win;
+    }
+    if !white_to_move {
+        let escapes = KING_STEPS.iter().filter_map(|&d| step(black_king, d))
+            .any(|sq| king_distance(sq, white_king) > 1 && !pawn_attacks(pawn, sq));
+        let takes_pawn = king_distance(black_king, pawn) == 1 && king_distance(white_king, pawn) > 1;
+        if !escapes || takes_pawn {
+            return Kpk::Draw;
+        }
+    }
+    Kpk::Unknown
+}
+
+fn kpk_classify(results: &[Kpk], white_to_move: bool, white_king: usize, black_king: usize, pawn: usize) -> Kpk {
+    // White wins with any winning move, Black draws with any drawing move
+    let (good, bad) = if white_to_move { (Kpk::Win, Kpk::Draw) } else { (Kpk::Draw, Kpk::Win) };
+    let mut successors = vec! [];
+    if white_to_move {
+        for sq in KING_STEPS.iter().filter_map(|&d| step(white_king, d)) {
+            successors.push(results[kpk_index(false, sq, black_king, pawn)]);
+        }
+        let push = pawn + 8;
+        if pawn / 8 < 6 && push != white_king && push != black_king {
+            successors.push(results[kpk_index(false, white_king, black_king, push)]);
+            if pawn / 8 == 1 && push + 8 != white_king && push + 8 != black_king {
+                successors.push(results[kpk_index(false, white_king, black_king, push + 8)]);
+            }
+        }
+    } else {
+        for sq in KING_STEPS.iter().filter_map(|&d| step(black_king, d)) {
+            successors.push(results[kpk_index(true, white_king, sq, pawn)]);
+        }
+    }
+
+    if successors.contains(&good) {
+        good
+    } else if successors.contains(&Kpk::Unknown) {
+        Kpk::Unknown
+    } else {
+        bad
+    }
+}
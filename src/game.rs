@@ -1,8 +1,14 @@
 use crate::core::{Board, Piece, Color, Color::*, Square, Figure};
+use crate::parser::{parse_fen, export_fen_position};
+use crate::search::Score;
+use crate::variant::{Variant, Standard, Chess960, Crazyhouse};
 
 use std::collections::hash_set::HashSet;
-use std::fmt::Display;
-use itertools::Itertools;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const STANDARD_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+pub const CRAZYHOUSE_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1";
 
 pub struct GameBuilder {
     // ?
@@ -13,7 +19,11 @@ pub struct Game{
     pub meta: Option<GameMeta>,
     pub board: Board,
     pub state: State,
-    pub moves: Vec<Move>,
+    pub start_fen: String, // the position before the first move, needed to replay the moves
+    undo: Vec<Undo>, // for each of the moves leading to the current position, to take them back without replaying the game
+    tree: GameTree,
+    cursor: NodeId,
+    variant: Arc<dyn Variant>, // the rules the game is played by
 }
 
 #[derive(Clone, Eq, PartialEq)]
@@ -21,38 +31,129 @@ pub struct State {
     // en-passant, castling etc is all done via the game, not the pieces or board as it requires knowledge about game state
     turn: Color, // the color to make the next move
     result: Option<GameResult>,
-    check: bool, 
-    castling_privileges: HashSet<(Piece, Piece)>, 
+    termination: Option<Termination>, // why the game ended, if the rules tell
+    check: bool,
+    castling_privileges: HashSet<(Piece, Piece)>,
     possible_en_passant_moves: HashSet<(Piece, Square)>, // the pawn to take en-passant and the target square
+    halfmove_clock: u32, // half moves since the last capture or pawn move, used for the fifty-move rule
+    fullmove_number: u32,
+    pockets: Option<[Pocket; 2]>, // the pieces White and Black can drop in Crazyhouse, None in other games
+    promoted: HashSet<Square>, // the squares of promoted pieces, they turn back into pawns when captured in Crazyhouse
+    checks: Option<[u8; 2]>, // the checks given by White and Black in Three-check, None in other games
 }
 
-#[derive(Clone, Eq, PartialEq)]
+// what it takes to take back a move: the state before it and the pieces it changed on the board
+#[derive(Clone)]
+struct Undo {
+    state: State,
+    removed: Vec<Piece>, // the pieces the move took off the board, the moving piece on its old square included
+    added: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum GameResult { WhiteWin, BlackWin, Draw }
 
-#[derive(Clone, Eq, PartialEq)]
+/// The reason a game ended.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    TimeForfeit,
+    /// The third check was given in Three-check.
+    ThirdCheck,
+    /// A king reached the center in King of the Hill.
+    KingInCenter,
+    /// A king was blown up in Atomic chess.
+    KingExploded,
+    /// A side has lost all its pieces, which wins in Antichess and loses for White in Horde.
+    NoPiecesLeft,
+    /// A king reached the eighth rank in Racing Kings.
+    KingReachedGoal,
+    /// Any other rule of a variant.
+    VariantRule,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct GameMeta {
     // player info, year, place, tournament, player ratings etc etc
+    pub tags: Vec<(String, String)>, // PGN tag pairs in their original order
+}
+
+/// Index of a node in the `GameTree`.
+pub type NodeId = usize;
+
+/// All moves played or analysed in a game. The tree starts with the root node for the starting position,
+/// every other node holds the move which leads to it from its parent.
+#[derive(Debug, Clone)]
+pub struct GameTree {
+    nodes: Vec<Option<GameTreeNode>>, // None for deleted nodes
 }
 
+#[derive(Debug, Clone)]
 pub struct GameTreeNode {
-    
+    pub move_: Option<Move>, // None for the root
+    pub meta: MoveMeta, // for the root, comments and evaluation refer to the starting position
+    pub parent: Option<NodeId>,
+    pub mainline: Option<NodeId>, // the main continuation
+    pub variations: Vec<Variation>, // alternatives to the main continuation
 }
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub struct Move {
     piece: Piece,
     target_square: Square,
-    // variations: Vec<Variation>
+    flag: Option<MoveMetaFlag>,
 }
 
+/// Annotations of a move, as found in PGN comments and Numeric Annotation Glyphs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MoveMeta {
-    // flags, move time, comments, engine evaluation etc
+    pub comment_before: Option<String>, // PGN keeps these only for the first move of a variation
+    pub comment_after: Option<String>,
+    pub nags: Vec<u8>, // Numeric Annotation Glyphs, e.g. 1 for '!' or 14 for '+='
+    pub clock: Option<Duration>, // remaining time after the move, [%clk]
+    pub elapsed: Option<Duration>, // time spent on the move, [%emt]
+    pub evaluation: Option<Score>, // engine evaluation from White's point of view, [%eval]
+    pub depth: Option<u32>, // search depth of the evaluation
+    pub arrows: Vec<Arrow>, // [%cal]
+    pub highlights: Vec<Highlight>, // [%csl]
+}
+
+/// The colors of arrows and square highlights, written as their initial letter in PGN comments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkColor { Green, Red, Yellow, Blue }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    pub color: MarkColor,
+    pub from: Square,
+    pub to: Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Highlight {
+    pub color: MarkColor,
+    pub square: Square,
 }
 
-pub enum MoveMetaFlag {EnPassant, Castling, Promotion}
+/// Special moves which need more than moving a piece to its target square.
+/// `EnPassant` holds the captured pawn, `Castling` the rook the king castles with.
+/// `Drop` puts a piece from the pocket on the board in Crazyhouse, the moving piece already stands on the target square.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum MoveMetaFlag {EnPassant(Piece), Castling(Piece), Promotion(Figure), Drop}
+
+/// The captured pieces a side holds in Crazyhouse, ready to be dropped on the board.
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
+pub struct Pocket {
+    counts: [u8; 5], // pawns, knights, bishops, rooks, queens
+}
 
+/// An alternative to the mainline continuation of a node, starting with the move of node `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Variation {
-    
+    pub start: NodeId,
 }
 
 impl Game {
@@ -62,115 +163,915 @@ impl Game {
         let board = Board::new_in_standard_position();
         let state = State::new(&board);
         let meta = GameMeta::new();
-        let moves: Vec<Move> = Vec::new();
+        let start_fen = String::from(STANDARD_FEN);
+
+        Game {board, state, meta: Some(meta), start_fen, undo: Vec::new(), tree: GameTree::new(), cursor: GameTree::ROOT,
+            variant: Arc::new(Standard)}
+    }
 
-        Game {board: board, state: state, meta: Some(meta), moves: moves}
+    /// Creates a game in the starting position of the variant, played by its rules.
+    pub fn with_variant(variant: Arc<dyn Variant>) -> Self {
+        let fen = variant.start_fen();
+        Game::from_fen_with_variant(&fen, variant).expect("the starting position of a variant has to be a valid Fen record")
     }
 
+    /// The rules the game is played by.
+    pub fn variant(&self) -> &dyn Variant {
+        self.variant.as_ref()
+    }
+
+    /// Creates a Crazyhouse game, where captured pieces go to the capturing side's pocket and can be dropped back on the board.
+    pub fn new_crazyhouse() -> Self {
+        Game::with_variant(Arc::new(Crazyhouse))
+    }
+
+    /// Creates a Chess960 game from the starting position with the given Scharnagl index, see `Board::new_chess960`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let game = rust_chess::game::Game::new_chess960(518).unwrap();
+    /// assert_eq!(rust_chess::game::STANDARD_FEN, game.to_fen());
+    /// ```
+    pub fn new_chess960(index: u16) -> Option<Self> {
+        Board::new_chess960(index)?;
+        Some(Game::with_variant(Arc::new(Chess960 { index })))
+    }
+
+    /// Creates a game from a Fen record, including side to move, castling privileges, en-passant square and move counters.
+    /// Missing trailing fields fall back to their values in the standard starting position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let game = rust_chess::game::Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    /// assert_eq!(rust_chess::core::Color::Black, game.current_color());
+    /// ```
+    /// A Fen record with pockets, e.g. `[Qp]`, starts a Crazyhouse game, otherwise the standard rules apply.
+    pub fn from_fen(fen: &str) -> Result<Self, &str> {
+        let variant: Arc<dyn Variant> = match fen.split_whitespace().next().map(split_pockets) {
+            Some(Ok((_, Some(_)))) => Arc::new(Crazyhouse),
+            _ => Arc::new(Standard),
+        };
+        Game::from_fen_with_variant(fen, variant)
+    }
+
+    /// Creates a game of the variant from a Fen record.
+    pub fn from_fen_with_variant(fen: &str, variant: Arc<dyn Variant>) -> Result<Self, &str> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let (placement, mut pockets) = match fields.first() {
+            Some(placement) => split_pockets(placement)?,
+            None => return Err("FEN Parsing Error"),
+        };
+        if pockets.is_none() && variant.name() == Crazyhouse.name() {
+            pockets = Some([Pocket::default(), Pocket::default()]);
+        }
+        // promoted pieces are marked with a tilde, which only makes sense with pockets to return them to as pawns
+        let promoted = promoted_squares(&placement)?;
+        if !promoted.is_empty() && pockets.is_none() {
+            return Err("FEN Parsing Error");
+        }
+        let board = parse_fen(&placement.replace('~', "")).map_err(|_| "FEN Parsing Error")?;
+
+        let turn = match fields.get(1) {
+            None | Some(&"w") => White,
+            Some(&"b") => Black,
+            _ => return Err("FEN Parsing Error"),
+        };
+
+        let mut castling_privileges: HashSet<(Piece, Piece)> = HashSet::new();
+        for c in fields.get(2).unwrap_or(&"-").chars().filter(|&c| c != '-') {
+            let color = if c.is_ascii_uppercase() { White } else { Black };
+            let king = match board.king(color) {
+                Some(king) => *king,
+                None => return Err("FEN Parsing Error"),
+            };
+            // the outermost rook on the king's rank on the respective side
+            let rooks = board.pieces.iter()
+                .filter(|p| p.color == color && p.figure == Figure::Rook && p.square.pos.1 == king.square.pos.1);
+            // Shredder-FEN names the file of the rook, so does X-FEN if it is not the outermost one
+            let rook = match c.to_ascii_lowercase() {
+                'k' => rooks.filter(|r| r.square.pos.0 > king.square.pos.0).max_by_key(|r| r.square.pos.0),
+                'q' => rooks.filter(|r| r.square.pos.0 < king.square.pos.0).min_by_key(|r| r.square.pos.0),
+                file @ 'a'..='h' => rooks.filter(|r| r.square.pos.0 == file).min_by_key(|r| r.square.pos.0),
+                _ => return Err("FEN Parsing Error"),
+            };
+            match rook {
+                Some(rook) => castling_privileges.insert((king, *rook)),
+                None => return Err("FEN Parsing Error"),
+            };
+        }
+
+        let mut possible_en_passant_moves: HashSet<(Piece, Square)> = HashSet::new();
+        match fields.get(3) {
+            None | Some(&"-") => {},
+            Some(field) => {
+                let target = match board.get(field) {
+                    Some(square) => *square,
+                    None => return Err("FEN Parsing Error"),
+                };
+                let direction = if turn == White { -1 } else { 1 };
+                if let Some(pawn_square) = target.move_by((0, direction)) {
+                    possible_en_passant_moves = Game::en_passant_captures(&board, &pawn_square, target, turn);
+                }
+            }
+        }
+
+        let halfmove_clock = match fields.get(4) {
+            Some(field) => field.parse::<u32>().map_err(|_| "FEN Parsing Error")?,
+            None => 0,
+        };
+        let fullmove_number = match fields.get(5) {
+            Some(field) => field.parse::<u32>().map_err(|_| "FEN Parsing Error")?,
+            None => 1,
+        };
+        // the checks given so far in Three-check, e.g. +1+0 after White gave a check
+        let checks = match fields.get(6).map(|field| field.strip_prefix('+').and_then(|f| f.split_once('+'))) {
+            Some(Some((white, black))) => Some([white.parse::<u8>().map_err(|_| "FEN Parsing Error")?,
+                black.parse::<u8>().map_err(|_| "FEN Parsing Error")?]),
+            Some(None) => return Err("FEN Parsing Error"),
+            None => variant.check_limit().map(|_| [0, 0]),
+        };
+
+        let state = State {
+            turn,
+            result: None,
+            termination: None,
+            check: false,
+            castling_privileges,
+            possible_en_passant_moves,
+            halfmove_clock,
+            fullmove_number,
+            pockets,
+            promoted,
+            checks,
+        };
+        let mut game = Game {board, state, meta: Some(GameMeta::new()), start_fen: String::new(), undo: Vec::new(),
+            tree: GameTree::new(), cursor: GameTree::ROOT, variant};
+        if game.board.king(turn).is_some() {
+            game.state.check = game.in_check();
+        }
+        game.start_fen = game.to_fen();
+        Ok(game)
+    }
+
+    /// Exports the current position as a Fen record. Castling privileges are written as in X-FEN:
+    /// `KQkq` for the outermost rooks and the file of the rook otherwise, which only happens in Chess960.
+    pub fn to_fen(&self) -> String {
+        self.fen_with(false)
+    }
+
+    /// Exports the current position as a Shredder-FEN record, where castling privileges name the files of the rooks, e.g. `HAha`.
+    pub fn to_shredder_fen(&self) -> String {
+        self.fen_with(true)
+    }
+
+    fn fen_with(&self, rook_files: bool) -> String {
+        let turn = match self.state.turn {
+            White => "w",
+            Black => "b",
+        };
+
+        let mut castling: Vec<((bool, bool), char)> = self.state.castling_privileges.iter()
+            .map(|(king, rook)| {
+                let kingside = rook.square.pos.0 > king.square.pos.0;
+                // is there another rook further out on the same side?
+                let outermost = !self.board.pieces.iter().any(|p| p.color == rook.color && p.figure == Figure::Rook
+                    && p.square.pos.1 == rook.square.pos.1
+                    && if kingside { p.square.pos.0 > rook.square.pos.0 } else { p.square.pos.0 < rook.square.pos.0 });
+                let c = match (rook_files || !outermost, kingside) {
+                    (true, _) => rook.square.pos.0,
+                    (false, true) => 'k',
+                    (false, false) => 'q',
+                };
+                let c = match king.color {
+                    White => c.to_ascii_uppercase(),
+                    Black => c,
+                };
+                // White before Black, kingside before queenside
+                ((king.color == Black, !kingside), c)
+            })
+            .collect();
+        castling.sort();
+        castling.dedup();
+        let castling = if castling.is_empty() { String::from("-") } else { castling.iter().map(|(_, c)| c).collect() };
+
+        let en_passant = match self.state.possible_en_passant_moves.iter().next() {
+            Some((_, square)) => square.to_string(),
+            None => String::from("-"),
+        };
+
+        let mut placement = export_fen_position(&self.board);
+        if let Some(pockets) = &self.state.pockets {
+            // promoted pieces are marked with a tilde, e.g. Q~
+            let mut marked = String::new();
+            let mut square = (0u8, 7u8);
+            for c in placement.chars() {
+                marked.push(c);
+                match c {
+                    '/' => square = (0, square.1 - 1),
+                    '1'..='8' => square.0 += c as u8 - b'0',
+                    _ => {
+                        if self.state.promoted.contains(&Square { pos: ((b'a' + square.0) as char, (b'1' + square.1) as char) }) {
+                            marked.push('~');
+                        }
+                        square.0 += 1;
+                    }
+                }
+            }
+            placement = format!("{}[{}{}]", marked, pockets[0].to_fen(White), pockets[1].to_fen(Black));
+        }
+
+        let checks = match self.state.checks {
+            Some([white, black]) => format!(" +{}+{}", white, black),
+            None => String::new(),
+        };
+        format!("{} {} {} {} {} {}{}", placement, turn, castling, en_passant,
+            self.state.halfmove_clock, self.state.fullmove_number, checks)
+    }
+
+    /// Plays a move from the current position. If the move is already a continuation in the tree, the cursor follows it,
+    /// otherwise it is added as the mainline or, if there is one already, as a new variation.
     pub fn make_move(&mut self, move_: Move) -> &mut Self {
-        // check move validity? what to do if move invalid or board game in finished state? 
-
-        // promotion? castles? 
-        // in the case for a promotion it is enough to replace the move_.piece by the desired promotion and proceed as usual 
-        
-        
-        // remove pieces from source and target square and add moving piece 
-        // this also works for non-capturing moves (where there is no piece on the target square)
-        // works by default for en-passant 
-        self.board
-            .remove_piece_by_square(&move_.piece.square)
-            .remove_piece_by_square(&move_.target_square)
-            .add_piece( Piece {square: move_.target_square, ..move_.piece} );
-      
+        // check move validity? what to do if move invalid or board game in finished state?
+        let node = match self.tree.find_child(self.cursor, &move_) {
+            Some(node) => node,
+            None => self.tree.add_child(self.cursor, move_),
+        };
+        self.step_into(node);
+
+        self
+    }
+
+    // updates board and state for the move, without touching the tree
+    fn play(&mut self, move_: Move) {
+        // in Chess960 the king or rook may already stand on their target squares when castling
+        let captured = match move_.flag {
+            Some(MoveMetaFlag::EnPassant(pawn)) => Some(pawn),
+            Some(MoveMetaFlag::Castling(_)) | Some(MoveMetaFlag::Drop) => None,
+            _ => self.board.check_square_for_piece(&move_.target_square).copied(),
+        };
+        let is_capture = captured.is_some();
+        self.variant.apply_move(&mut self.board, &move_);
+
+        if let Some(pockets) = &mut self.state.pockets {
+            let own = &mut pockets[pocket_index(move_.piece.color)];
+            if move_.flag == Some(MoveMetaFlag::Drop) {
+                own.remove(move_.piece.figure);
+            }
+            if let Some(captured) = captured {
+                // promoted pieces go back to the pocket as pawns
+                let figure = if self.state.promoted.remove(&captured.square) { Figure::Pawn } else { captured.figure };
+                own.add(figure);
+            }
+            if self.state.promoted.remove(&move_.piece.square) || move_.promotion().is_some() {
+                self.state.promoted.insert(move_.target_square);
+            }
+        }
+
+        // a moved king or rook loses its castling privileges, as does a captured rook or one removed by the rules of a variant
+        let board = &self.board;
+        self.state.castling_privileges
+            .retain(|(king, rook)| *king != move_.piece && *rook != move_.piece && rook.square != move_.target_square
+                && board.check_square_for_piece(&king.square) == Some(king) && board.check_square_for_piece(&rook.square) == Some(rook));
+
+        // a pawn which moved two squares can be taken en-passant by the opponent's next move
+        self.state.possible_en_passant_moves.clear();
+        if move_.piece.figure == Figure::Pawn && move_.piece.square.rank_index().abs_diff(move_.target_square.rank_index()) == 2 {
+            let direction = if move_.piece.color == White { 1 } else { -1 };
+            let passed_square = move_.piece.square.move_by((0, direction)).unwrap();
+            self.state.possible_en_passant_moves = Game::en_passant_captures(
+                &self.board, &move_.target_square, passed_square, move_.piece.color.opposite());
+        }
+
+        if is_capture || move_.piece.figure == Figure::Pawn {
+            self.state.halfmove_clock = 0;
+        } else {
+            self.state.halfmove_clock += 1;
+        }
+        if move_.piece.color == Black {
+            self.state.fullmove_number += 1;
+        }
 
         // recalculate checks, privileges etc
         self.state.turn = self.next_color();
-        self.state.check = self.in_check();
-        self.moves.push(move_);
+        self.state.check = self.board.king(self.state.turn).is_some() && self.in_check();
+        if let (true, Some(checks)) = (self.state.check, self.state.checks.as_mut()) {
+            checks[pocket_index(move_.piece.color)] += 1;
+        }
+    }
 
-        self
+    pub fn tree(&self) -> &GameTree {
+        &self.tree
+    }
+
+    /// The tree with mutable access to the annotations, its structure can only be changed through the game.
+    pub fn tree_mut(&mut self) -> &mut GameTree {
+        &mut self.tree
+    }
+
+    /// The annotations of the move leading to the current position.
+    pub fn move_meta(&self) -> &MoveMeta {
+        &self.tree.at(self.cursor).meta
+    }
+
+    pub fn move_meta_mut(&mut self) -> &mut MoveMeta {
+        &mut self.tree.at_mut(self.cursor).meta
+    }
+
+    /// The moves leading to the current position, i.e. the path from the root of the tree to the cursor.
+    pub fn moves(&self) -> Vec<Move> {
+        self.tree.moves_to(self.cursor).unwrap()
     }
 
-    pub fn simulate_move(&mut self, move_: Move) -> Self {
+    /// The node of the current position.
+    pub fn cursor(&self) -> NodeId {
+        self.cursor
+    }
+
+    /// The variations branching off at the current position, i.e. the alternatives to `forward`.
+    pub fn variations(&self) -> &[Variation] {
+        &self.tree.at(self.cursor).variations
+    }
+
+    /// Moves the cursor to any node of the tree and sets up its position.
+    /// Returns false if the node is not in the tree, e.g. because its variation was deleted.
+    pub fn go_to(&mut self, node: NodeId) -> bool {
+        if !self.tree.contains(node) {
+            return false;
+        }
+        // take back moves until the cursor is on the path to the node, then play the rest of the path
+        let path = self.tree.path_to(node);
+        while path.get(self.undo.len()) != Some(&self.cursor) {
+            self.back();
+        }
+        for &next in &path[self.undo.len() + 1..] {
+            self.step_into(next);
+        }
+        true
+    }
+
+    /// Follows the mainline by one move. Returns false at the end of the line.
+    pub fn forward(&mut self) -> bool {
+        match self.tree.at(self.cursor).mainline {
+            Some(node) => {
+                self.step_into(node);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Takes back one move. Returns false at the starting position.
+    pub fn back(&mut self) -> bool {
+        match (self.tree.at(self.cursor).parent, self.undo.pop()) {
+            (Some(parent), Some(undo)) => {
+                self.board.pieces.retain(|p| !undo.added.contains(p));
+                self.board.pieces.extend(undo.removed);
+                self.state = undo.state;
+                self.cursor = parent;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.go_to(GameTree::ROOT);
+    }
+
+    /// Follows the mainline from the current position to its end.
+    pub fn go_to_end(&mut self) {
+        while self.forward() {}
+    }
+
+    /// Plays the first move of the variation with the given index at the current position. Returns false if there is no such variation.
+    pub fn enter_variation(&mut self, index: usize) -> bool {
+        match self.variations().get(index) {
+            Some(variation) => {
+                self.step_into(variation.start);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn step_into(&mut self, node: NodeId) {
+        let move_ = self.tree.at(node).move_.unwrap();
+        let before = self.board.pieces.clone();
+        let state = self.state.clone();
+        self.play(move_);
+        let removed = before.iter().filter(|p| !self.board.pieces.contains(p)).copied().collect();
+        let added = self.board.pieces.iter().filter(|p| !before.contains(p)).copied().collect();
+        self.undo.push(Undo { state, removed, added });
+        self.cursor = node;
+    }
+
+    /// Promotes the variation the current position belongs to: it becomes the mainline at the node it branches off
+    /// and the former mainline continuation takes its place as a variation. Returns false if the position is on the mainline.
+    pub fn promote_variation(&mut self) -> bool {
+        match self.tree.variation_start(self.cursor) {
+            Some(start) => {
+                self.tree.promote(start);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Deletes the variation the current position belongs to, including all moves following it.
+    /// The cursor moves to the node the variation branched off. Returns false if the position is on the mainline.
+    pub fn delete_variation(&mut self) -> bool {
+        match self.tree.variation_start(self.cursor) {
+            Some(start) => {
+                let parent = self.tree.at(start).parent.unwrap();
+                self.go_to(parent);
+                self.tree.remove(start);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn simulate_move(&self, move_: Move) -> Self {
         // Evlaute a move on a copy of the game instance (original game is not affected)
         let mut res = self.clone();
         res.make_move(move_);
-        res 
+        res
     }
 
+    /// Generates a move from the `[start_square]-[end_square]` notation, e.g. `e2-e4`.
+    /// Promotions append the letter of the desired piece, e.g. `e7-e8q`; castling is written as the king's move, e.g. `e1-g1`.
     pub fn generate_move_from_str(&self, input: &str) -> Move {
-        let mut split = input.split("-");
+        let mut split = input.split('-');
         let start_square = split.next().unwrap();
         let end_square = split.next().unwrap();
         let piece = *self.board.check_square_for_piece(&Square::from_str(start_square)).unwrap();
-        
-        Move { piece: piece, target_square: Square::from_str(end_square)}
+        let target_square = Square::from_str(&end_square[..2]);
+        let promotion = end_square[2..].chars().next()
+            .map(|c| Piece::from_fen(c, &target_square).unwrap().figure);
+
+        // pick up castling, en-passant and promotion flags from the generated moves
+        self.get_moves(piece.color).into_iter()
+            .find(|mv| mv.piece == piece && mv.target_square == target_square && mv.promotion() == promotion)
+            .unwrap_or(Move::new(piece, target_square))
     }
 
     pub fn move_from_str(&mut self, input: &str) -> &mut Self {
         self.make_move(self.generate_move_from_str(input))
     }
 
+    /// Parses a move in UCI long algebraic notation, e.g. `e2e4` or `e7e8q`, and checks it is legal in the current position.
+    /// Castling is written as the king's move, e.g. `e1g1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut game = rust_chess::game::Game::new();
+    /// let e4 = game.parse_uci_move("e2e4").unwrap();
+    /// game.make_move(e4);
+    /// assert!(game.parse_uci_move("e2e4").is_err());
+    /// ```
+    pub fn parse_uci_move(&self, input: &str) -> Result<Move, &str> {
+        self.parse_uci_move_with(input, false)
+    }
+
+    /// Like `parse_uci_move`, with `king_takes_rook` castling may also be written as the king capturing its own rook, e.g. `e1h1`.
+    /// This is how castling is encoded for Chess960, where the king's target square alone can be ambiguous.
+    pub fn parse_uci_move_with(&self, input: &str, king_takes_rook: bool) -> Result<Move, &str> {
+        let input = input.trim();
+        if let Some((letter, square)) = input.split_once('@') {
+            let figure = match letter {
+                "P" | "N" | "B" | "R" | "Q" => figure_from_letter(letter.chars().next().unwrap()).unwrap_or(Figure::Pawn),
+                _ => return Err("Invalid UCI move notation"),
+            };
+            let square = self.board.get(square).ok_or("Invalid UCI move notation")?;
+            return self.find_drop(figure, *square);
+        }
+        if !input.is_ascii() || (input.len() != 4 && input.len() != 5) {
+            return Err("Invalid UCI move notation");
+        }
+        let (source, target) = match (self.board.get(&input[0..2]), self.board.get(&input[2..4])) {
+            (Some(source), Some(target)) => (*source, *target),
+            _ => return Err("Invalid UCI move notation"),
+        };
+        let promotion = match input[4..].chars().next() {
+            None => None,
+            Some(c @ ('q' | 'r' | 'b' | 'n' | 'k')) => match Piece::from_fen(c, &target).unwrap().figure {
+                figure if self.variant.promotion_figures().contains(&figure) => Some(figure),
+                _ => return Err("Invalid UCI move notation"),
+            },
+            Some(_) => return Err("Invalid UCI move notation"),
+        };
+
+        let legal_moves = self.get_legal_moves(self.state.turn);
+        // in Chess960 a king move can end on the same square as castling would, the plain king move takes precedence then
+        let found = legal_moves.iter()
+            .filter(|mv| mv.piece.square == source && mv.target_square == target && source != target && mv.promotion() == promotion)
+            .min_by_key(|mv| mv.is_castling());
+        if let Some(mv) = found {
+            return Ok(*mv);
+        }
+
+        // a castling move where the king stays on its square is always written as king takes rook, see `to_uci`
+        if promotion.is_none() {
+            let castling = legal_moves.iter()
+                .filter(|mv| king_takes_rook || mv.piece.square == mv.target_square)
+                .find(|mv| mv.piece.square == source && matches!(mv.flag, Some(MoveMetaFlag::Castling(rook)) if rook.square == target));
+            if let Some(mv) = castling {
+                return Ok(*mv);
+            }
+        }
+        Err("Illegal move")
+    }
+
+    /// Writes a legal move in Standard Algebraic Notation, e.g. `Nbd7`, `exd5`, `e8=Q+` or `O-O`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let game = rust_chess::game::Game::new();
+    /// let mv = game.parse_uci_move("g1f3").unwrap();
+    /// assert_eq!("Nf3", game.move_to_san(&mv));
+    /// ```
+    pub fn move_to_san(&self, move_: &Move) -> String {
+        let mut san = match move_.flag {
+            Some(MoveMetaFlag::Castling(rook)) if rook.square.pos.0 > move_.piece.square.pos.0 => String::from("O-O"),
+            Some(MoveMetaFlag::Castling(_)) => String::from("O-O-O"),
+            Some(MoveMetaFlag::Drop) => move_.to_uci(),
+            _ => {
+                let is_capture = self.board.check_square_for_piece(&move_.target_square).is_some()
+                    || matches!(move_.flag, Some(MoveMetaFlag::EnPassant(_)));
+                let mut san = String::new();
+                if move_.piece.figure == Figure::Pawn {
+                    if is_capture {
+                        san.push(move_.piece.square.pos.0);
+                    }
+                } else {
+                    san.push(move_.piece.figure.letter());
+                    // other pieces of the same kind which could go to the target square as well
+                    let others: Vec<Square> = self.get_legal_moves(move_.piece.color).iter()
+                        .filter(|mv| mv.piece.figure == move_.piece.figure && mv.target_square == move_.target_square
+                            && mv.piece.square != move_.piece.square && !mv.is_castling())
+                        .map(|mv| mv.piece.square)
+                        .collect();
+                    if !others.is_empty() {
+                        if others.iter().all(|sq| sq.pos.0 != move_.piece.square.pos.0) {
+                            san.push(move_.piece.square.pos.0);
+                        } else if others.iter().all(|sq| sq.pos.1 != move_.piece.square.pos.1) {
+                            san.push(move_.piece.square.pos.1);
+                        } else {
+                            san.push_str(&move_.piece.square.to_string());
+                        }
+                    }
+                }
+                if is_capture {
+                    san.push('x');
+                }
+                san.push_str(&move_.target_square.to_string());
+                if let Some(figure) = move_.promotion() {
+                    san.push('=');
+                    san.push(figure.letter());
+                }
+                san
+            }
+        };
+
+        let mut after = self.position_copy();
+        after.play(*move_);
+        if after.state.check {
+            let mate = after.get_legal_moves(after.state.turn).is_empty();
+            san.push(if mate { '#' } else { '+' });
+        }
+        san
+    }
+
+    /// Parses a move in Standard Algebraic Notation, e.g. `Nf3`, `exd6`, `e8=Q` or `O-O`, and checks it is legal in the current position.
+    /// Check and annotation suffixes like `+`, `#` or `!?` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let game = rust_chess::game::Game::new();
+    /// assert_eq!("g1f3", game.parse_san("Nf3").unwrap().to_uci());
+    /// assert!(game.parse_san("Nf4").is_err());
+    /// ```
+    pub fn parse_san(&self, input: &str) -> Result<Move, &str> {
+        let san = input.trim().trim_end_matches(['+', '#', '!', '?']);
+        let legal_moves = self.get_legal_moves(self.state.turn);
+
+        if san == "O-O" || san == "0-0" || san == "O-O-O" || san == "0-0-0" {
+            let kingside = san.len() == 3;
+            return legal_moves.into_iter()
+                .find(|mv| matches!(mv.flag, Some(MoveMetaFlag::Castling(rook))
+                    if (rook.square.pos.0 > mv.piece.square.pos.0) == kingside))
+                .ok_or("Illegal move");
+        }
+
+        if !san.is_ascii() || san.len() < 2 {
+            return Err("Invalid SAN move notation");
+        }
+        // drops, e.g. N@f3, pawn drops may leave out the letter: @e4
+        if let Some((letter, square)) = san.split_once('@') {
+            let figure = match letter {
+                "" | "P" => Figure::Pawn,
+                _ => letter.chars().next().and_then(figure_from_letter).filter(|_| letter.len() == 1).ok_or("Invalid SAN move notation")?,
+            };
+            let square = self.board.get(square).ok_or("Invalid SAN move notation")?;
+            return self.find_drop(figure, *square);
+        }
+        // promotions are written as e8=Q, sometimes without the equals sign
+        let (san, promotion) = match san.char_indices().last() {
+            Some((i, c @ ('Q' | 'R' | 'B' | 'N' | 'K'))) => (san[..i].trim_end_matches('='), Some(figure_from_letter(c).unwrap())),
+            _ => (san, None),
+        };
+        let (figure, rest) = match san.chars().next().and_then(figure_from_letter) {
+            Some(figure) => (figure, &san[1..]),
+            None => (Figure::Pawn, san),
+        };
+        if rest.len() < 2 {
+            return Err("Invalid SAN move notation");
+        }
+        let target = match self.board.get(&rest[rest.len() - 2..]) {
+            Some(square) => *square,
+            None => return Err("Invalid SAN move notation"),
+        };
+        // whatever is left in front of the target square narrows down the moving piece
+        let disambiguation: Vec<char> = rest[..rest.len() - 2].chars().filter(|&c| c != 'x' && c != '-').collect();
+        if disambiguation.iter().any(|c| !matches!(c, 'a'..='h' | '1'..='8')) {
+            return Err("Invalid SAN move notation");
+        }
+
+        let mut candidates = legal_moves.into_iter()
+            .filter(|mv| mv.piece.figure == figure && mv.target_square == target && mv.promotion() == promotion && !mv.is_castling())
+            .filter(|mv| disambiguation.iter().all(|&c| c == mv.piece.square.pos.0 || c == mv.piece.square.pos.1));
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err("Ambiguous move"),
+            (None, _) => Err("Illegal move"),
+        }
+    }
+
+    fn find_drop(&self, figure: Figure, square: Square) -> Result<Move, &str> {
+        let drop = Move::new_drop(Piece { color: self.state.turn, figure, square });
+        if self.get_legal_moves(self.state.turn).contains(&drop) {
+            Ok(drop)
+        } else {
+            Err("Illegal move")
+        }
+    }
+
+    /// A copy of the current position without the moves leading to it, which is cheap to make and to play moves on,
+    /// e.g. in a search. The copy starts a tree of its own.
+    pub fn position_copy(&self) -> Game {
+        Game {board: self.board.clone(), state: self.state.clone(), meta: None, start_fen: String::new(), undo: Vec::new(),
+            tree: GameTree::new(), cursor: GameTree::ROOT, variant: self.variant.clone()}
+    }
+
+    /// Get the pseudo-legal moves for the given color, including castling, en-passant and promotion moves.
+    /// This does not check whether a move exposes the own king to a check, see `get_legal_moves` for that.
     pub fn get_moves(&self, color: Color) -> HashSet<Move> {
-        
+
         let mut result: HashSet<Move> = HashSet::new();
-        
+
+        // get available moves for all pieces
         for piece in self.board.pieces.iter().filter(|p| p.color == color).collect::<Vec<&Piece>>() {
             for target_square in &piece.get_available_squares(&self.board) {
-                result.insert(Move {piece: *piece, target_square: *target_square});
+                // extend with promotion moves
+                if piece.figure == Figure::Pawn && (target_square.pos.1 == '1' || target_square.pos.1 == '8') {
+                    for figure in self.variant.promotion_figures().iter().copied() {
+                        result.insert(Move {piece: *piece, target_square: *target_square, flag: Some(MoveMetaFlag::Promotion(figure))});
+                    }
+                } else {
+                    result.insert(Move::new(*piece, *target_square));
+                }
             }
         }
-        
-        result
-        // get available moves for all pieces
-        
 
         // extend with castling moves
+        result.extend(self.get_castling_moves(color));
+
+        // extend with drops from the pocket
+        result.extend(self.get_drops(color));
 
         // extend with en-passant moves
+        for (pawn, target_square) in self.state.possible_en_passant_moves.iter().filter(|(p, _)| p.color == color) {
+            let captured_square = Square { pos: (target_square.pos.0, pawn.square.pos.1) };
+            if let Some(captured) = self.board.check_square_for_piece(&captured_square) {
+                result.insert(Move {piece: *pawn, target_square: *target_square, flag: Some(MoveMetaFlag::EnPassant(*captured))});
+            }
+        }
 
-        // extend with promotion moves
+        self.variant.generate_moves(self, color, &mut result);
 
-        // set move meta information, if applicable
+        result
+    }
+
+    /// Get the legal moves for the given color, i.e. the moves which do not leave the own king in check.
+    pub fn get_legal_moves(&self, color: Color) -> HashSet<Move> {
+        // no moves can be made once the game has ended by a rule of the variant
+        if self.variant.game_end(self).is_some() {
+            return HashSet::new();
+        }
+        self.get_moves(color).into_iter()
+            .filter(|mv| self.is_legal(mv))
+            .collect()
+    }
+
+    /// Checks that a (pseudo-legal) move may be played by the rules of the variant,
+    /// for standard chess that it does not leave the own king in check.
+    pub fn is_legal(&self, move_: &Move) -> bool {
+        self.variant.is_legal(self, move_)
+    }
+
+    fn get_drops(&self, color: Color) -> HashSet<Move> {
+        let mut result = HashSet::new();
+        let pocket = match self.pocket(color) {
+            Some(pocket) => pocket,
+            None => return result,
+        };
+        for square in self.board.squares_as_vec() {
+            if self.board.check_square_for_piece(square).is_some() {
+                continue;
+            }
+            for figure in pocket.figures() {
+                // pawns can not be dropped on the first and last rank
+                if figure == Figure::Pawn && (square.pos.1 == '1' || square.pos.1 == '8') {
+                    continue;
+                }
+                result.insert(Move::new_drop(Piece { color, figure, square: *square }));
+            }
+        }
+        result
+    }
+
+    /// The pocket of the color in Crazyhouse, None in games without drops.
+    pub fn pocket(&self, color: Color) -> Option<&Pocket> {
+        self.state.pockets.as_ref().map(|pockets| &pockets[pocket_index(color)])
+    }
+
+    fn get_castling_moves(&self, color: Color) -> HashSet<Move> {
+        let mut result = HashSet::new();
+        if self.board.king(color).is_none() || self.in_check_color(color) {
+            return result;
+        }
+
+        for (king, rook) in self.state.castling_privileges.iter().filter(|(k, _)| k.color == color) {
+            // both pieces have to be on their squares still
+            if self.board.check_square_for_piece(&king.square) != Some(king)
+                || self.board.check_square_for_piece(&rook.square) != Some(rook) {
+                continue;
+            }
+
+            let king_target = castling_king_target(king, rook);
+            let rook_target = castling_rook_target(&king_target);
+
+            // all squares passed by king or rook have to be empty, apart from the castling pieces themselves
+            let files = [king.square.pos.0, king_target.pos.0, rook.square.pos.0, rook_target.pos.0];
+            let (min_file, max_file) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+            let blocked = (min_file..=max_file)
+                .map(|f| Square { pos: (f, king.square.pos.1) })
+                .filter(|sq| *sq != king.square && *sq != rook.square)
+                .any(|sq| self.board.check_square_for_piece(&sq).is_some());
+            if blocked {
+                continue;
+            }
+
+            // the king may not pass through or land on an attacked square
+            let (from, to) = (king.square.pos.0.min(king_target.pos.0), king.square.pos.0.max(king_target.pos.0));
+            let attacked = (from..=to)
+                .map(|f| Square { pos: (f, king.square.pos.1) })
+                .any(|sq| self.is_square_attacked(sq, color.opposite(), king));
+            if attacked {
+                continue;
+            }
+
+            result.insert(Move {piece: *king, target_square: king_target, flag: Some(MoveMetaFlag::Castling(*rook))});
+        }
+        result
+    }
+
+    fn is_square_attacked(&self, square: Square, by: Color, king: &Piece) -> bool {
+        // place the king on the square in question and see if the opponent could capture it
+        let mut board = self.board.clone();
+        let moved_king = Piece { square, ..*king };
+        board.remove_piece(king).remove_piece_by_square(&square).add_piece(moved_king);
+        debug_assert!(king.color != by);
+        self.variant.in_check(&board, king.color)
+    }
+
+    fn en_passant_captures(board: &Board, pawn_square: &Square, target: Square, color: Color) -> HashSet<(Piece, Square)> {
+        // the pawns of the given color next to the pawn which has just moved two squares
+        [(1, 0), (-1, 0)].iter()
+            .filter_map(|&direction| pawn_square.move_by(direction))
+            .filter_map(|sq| board.check_square_for_piece(&sq))
+            .filter(|p| p.figure == Figure::Pawn && p.color == color)
+            .map(|p| (*p, target))
+            .collect()
     }
 
     pub fn in_check(&self) -> bool {
-        // get king to move 
+        // get king to move
         self.in_check_color(self.state.turn)
     }
 
     pub fn in_check_color(&self, color: Color) -> bool {
-        // checks if the provided color is in check
-        let curr_king = self.board.pieces.iter()
-            .find(|&p| 
-                    p.color == color && 
-                    p.figure == Figure::King)
-            .unwrap();
-    
-        self.board.is_attacked(*curr_king)
+        // checks if the provided color is in check by the rules of the variant
+        self.variant.in_check(&self.board, color)
     }
 
     pub fn in_checkmate(&mut self) -> bool {
         // curr_king is in check
-        // no possible move can end the check 
+        // no possible move can end the check
         self.in_checkmate_color(self.state.turn)
     }
 
     pub fn in_checkmate_color(&mut self, color: Color) -> bool {
         // king is in check,
-        // no possible move can end the check 
+        // no possible move can end the check
         if !self.in_check_color(color) {
-            return false 
+            return false
         }
 
-        // simulate move to check state again
-        self.get_moves(color).iter()
-            .all(|&mv| self.simulate_move(mv).in_check_color(color))
+        self.get_legal_moves(color).is_empty()
     }
 
     pub fn in_stale_mate(&self) -> bool {
-        // curr player has no valid moves 
-        false
+        // curr player has no valid moves
+        !self.in_check() && self.get_legal_moves(self.state.turn).is_empty()
+    }
+
+    /// Checks whether the color can not checkmate the opponent by any sequence of legal moves, see `Variant::has_insufficient_material`.
+    pub fn has_insufficient_material(&self, color: Color) -> bool {
+        self.variant.has_insufficient_material(self, color)
+    }
+
+    /// Checks the current position for the end of the game and records the result and its reason, if any: first by the
+    /// rules of the variant, then for checkmate, stalemate, the fifty-move rule and insufficient material on both sides.
+    pub fn update_result(&mut self) -> Option<GameResult> {
+        if self.state.result.is_some() {
+            return self.state.result;
+        }
+        let end = match self.variant.game_end(self) {
+            Some(end) => Some(end),
+            None if self.get_legal_moves(self.state.turn).is_empty() => {
+                let termination = if self.in_check() { Termination::Checkmate } else { Termination::Stalemate };
+                Some((self.variant.no_moves_result(self), termination))
+            },
+            None if self.state.halfmove_clock >= 100 => Some((GameResult::Draw, Termination::FiftyMoveRule)),
+            None if self.has_insufficient_material(White) && self.has_insufficient_material(Black) =>
+                Some((GameResult::Draw, Termination::InsufficientMaterial)),
+            None => None,
+        };
+        if let Some((result, termination)) = end {
+            self.state.result = Some(result);
+            self.state.termination = Some(termination);
+        }
+        self.state.result
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.state.result
+    }
+
+    /// Records the result of a game which ended for a reason the rules can not tell, e.g. a resignation or a result read from PGN.
+    /// The reason is cleared, see `set_termination`.
+    pub fn set_result(&mut self, result: Option<GameResult>) {
+        self.state.result = result;
+        self.state.termination = None;
+    }
+
+    /// Why the game ended, as found by `update_result` or recorded with `set_termination`.
+    pub fn termination(&self) -> Option<Termination> {
+        self.state.termination
+    }
+
+    pub fn set_termination(&mut self, termination: Option<Termination>) {
+        self.state.termination = termination;
+    }
+
+    /// Checks whether the color may still castle to the given side, i.e. the king and that rook have not moved yet.
+    pub fn has_castling_privilege(&self, color: Color, kingside: bool) -> bool {
+        self.state.castling_privileges.iter()
+            .any(|(king, rook)| king.color == color && (rook.square.pos.0 > king.square.pos.0) == kingside)
+    }
+
+    /// The square a pawn passed with a double step, if the side to move has a pawn to take it en passant.
+    pub fn en_passant_square(&self) -> Option<Square> {
+        self.state.possible_en_passant_moves.iter().next().map(|(_, square)| *square)
+    }
+
+    /// The number of checks the color has given in Three-check, None in other games.
+    pub fn checks_given(&self, color: Color) -> Option<u8> {
+        self.state.checks.map(|checks| checks[pocket_index(color)])
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.state.fullmove_number
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.state.halfmove_clock
     }
 
     pub fn current_color(&self) -> Color {
@@ -178,10 +1079,7 @@ impl Game {
     }
 
     fn next_color(&self) -> Color {
-        match self.state.turn {
-            Color::White => Color::Black,
-            _ => Color::White 
-        }
+        self.state.turn.opposite()
     }
 
     pub fn is_valid(&self) -> bool {
@@ -190,10 +1088,271 @@ impl Game {
 
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+/// Moves the pieces on the board as described by the move by the standard rules, without any regard to the game state.
+pub fn apply_move(board: &mut Board, move_: &Move) {
+    match move_.flag {
+        Some(MoveMetaFlag::Drop) => {
+            board.add_piece(move_.piece);
+        },
+        Some(MoveMetaFlag::Castling(rook)) => {
+            let rook_target = castling_rook_target(&move_.target_square);
+            board
+                .remove_piece(&move_.piece)
+                .remove_piece(&rook)
+                .add_piece( Piece {square: move_.target_square, ..move_.piece} );
+            board.add_piece( Piece {square: rook_target, ..rook} );
+        },
+        _ => {
+            // remove pieces from source and target square and add moving piece
+            // this also works for non-capturing moves (where there is no piece on the target square)
+            if let Some(MoveMetaFlag::EnPassant(captured)) = move_.flag {
+                board.remove_piece(&captured);
+            }
+            // in the case for a promotion it is enough to replace the figure of the moving piece
+            let figure = match move_.flag {
+                Some(MoveMetaFlag::Promotion(figure)) => figure,
+                _ => move_.piece.figure,
+            };
+            board
+                .remove_piece_by_square(&move_.piece.square)
+                .remove_piece_by_square(&move_.target_square)
+                .add_piece( Piece {square: move_.target_square, figure, ..move_.piece} );
+        }
+    }
+}
+
+// separates the pockets from the piece placement of a Crazyhouse Fen record, written as [QRp] or as a ninth rank
+fn split_pockets(placement: &str) -> Result<(String, Option<[Pocket; 2]>), &'static str> {
+    let (placement, pockets) = match placement.split_once('[') {
+        Some((placement, pockets)) => match pockets.strip_suffix(']') {
+            Some(pockets) => (placement, Some(pockets)),
+            None => return Err("FEN Parsing Error"),
+        },
+        None => match placement.match_indices('/').nth(7) {
+            Some((index, _)) => (&placement[..index], Some(&placement[index + 1..])),
+            None => (placement, None),
+        },
+    };
+    let pockets = match pockets {
+        Some(letters) => {
+            let mut pockets = [Pocket::default(), Pocket::default()];
+            for c in letters.chars() {
+                let piece = Piece::from_fen(c, &Square { pos: ('a', '1') }).map_err(|_| "FEN Parsing Error")?;
+                if piece.figure == Figure::King {
+                    return Err("FEN Parsing Error");
+                }
+                pockets[pocket_index(piece.color)].add(piece.figure);
+            }
+            Some(pockets)
+        },
+        None => None,
+    };
+    Ok((placement.to_string(), pockets))
+}
+
+// the squares of the pieces marked as promoted with a tilde in the piece placement, the tilde has to follow a piece
+fn promoted_squares(placement: &str) -> Result<HashSet<Square>, &'static str> {
+    let mut promoted = HashSet::new();
+    let mut square = (0u8, 7u8);
+    let mut after_piece = false;
+    for c in placement.chars() {
+        match c {
+            '/' => square = (0, square.1.saturating_sub(1)),
+            '1'..='8' => square.0 += c as u8 - b'0',
+            '~' if after_piece && square.0 <= 8 => {
+                promoted.insert(Square { pos: ((b'a' + square.0 - 1) as char, (b'1' + square.1) as char) });
+            },
+            '~' => return Err("FEN Parsing Error"),
+            _ => square.0 += 1,
+        }
+        after_piece = c.is_ascii_alphabetic();
+    }
+    Ok(promoted)
+}
+
+fn pocket_index(color: Color) -> usize {
+    match color {
+        White => 0,
+        Black => 1,
+    }
+}
+
+fn figure_from_letter(c: char) -> Option<Figure> {
+    match c {
+        'R' => Some(Figure::Rook),
+        'N' => Some(Figure::Knight),
+        'B' => Some(Figure::Bishop),
+        'Q' => Some(Figure::Queen),
+        'K' => Some(Figure::King),
+        _ => None
+    }
+}
+
+/// The king castles towards the g-file with a rook on its right and towards the c-file otherwise.
+fn castling_king_target(king: &Piece, rook: &Piece) -> Square {
+    let file = if rook.square.pos.0 > king.square.pos.0 { 'g' } else { 'c' };
+    Square { pos: (file, king.square.pos.1) }
+}
+
+/// The rook ends up next to the king, on the f-file or the d-file respectively.
+fn castling_rook_target(king_target: &Square) -> Square {
+    let file = if king_target.pos.0 == 'g' { 'f' } else { 'd' };
+    Square { pos: (file, king_target.pos.1) }
+}
+
+impl GameTree {
+
+    pub const ROOT: NodeId = 0;
+
+    pub fn new() -> Self {
+        GameTree { nodes: vec! [Some(GameTreeNode { move_: None, meta: MoveMeta::default(), parent: None, mainline: None, variations: vec! [] })] }
+    }
+
+    /// The node with the given id, None if there is no such node or it was deleted with its variation.
+    pub fn node(&self, node: NodeId) -> Option<&GameTreeNode> {
+        self.nodes.get(node).and_then(Option::as_ref)
+    }
+
+    pub fn meta_mut(&mut self, node: NodeId) -> Option<&mut MoveMeta> {
+        self.nodes.get_mut(node).and_then(Option::as_mut).map(|node| &mut node.meta)
+    }
+
+    /// The continuations of a node, the mainline first.
+    pub fn children(&self, node: NodeId) -> Vec<NodeId> {
+        match self.node(node) {
+            Some(node) => node.mainline.into_iter().chain(node.variations.iter().map(|v| v.start)).collect(),
+            None => vec! [],
+        }
+    }
+
+    /// Whether the node is part of the tree, i.e. it exists and was not deleted with its variation.
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
+
+    // the node of an id the tree handed out itself, which is still part of it
+    fn at(&self, node: NodeId) -> &GameTreeNode {
+        self.nodes[node].as_ref().expect("the node was deleted")
+    }
+
+    fn at_mut(&mut self, node: NodeId) -> &mut GameTreeNode {
+        self.nodes[node].as_mut().expect("the node was deleted")
+    }
+
+    // the nodes from the root to the node, both included
+    fn path_to(&self, node: NodeId) -> Vec<NodeId> {
+        let mut path = vec! [node];
+        while let Some(parent) = self.at(*path.last().unwrap()).parent {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// The moves leading from the starting position to the node, None if the node is not part of the tree.
+    pub fn moves_to(&self, node: NodeId) -> Option<Vec<Move>> {
+        if !self.contains(node) {
+            return None;
+        }
+        let mut moves = vec! [];
+        let mut current = node;
+        while let Some(parent) = self.at(current).parent {
+            moves.push(self.at(current).move_.unwrap());
+            current = parent;
+        }
+        moves.reverse();
+        Some(moves)
+    }
+
+    /// The moves of the mainline, from the starting position to its end.
+    pub fn mainline(&self) -> Vec<Move> {
+        let mut moves = vec! [];
+        let mut current = GameTree::ROOT;
+        while let Some(next) = self.at(current).mainline {
+            moves.push(self.at(next).move_.unwrap());
+            current = next;
+        }
+        moves
+    }
+
+    /// Whether the node is the mainline continuation of its parent (or the root). False if the node is not part of the tree.
+    pub fn is_mainline_child(&self, node: NodeId) -> bool {
+        match self.node(node).map(|node| node.parent) {
+            Some(Some(parent)) => self.at(parent).mainline == Some(node),
+            Some(None) => true,
+            None => false,
+        }
+    }
+
+    fn find_child(&self, node: NodeId, move_: &Move) -> Option<NodeId> {
+        self.children(node).into_iter().find(|&child| self.at(child).move_.as_ref() == Some(move_))
+    }
+
+    fn add_child(&mut self, node: NodeId, move_: Move) -> NodeId {
+        let child = self.nodes.len();
+        self.nodes.push(Some(GameTreeNode { move_: Some(move_), meta: MoveMeta::default(), parent: Some(node), mainline: None, variations: vec! [] }));
+        let parent = self.at_mut(node);
+        if parent.mainline.is_none() {
+            parent.mainline = Some(child);
+        } else {
+            parent.variations.push(Variation { start: child });
+        }
+        child
+    }
+
+    // the first node of the innermost variation containing the node, None on the mainline
+    fn variation_start(&self, node: NodeId) -> Option<NodeId> {
+        let mut current = node;
+        while let Some(parent) = self.at(current).parent {
+            if !self.is_mainline_child(current) {
+                return Some(current);
+            }
+            current = parent;
+        }
+        None
+    }
+
+    fn promote(&mut self, start: NodeId) {
+        let parent = self.at(start).parent.unwrap();
+        let parent = self.at_mut(parent);
+        let index = parent.variations.iter().position(|v| v.start == start).unwrap();
+        parent.variations[index].start = parent.mainline.unwrap();
+        parent.mainline = Some(start);
+    }
+
+    // deletes the subtree, the other nodes stay in place so their ids remain valid
+    fn remove(&mut self, start: NodeId) {
+        let parent = self.at(start).parent.unwrap();
+        let parent = self.at_mut(parent);
+        parent.variations.retain(|v| v.start != start);
+        if parent.mainline == Some(start) {
+            parent.mainline = None;
+        }
+        let mut deleted = vec! [start];
+        while let Some(node) = deleted.pop() {
+            deleted.extend(self.children(node));
+            self.nodes[node] = None;
+        }
+    }
+
+}
+
+impl Default for GameTree {
+    fn default() -> Self {
+        GameTree::new()
+    }
+}
+
 impl State {
 
     pub fn new(board: &Board) -> Self {
-        
+
         let possible_en_passant_moves: HashSet<(Piece, Square)> = HashSet::new();
         let mut castling_privileges: HashSet<(Piece, Piece)> = HashSet::new();
         for rook in board.pieces.iter()
@@ -206,21 +1365,93 @@ impl State {
         //         .tuple_combinations()
         //         .filter(|(&a,&b)| (a.figure == King || b.figure == King) && (a.color == b.color))
         //         .collect::<HashSet<(Piece, Piece)>>();
-        State { 
-            turn: White, 
-            result: None, 
-            check: false, 
-            castling_privileges: castling_privileges, 
-            possible_en_passant_moves: possible_en_passant_moves }
+        State {
+            turn: White,
+            result: None,
+            termination: None,
+            check: false,
+            castling_privileges,
+            possible_en_passant_moves,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            pockets: None,
+            promoted: HashSet::new(),
+            checks: None }
     }
 
 }
 
 impl Move {
 
+    pub fn new(piece: Piece, target_square: Square) -> Self {
+        Move { piece, target_square, flag: None }
+    }
+
+    /// A Crazyhouse drop of the piece on its square.
+    pub fn new_drop(piece: Piece) -> Self {
+        Move { piece, target_square: piece.square, flag: Some(MoveMetaFlag::Drop) }
+    }
+
+    pub fn piece(&self) -> Piece {
+        self.piece
+    }
+
+    pub fn is_drop(&self) -> bool {
+        self.flag == Some(MoveMetaFlag::Drop)
+    }
+
+    pub fn target_square(&self) -> Square {
+        self.target_square
+    }
+
+    pub fn flag(&self) -> Option<MoveMetaFlag> {
+        self.flag
+    }
+
+    pub fn is_castling(&self) -> bool {
+        matches!(self.flag, Some(MoveMetaFlag::Castling(_)))
+    }
+
+    /// The move in UCI long algebraic notation: source and target square plus the letter of a promotion, e.g. `e2e4` or `e7e8q`.
+    /// Castling is written as the king's move, e.g. `e1g1`, and drops with the letter of the piece, e.g. `N@f3`.
+    /// A Chess960 castling move where the king stays on its square is written as the king taking its rook, e.g. `g1h1`.
+    pub fn to_uci(&self) -> String {
+        if self.is_drop() {
+            return format!("{}@{}", self.piece.figure.letter(), self.target_square);
+        }
+        if let Some(MoveMetaFlag::Castling(rook)) = self.flag {
+            if self.piece.square == self.target_square {
+                return format!("{}{}", self.piece.square, rook.square);
+            }
+        }
+        let promotion = match self.promotion() {
+            Some(figure) => Piece { color: Black, figure, square: self.target_square }.to_fen_letter(),
+            None => String::new(),
+        };
+        format!("{}{}{}", self.piece.square, self.target_square, promotion)
+    }
+
+    /// Like `to_uci`, with `king_takes_rook` castling is written as the king capturing its own rook, e.g. `e1h1`, as
+    /// engines expect it for Chess960.
+    pub fn to_uci_with(&self, king_takes_rook: bool) -> String {
+        match self.flag {
+            Some(MoveMetaFlag::Castling(rook)) if king_takes_rook => format!("{}{}", self.piece.square, rook.square),
+            _ => self.to_uci(),
+        }
+    }
+
+    /// The figure a pawn is promoted to, if this is a promotion move.
+    pub fn promotion(&self) -> Option<Figure> {
+        match self.flag {
+            Some(MoveMetaFlag::Promotion(figure)) => Some(figure),
+            _ => None
+        }
+    }
+
     pub fn to_str(&self) -> String {
-        let str = String::new();
-        
+        if self.is_drop() {
+            return self.to_uci();
+        }
         let piece_letter = match self.piece.figure {
             Figure::Pawn => return self.target_square.to_string(),
             Figure::Rook => "R",
@@ -236,28 +1467,116 @@ impl Move {
 
 }
 
+impl std::fmt::Display for Termination {
+
+    /// The reason as written in the PGN `Termination` tag.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::FiftyMoveRule => "fifty-move rule",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::ThirdCheck => "third check",
+            Termination::KingInCenter => "king in the center",
+            Termination::KingExploded => "king exploded",
+            Termination::NoPiecesLeft => "no pieces left",
+            Termination::KingReachedGoal => "king reached the goal",
+            Termination::VariantRule => "variant rule",
+        };
+        write!(f, "{}", reason)
+    }
+
+}
+
 impl std::fmt::Display for Move {
-    
+
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_drop() {
+            return write!(f, "{}", self.to_uci());
+        }
         let piece_letter = match self.piece.figure {
-            Figure::Pawn => return write!(f, "{}", self.target_square.to_string()),
+            Figure::Pawn => return write!(f, "{}", self.target_square),
             Figure::Rook => "R",
             Figure::Knight => "N",
             Figure::Bishop => "B",
             Figure::Queen => "Q",
             Figure::King => "K"
         };
-        
+
         write!(f, "{}{}-{}", piece_letter, self.piece.square, self.target_square)
     }
-    
+
+}
+
+impl Pocket {
+
+    const FIGURES: [Figure; 5] = [Figure::Pawn, Figure::Knight, Figure::Bishop, Figure::Rook, Figure::Queen];
+
+    pub fn count(&self, figure: Figure) -> u8 {
+        Pocket::FIGURES.iter().position(|&f| f == figure).map_or(0, |i| self.counts[i])
+    }
+
+    pub fn add(&mut self, figure: Figure) {
+        if let Some(i) = Pocket::FIGURES.iter().position(|&f| f == figure) {
+            self.counts[i] += 1;
+        }
+    }
+
+    /// Takes a piece out of the pocket. Returns false if there is none.
+    pub fn remove(&mut self, figure: Figure) -> bool {
+        match Pocket::FIGURES.iter().position(|&f| f == figure) {
+            Some(i) if self.counts[i] > 0 => {
+                self.counts[i] -= 1;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// The different figures in the pocket.
+    pub fn figures(&self) -> Vec<Figure> {
+        Pocket::FIGURES.iter().copied().filter(|&f| self.count(f) > 0).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&c| c == 0)
+    }
+
+    // the pieces as Fen letters, the most valuable first
+    fn to_fen(self, color: Color) -> String {
+        Pocket::FIGURES.iter().rev()
+            .flat_map(|&figure| {
+                let letter = Piece { color, figure, square: Square { pos: ('a', '1') } }.to_fen_letter();
+                std::iter::repeat_n(letter, self.count(figure) as usize)
+            })
+            .collect()
+    }
+
 }
 
 impl GameMeta {
 
     pub fn new() -> Self {
-        GameMeta {}
+        GameMeta { tags: vec! [] }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Sets the value of a tag, keeping its position if it exists already.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = String::from(value),
+            None => self.tags.push((String::from(name), String::from(value))),
+        }
     }
 
 }
 
+impl Default for GameMeta {
+    fn default() -> Self {
+        GameMeta::new()
+    }
+}
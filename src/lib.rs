@@ -2,6 +2,19 @@
 pub mod parser;
 pub mod pieces;
 pub mod game;
+pub mod clock;
+pub mod variant;
+pub mod search;
+pub mod uci_client;
+pub mod book;
+pub mod tablebase;
+pub mod endgame;
+pub mod retrograde;
+pub mod svg;
+pub mod render;
+pub mod database;
+pub mod material;
+pub mod see;
 
 pub mod core {
     use std::fmt;
@@ -11,10 +24,10 @@ pub mod core {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub enum Color { Black, White }
 
-    #[derive(Debug, Clone, Copy, PartialEq, Hash)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub enum Figure { Pawn, Rook, Knight, Bishop, Queen, King }
 
-    #[derive(Debug, Clone, Copy, Eq, Hash)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Square {
         pub pos: (char, char)
     }
@@ -26,15 +39,52 @@ pub mod core {
         pub is_valid: bool
     }
 
-    #[derive(Debug, Clone, Copy, Hash)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Piece {
         pub color: Color,
         pub figure: Figure,
         pub square: Square
     }
 
+    // the steps of the king and the jumps of the knight as (file, rank) offsets
+    pub(crate) const KING_STEPS: [(i8, i8); 8] = [(1,1), (1,0), (1,-1), (0,-1), (-1,-1), (-1,0), (-1,1), (0,1)];
+    pub(crate) const KNIGHT_JUMPS: [(i8, i8); 8] = [(1,2), (2,1), (2,-1), (1,-2), (-1,-2), (-2,-1), (-2,1), (-1,2)];
+
+    // the letters of the figures from the strongest to the weakest, the order of material signatures and table names
+    pub(crate) const FIGURE_LETTERS: &str = "KQRBNP";
+
+
+    impl Color {
+        /// Returns the color of the opposing side.
+        pub fn opposite(&self) -> Color {
+            match self {
+                Color::White => Color::Black,
+                Color::Black => Color::White
+            }
+        }
+    }
+
+    impl Figure {
+        /// The uppercase letter of the figure, e.g. `N` for the knight.
+        pub(crate) fn letter(&self) -> char {
+            match self {
+                Figure::King => 'K',
+                Figure::Queen => 'Q',
+                Figure::Rook => 'R',
+                Figure::Bishop => 'B',
+                Figure::Knight => 'N',
+                Figure::Pawn => 'P',
+            }
+        }
+
+        /// The place of the figure in `FIGURE_LETTERS`, 0 for the king up to 5 for the pawn.
+        pub(crate) fn order(&self) -> usize {
+            FIGURE_LETTERS.find(self.letter()).unwrap()
+        }
+    }
 
     impl Square {
+        #[allow(clippy::should_implement_trait)]
         pub fn from_str(s: &str) -> Square {
             assert_eq!(s.chars().count(), 2);
             let chars: Vec<char> = s.chars().collect();
@@ -45,28 +95,55 @@ pub mod core {
             let delta_f = ((self.pos.0 as i8) - ('a' as i8)) + delta.0;
             let delta_r = ((self.pos.1 as i8) - ('1' as i8)) + delta.1;
 
-            if delta_f >= 0 && delta_f >= 0 {
-                let files: Vec<char> = ('a'..='h').collect();
-                let ranks: Vec<char> = ('1'..='8').collect();
-                let new_file: &char = files.get(delta_f as usize)?;
-                let new_rank: &char = ranks.get(delta_r as usize)?;
+            if (0..8).contains(&delta_f) && (0..8).contains(&delta_r) {
+                let new_file = (b'a' + delta_f as u8) as char;
+                let new_rank = (b'1' + delta_r as u8) as char;
 
-                Some( Square {pos: (*new_file, *new_rank)} )
+                Some( Square {pos: (new_file, new_rank)} )
             } else {
                 None
             }
         }
+
+        /// Zero-based file index, 0 for the a-file up to 7 for the h-file.
+        pub fn file_index(&self) -> u8 {
+            self.pos.0 as u8 - b'a'
+        }
+
+        /// Zero-based rank index, 0 for the first rank up to 7 for the eighth rank.
+        pub fn rank_index(&self) -> u8 {
+            self.pos.1 as u8 - b'1'
+        }
+
+        /// Zero-based square index, rank by rank: 0 for a1, 7 for h1 up to 63 for h8.
+        pub(crate) fn index(&self) -> usize {
+            self.rank_index() as usize * 8 + self.file_index() as usize
+        }
+
+        pub(crate) fn from_index(index: usize) -> Square {
+            Square { pos: ((b'a' + (index % 8) as u8) as char, (b'1' + (index / 8) as u8) as char) }
+        }
     }
 
-    impl fmt::Display for Square {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{}{}", self.pos.0, self.pos.1)
+    /// The index of the square a (file, rank) offset away from the square with the given index, None off the board.
+    pub(crate) fn step(square: usize, (file, rank): (i8, i8)) -> Option<usize> {
+        let file = (square % 8) as i8 + file;
+        let rank = (square / 8) as i8 + rank;
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(rank as usize * 8 + file as usize)
+        } else {
+            None
         }
     }
 
-    impl PartialEq for Square {
-        fn eq(&self, other: &Self) -> bool {
-            self.pos.0 == other.pos.0 && self.pos.1 == other.pos.1
+    /// The number of king moves between the squares with the given indices.
+    pub(crate) fn king_distance(a: usize, b: usize) -> usize {
+        (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8))
+    }
+
+    impl fmt::Display for Square {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}{}", self.pos.0, self.pos.1)
         }
     }
 
@@ -80,10 +157,15 @@ pub mod core {
 
             for (i,f) in files.iter().enumerate() {
                 for (j,r) in ranks.iter().enumerate() {
-                    squares[j][i] = Square{ pos: (f.clone(), r.clone()) };
+                    squares[j][i] = Square{ pos: (*f, *r) };
                 }
             }
-            Board {squares: squares, is_valid: true, pieces: Vec::new()}
+            Board {squares, is_valid: true, pieces: Vec::new()}
+        }
+
+        /// Returns the king of the given color, if there is one on the board.
+        pub fn king(&self, color: Color) -> Option<&Piece> {
+            self.pieces.iter().find(|p| p.color == color && p.figure == Figure::King)
         }
 
         pub fn new_in_standard_position() -> Board {
@@ -91,19 +173,39 @@ pub mod core {
             parse_fen(std_fen).unwrap()
         }
 
+        /// Creates a board in one of the 960 starting positions of Chess960, numbered as by Scharnagl.
+        /// Index 518 is the standard starting position. Returns None for indices from 960 on.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// let board = rust_chess::core::Board::new_chess960(0).unwrap();
+        /// assert_eq!("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR", rust_chess::parser::export_fen_position(&board));
+        /// ```
+        pub fn new_chess960(index: u16) -> Option<Board> {
+            let back_rank = chess960_back_rank(index)?;
+            let mut board = Board::new();
+            for (file, figure) in ('a'..='h').zip(back_rank) {
+                for (color, piece_rank, pawn_rank) in [(Color::White, '1', '2'), (Color::Black, '8', '7')] {
+                    board.add_piece(Piece { color, figure, square: Square { pos: (file, piece_rank) } });
+                    board.add_piece(Piece { color, figure: Figure::Pawn, square: Square { pos: (file, pawn_rank) } });
+                }
+            }
+            Some(board)
+        }
+
         pub fn get(&self, index_str: &str) -> Option<&Square> {
             // is valid notation string?
             let lowercase = index_str.to_ascii_lowercase();
             let bytes = lowercase.trim().as_bytes();
-            if !bytes.len() == 2 {
-                return None
+            if bytes.len() != 2 {
+                None
             } else {
-                let res = match (bytes[0] as char, bytes[1] as char) {
-                    ('a'..='h', '1'..='8') => Some(&self.squares[(bytes[1] as u8 - b'1') as usize]
-                                                                [(bytes[0] as u8 - b'a') as usize]),
+                match (bytes[0] as char, bytes[1] as char) {
+                    ('a'..='h', '1'..='8') => Some(&self.squares[(bytes[1] - b'1') as usize]
+                                                                [(bytes[0] - b'a') as usize]),
                     _ => None
-                };
-                return res;
+                }
             }
         }
 
@@ -116,7 +218,10 @@ pub mod core {
         }
 
         pub fn get_from_square(&self, square: &Square) -> Option<&Square> {
-            self.get(&String::from_iter([square.pos.0, square.pos.1])[..])
+            match square.pos {
+                ('a'..='h', '1'..='8') => Some(&self.squares[square.rank_index() as usize][square.file_index() as usize]),
+                _ => None
+            }
         }
 
         pub fn get_from_coord(&self, rank_byte: u8, file_byte: u8) -> Option<&Square> {
@@ -147,32 +252,30 @@ pub mod core {
         }
 
         pub fn get_rank_from_square(&self, square: &Square) -> HashSet<&Square> {
-            let rank: Vec<&Square> = self.squares_as_vec().iter().map(|sq| *sq).filter(|&sq| sq.pos.1 == square.pos.1).collect();
+            let rank: Vec<&Square> = self.squares_as_vec().iter().copied().filter(|&sq| sq.pos.1 == square.pos.1).collect();
             HashSet::from_iter(rank.iter().copied())
                         // self.squares_as_set().iter().map(|sq| *sq)
         }
 
         pub fn get_file_from_square(&self, square: &Square) -> HashSet<&Square> {
-            let file: Vec<&Square> = self.squares_as_vec().iter().map(|sq| *sq).filter(|&sq| sq.pos.0 == square.pos.0).collect();
+            let file: Vec<&Square> = self.squares_as_vec().iter().copied().filter(|&sq| sq.pos.0 == square.pos.0).collect();
             HashSet::from_iter(file.iter().copied())
         }
 
         pub fn get_diag_from_square(&self, square: &Square) -> HashSet<&Square> {
             let directions = vec! [(1,1), (-1,-1), (1,-1), (-1,1)];
             let mut squares: Vec<&Square> = vec! [];
-            squares.push(&self.get( &String::from_iter([square.pos.0, square.pos.1])[..]).unwrap());
+            squares.push(self.get( &String::from_iter([square.pos.0, square.pos.1])[..]).unwrap());
 
             for direction in directions {
                 let mut next_square = square.move_by(direction);
-                while next_square.is_some() {
-                    let next_pos = next_square.unwrap().pos;
-                    let intermediate = &self.get( &String::from_iter([next_pos.0, next_pos.1])[..]);
-                    if intermediate.is_none() {
-                        break;
-                    } else {
-                        let next_square_board = intermediate.unwrap();
-                        squares.push(next_square_board);
-                        next_square = next_square_board.move_by(direction);
+                while let Some(next) = next_square {
+                    match self.get_from_square(&next) {
+                        Some(next_square_board) => {
+                            squares.push(next_square_board);
+                            next_square = next_square_board.move_by(direction);
+                        },
+                        None => break
                     }
                 }
             };
@@ -204,49 +307,118 @@ pub mod core {
         }
 
         pub fn is_attacked(&self, piece: Piece) -> bool {
-            // get pieces of other color
-            // combine their possible squares
-            // check if 'piece' position is in that set
-            self.pieces.iter()
-                .filter(|p| p.color != piece.color)
-                .flat_map(|p| p.get_attacked_squares(&self))
-                .find(|&sq| sq == piece.square).is_some()
-        }
-    }
+            // look outwards from the piece's square for pieces of the other color which could capture it
+            // this gives the same answer as combining the attacked squares of all opposing pieces, but is a lot cheaper
+            let attacker = |sq: Option<Square>, figures: &[Figure]| -> bool {
+                sq.and_then(|sq| self.check_square_for_piece(&sq))
+                    .is_some_and(|p| p.color != piece.color && figures.contains(&p.figure))
+            };
 
-    impl fmt::Display for Board {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            // TODO: refactor this
-            let mut rank_rev = self.squares.clone();
-            // let str = String::new();
-            rank_rev.reverse();
-            for rank in rank_rev {
-                for file in rank {
-                    if let Some(piece) = self.check_square_for_piece(&file) {
-                        // write!(f, "{}", piece.to_fen_letter())?;
-                        write!(f, "{}", piece.to_symbol())?;
-                    } else {
-                        write!(f, " ")?;
+            // pawns capture towards the opposite side, so an attacking pawn stands one rank 'behind' the square
+            let pawn_rank = if piece.color == Color::White { 1 } else { -1 };
+
+            if KNIGHT_JUMPS.iter().any(|&d| attacker(piece.square.move_by(d), &[Figure::Knight]))
+                || KING_STEPS.iter().any(|&d| attacker(piece.square.move_by(d), &[Figure::King]))
+                || [-1, 1].iter().any(|&f| attacker(piece.square.move_by((f, pawn_rank)), &[Figure::Pawn])) {
+                return true;
+            }
+
+            for (direction, figures) in [
+                ((0,1), [Figure::Rook, Figure::Queen]), ((0,-1), [Figure::Rook, Figure::Queen]),
+                ((1,0), [Figure::Rook, Figure::Queen]), ((-1,0), [Figure::Rook, Figure::Queen]),
+                ((1,1), [Figure::Bishop, Figure::Queen]), ((1,-1), [Figure::Bishop, Figure::Queen]),
+                ((-1,1), [Figure::Bishop, Figure::Queen]), ((-1,-1), [Figure::Bishop, Figure::Queen])] {
+                let mut curr_square = piece.square;
+                while let Some(next_square) = curr_square.move_by(direction) {
+                    if let Some(other_piece) = self.check_square_for_piece(&next_square) {
+                        if other_piece.color != piece.color && figures.contains(&other_piece.figure) {
+                            return true;
+                        }
+                        break;
                     }
+                    curr_square = next_square;
                 }
-                writeln!(f, "")?;
             }
-            write!(f, "")
+
+            false
+        }
+
+        /// The pieces of the color which attack the square, whether it is empty or holds a piece of either color.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rust_chess::core::{Color, Square};
+        ///
+        /// let board = rust_chess::parser::parse_fen("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1").unwrap();
+        /// // the pawn attacks the empty square, the king defends its own pawn
+        /// assert_eq!(1, board.attackers_of(Square::from_str("e3"), Color::White).len());
+        /// assert_eq!(1, board.attackers_of(Square::from_str("d2"), Color::White).len());
+        /// assert_eq!(0, board.attackers_of(Square::from_str("d3"), Color::White).len());
+        /// ```
+        pub fn attackers_of(&self, square: Square, color: Color) -> Vec<Piece> {
+            self.pieces.iter().filter(|p| p.color == color && p.attacks(self, &square)).copied().collect()
+        }
+
+        /// All squares the color attacks, including empty squares and squares of its own pieces, which it defends.
+        pub fn attacked_squares(&self, color: Color) -> HashSet<Square> {
+            self.squares.iter().flatten()
+                .filter(|square| self.pieces.iter().any(|p| p.color == color && p.attacks(self, square)))
+                .copied()
+                .collect()
+        }
+
+        /// The pieces of the same color which protect the piece.
+        pub fn defenders_of(&self, piece: &Piece) -> Vec<Piece> {
+            self.attackers_of(piece.square, piece.color)
         }
     }
 
-    impl Piece {
+    /// The pieces on the first rank in the Chess960 starting position with the given Scharnagl index, from the a-file to the h-file.
+    pub fn chess960_back_rank(index: u16) -> Option<[Figure; 8]> {
+        if index >= 960 {
+            return None;
+        }
+        let mut rank: [Option<Figure>; 8] = [None; 8];
+        let mut n = index as usize;
+
+        // one bishop on a light square (b, d, f, h) and one on a dark square (a, c, e, g)
+        rank[(n % 4) * 2 + 1] = Some(Figure::Bishop);
+        n /= 4;
+        rank[(n % 4) * 2] = Some(Figure::Bishop);
+        n /= 4;
+
+        // the queen and the knights go to the n-th of the remaining empty squares
+        let place = |rank: &mut [Option<Figure>; 8], nth: usize, figure: Figure| {
+            let file = (0..8).filter(|&f| rank[f].is_none()).nth(nth).unwrap();
+            rank[file] = Some(figure);
+        };
+        place(&mut rank, n % 6, Figure::Queen);
+        n /= 6;
+        let knights = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)][n];
+        // the second knight's square counts without the first one
+        place(&mut rank, knights.0, Figure::Knight);
+        place(&mut rank, knights.1 - 1, Figure::Knight);
+
+        // the king between the two rooks on the remaining squares
+        for figure in [Figure::Rook, Figure::King, Figure::Rook] {
+            place(&mut rank, 0, figure);
+        }
+        Some(rank.map(|figure| figure.unwrap()))
     }
 
-    impl PartialEq for Piece {
-        fn eq(&self, other: &Self) -> bool {
-            self.color == other.color &&
-            self.figure == other.figure &&
-            self.square == other.square
+    impl Default for Board {
+        fn default() -> Self {
+            Board::new()
         }
     }
 
-    impl Eq for Piece {}
+    impl fmt::Display for Board {
+        /// The board with Unicode pieces and coordinates, see `BoardRenderer` for other styles.
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", crate::render::BoardRenderer::default().render(self))
+        }
+    }
 
 }
 
@@ -0,0 +1,108 @@
+//! The material on a board: how many pieces of each figure both sides have, and what follows from it.
+//!
+//! ```
+//! use rust_chess::core::Color;
+//! use rust_chess::material::Material;
+//! use rust_chess::parser::parse_fen;
+//!
+//! let board = parse_fen("8/5k2/8/3r4/8/2KR4/3P4/8 w - - 0 1").unwrap();
+//! let material = Material::from_board(&board);
+//! assert_eq!("KRPvKR", material.signature());
+//! assert_eq!(1, material.balance());
+//! assert_eq!(4, material.phase());
+//! assert_eq!(false, material.has_bishop_pair(Color::White));
+//! ```
+
+use crate::core::{Board, Color, Figure};
+
+// the order of the figures in a signature, the most valuable first
+const SIGNATURE_ORDER: [Figure; 6] = [Figure::King, Figure::Queen, Figure::Rook, Figure::Bishop, Figure::Knight, Figure::Pawn];
+
+/// The phase of the starting position, see `Material::phase`.
+pub const MAX_PHASE: i32 = 24;
+
+/// The number of pieces of each figure and color, and the colors of the squares the bishops stand on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Material {
+    // indexed by color, then by figure
+    counts: [[u8; 6]; 2],
+    // the bishops of each color on dark and on light squares
+    bishops: [[u8; 2]; 2],
+}
+
+impl Material {
+
+    pub fn from_board(board: &Board) -> Self {
+        let mut material = Material::default();
+        for piece in board.pieces.iter() {
+            material.counts[piece.color as usize][piece.figure as usize] += 1;
+            if piece.figure == Figure::Bishop {
+                let light = (piece.square.file_index() + piece.square.rank_index()) % 2;
+                material.bishops[piece.color as usize][light as usize] += 1;
+            }
+        }
+        material
+    }
+
+    pub fn count(&self, color: Color, figure: Figure) -> u8 {
+        self.counts[color as usize][figure as usize]
+    }
+
+    /// The number of pieces of the color, including the king.
+    pub fn pieces(&self, color: Color) -> u8 {
+        self.counts[color as usize].iter().sum()
+    }
+
+    /// The figures of both sides, White's first, e.g. `KRPvKR`. Each side starts with the king, followed by the other
+    /// figures from the most valuable to the pawns.
+    pub fn signature(&self) -> String {
+        let side = |color: Color| {
+            SIGNATURE_ORDER.iter()
+                .flat_map(|&figure| std::iter::repeat_n(figure.letter(), self.count(color, figure) as usize))
+                .collect::<String>()
+        };
+        format!("{}v{}", side(Color::White), side(Color::Black))
+    }
+
+    /// The material with the colors swapped, e.g. `KRvKRP` for `KRPvKR`.
+    pub fn flipped(&self) -> Self {
+        Material { counts: [self.counts[1], self.counts[0]], bishops: [self.bishops[1], self.bishops[0]] }
+    }
+
+    /// The material of White minus the one of Black in pawns, counting 3 for a knight or bishop, 5 for a rook and 9 for
+    /// a queen.
+    pub fn balance(&self) -> i32 {
+        let value = |color: Color| -> i32 {
+            [(Figure::Pawn, 1), (Figure::Knight, 3), (Figure::Bishop, 3), (Figure::Rook, 5), (Figure::Queen, 9)].iter()
+                .map(|&(figure, value)| value * self.count(color, figure) as i32)
+                .sum()
+        };
+        value(Color::White) - value(Color::Black)
+    }
+
+    /// How much of the material for the middlegame is left, to blend a middlegame and an endgame evaluation: every
+    /// knight and bishop counts 1, every rook 2 and every queen 4. The starting position has `MAX_PHASE`, and
+    /// positions with more pieces after promotions are capped to it; only kings and pawns are 0.
+    pub fn phase(&self) -> i32 {
+        let phase: i32 = [Color::White, Color::Black].iter()
+            .map(|&color| {
+                [(Figure::Knight, 1), (Figure::Bishop, 1), (Figure::Rook, 2), (Figure::Queen, 4)].iter()
+                    .map(|&(figure, value)| value * self.count(color, figure) as i32)
+                    .sum::<i32>()
+            })
+            .sum();
+        phase.min(MAX_PHASE)
+    }
+
+    /// Whether the color has bishops on both light and dark squares.
+    pub fn has_bishop_pair(&self, color: Color) -> bool {
+        self.bishops[color as usize].iter().all(|&count| count > 0)
+    }
+
+    /// Whether both sides have a single bishop, and the two are on squares of different colors.
+    pub fn has_opposite_colored_bishops(&self) -> bool {
+        let [black, white] = self.bishops;
+        self.count(Color::White, Figure::Bishop) == 1 && self.count(Color::Black, Figure::Bishop) == 1 && black != white
+    }
+
+}
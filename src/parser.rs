@@ -1,10 +1,16 @@
-/// This module holds the supported parsers and formats. Common formats are Fen, ACN, DCN and PGN
+//! This module holds the supported parsers and formats. Common formats are Fen, ACN, DCN and PGN
 
 use crate::core::Figure;    
 use crate::core::Board;
 use crate::core::Piece;
 use crate::core::Square;
 use crate::core::Color;
+use crate::game::{Game, GameMeta, GameResult, GameTree, NodeId, MoveMeta, MarkColor, Arrow, Highlight, Termination, STANDARD_FEN};
+use crate::search::Score;
+use crate::variant::{self, Standard, Variant};
+
+use std::time::Duration;
+
 /// Returns an Option of Board if fen_string is a valid Fen-encoded position
 ///
 /// # Arguments
@@ -25,7 +31,10 @@ pub fn parse_fen(fen_string: &str) -> Result<Board, &str> {
 
     // The first field contains the position as seen by the white player from the last to first rank.
     // However, we are parsing it from to first to the last rank, so it needs to be inverted
-    let mut ranks: Vec<&str> = fields.iter().next().unwrap().split('/').collect();
+    let mut ranks: Vec<&str> = fields[0].split('/').collect();
+    if ranks.len() != 8 {
+        return Err("FEN Parsing Error");
+    }
     ranks.reverse();
     for (r_index,r) in ranks.iter().enumerate() {
         let rank_as_bytes = r.as_bytes();
@@ -37,21 +46,23 @@ pub fn parse_fen(fen_string: &str) -> Result<Board, &str> {
                 return  Err("FEN Parsing Error");
             };
 
-            match *byte as char {
-                '1'..='8' => { 
-                    f_index += *byte - b'1'; 
-                    continue; 
-                },
-                _ => ()
+            // digits skip as many empty squares
+            if let '1'..='8' = *byte as char {
+                f_index += *byte - b'0'; 
+                continue; 
             } 
-            // TODO: if *byte in 1..8 skip as many
 
-            // let piece = Piece{color: Color::White, figure: Piece::from_fen(byte).unwrap(), square: board.get_from_coord(r_index as u8, f_index as u8).unwrap()};
-            let square = board.get_from_coord(f_index as u8, r_index as u8).unwrap();
+            let square = match board.get_from_coord(f_index, r_index as u8) {
+                Some(square) => square,
+                None => return Err("FEN Parsing Error"),
+            };
             let piece = Piece::from_fen(*byte as char, square).unwrap();
             pieces_vec.push(piece);
             f_index += 1;
         };
+        if f_index != 8 {
+            return Err("FEN Parsing Error");
+        }
     };
     for &piece in pieces_vec.iter() {
         // board.pieces.push(piece.unwrap());
@@ -62,6 +73,38 @@ pub fn parse_fen(fen_string: &str) -> Result<Board, &str> {
 }
 
 
+/// Returns the piece placement field of a Fen record for the given board
+///
+/// # Examples
+///
+/// ```
+/// let board = rust_chess::core::Board::new_in_standard_position();
+/// assert_eq!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR", rust_chess::parser::export_fen_position(&board));
+/// ```
+pub fn export_fen_position(board: &Board) -> String {
+    let mut ranks: Vec<String> = vec! [];
+    for rank in board.squares.iter().rev() {
+        let mut rank_str = String::new();
+        let mut empty = 0;
+        for square in rank.iter() {
+            match board.check_square_for_piece(square) {
+                Some(piece) => {
+                    if empty > 0 {
+                        rank_str.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    rank_str.push_str(&piece.to_fen_letter());
+                },
+                None => empty += 1
+            }
+        }
+        if empty > 0 {
+            rank_str.push_str(&empty.to_string());
+        }
+        ranks.push(rank_str);
+    }
+    ranks.join("/")
+}
 
 pub fn is_fen_piece_char(c: char) -> bool {
     let valid_chars = vec!['p', 'r', 'n', 'b', 'k', 'q', 'P', 'R', 'N', 'B', 'K', 'Q', '1', '2', '3', '4', '5', '6', '7', '8'];
@@ -72,7 +115,6 @@ pub fn is_fen_piece_char(c: char) -> bool {
 /// Parse a string representation of a move into tokens which can be interpreted by the game 
 /// The simples way to do this: [start_square]-[end_square]
 /// More advanced methods will follow
-
 pub enum Token {Piece, Square, Takes, Castles, CastlesSide, EnPassant 
 }
 
@@ -174,10 +216,536 @@ impl Piece {
             _ => Color::White,
         };
 
-        Ok(Piece { color: color, figure: figure, square: *pos  })
+        Ok(Piece { color, figure, square: *pos  })
+    }
+
+
+}
+
+
+
+/// Returns the game described by the first game of a PGN text, with its tags, variations, comments and NAGs.
+/// The cursor of the returned game is at the end of the mainline.
+///
+/// Comments may contain the commands `[%clk]`, `[%emt]`, `[%eval]`, `[%cal]` and `[%csl]`, which are read into the `MoveMeta` of the move.
+///
+/// # Examples
+///
+/// ```
+/// let game = rust_chess::parser::parse_pgn("[White \"Anderssen\"]\n\n1. e4 e5 2. Nf3 {A good move} Nc6 *").unwrap();
+/// assert_eq!(Some("Anderssen"), game.meta.as_ref().unwrap().tag("White"));
+/// assert_eq!(4, game.moves().len());
+/// ```
+pub fn parse_pgn(pgn: &str) -> Result<Game, String> {
+    let mut chars = pgn.chars().peekable();
+    let mut meta = GameMeta::new();
+
+    // tag pairs, e.g. [Event "Casual Game"]
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'[').is_none() {
+            break;
+        }
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '"')).collect();
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('"') {
+            return Err(format!("PGN Parsing Error: missing value of tag {}", name));
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some('\\') => value.extend(chars.next()),
+                Some('"') => break,
+                Some(c) => value.push(c),
+                None => return Err(format!("PGN Parsing Error: unterminated value of tag {}", name)),
+            }
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some(']') {
+            return Err(format!("PGN Parsing Error: unterminated tag {}", name));
+        }
+        meta.tags.push((name, value));
+    }
+
+    let variant = match meta.tag("Variant") {
+        Some(name) => Some(variant::from_name(name).ok_or(format!("PGN Parsing Error: unknown variant {}", name))?),
+        None => None,
+    };
+    let mut game = match (meta.tag("FEN"), variant) {
+        (Some(fen), Some(variant)) => Game::from_fen_with_variant(fen, variant).map_err(|e| format!("PGN Parsing Error: {}", e))?,
+        (Some(fen), None) => Game::from_fen(fen).map_err(|e| format!("PGN Parsing Error: {}", e))?,
+        (None, Some(variant)) => Game::with_variant(variant),
+        (None, None) => Game::new(),
+    };
+    let mut result = None;
+    // the nodes to return to at the end of the open variations
+    let mut variations: Vec<NodeId> = vec! [];
+    // a comment in front of the first move of a variation belongs to that move
+    let mut comment_before: Option<String> = None;
+    let mut at_variation_start = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {},
+            '{' | ';' => {
+                let end = if c == '{' { '}' } else { '\n' };
+                let comment: String = std::iter::from_fn(|| chars.next_if(|&c| c != end)).collect();
+                chars.next();
+                if at_variation_start {
+                    comment_before = Some(match comment_before.take() {
+                        Some(previous) => format!("{} {}", previous, comment),
+                        None => comment,
+                    });
+                } else {
+                    let meta = game.move_meta_mut();
+                    parse_comment(&comment, meta);
+                }
+            },
+            '(' => {
+                let cursor = game.cursor();
+                if !game.back() {
+                    return Err(String::from("PGN Parsing Error: variation without a move"));
+                }
+                variations.push(cursor);
+                at_variation_start = true;
+            },
+            ')' => match variations.pop() {
+                Some(node) => {
+                    game.go_to(node);
+                },
+                None => return Err(String::from("PGN Parsing Error: unbalanced parentheses")),
+            },
+            '$' => {
+                let nag: String = std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_digit())).collect();
+                match nag.parse::<u8>() {
+                    Ok(nag) => game.move_meta_mut().nags.push(nag),
+                    Err(_) => return Err(format!("PGN Parsing Error: invalid NAG ${}", nag)),
+                }
+            },
+            // escaped lines, used for non-PGN data
+            '%' => while chars.next_if(|&c| c != '\n').is_some() {},
+            _ => {
+                let mut token = String::from(c);
+                token.extend(std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && !"{}();$".contains(*c))));
+
+                match token.as_str() {
+                    "1-0" => { result = Some(GameResult::WhiteWin); break; },
+                    "0-1" => { result = Some(GameResult::BlackWin); break; },
+                    "1/2-1/2" => { result = Some(GameResult::Draw); break; },
+                    "*" => break,
+                    _ => {},
+                }
+
+                // move numbers, possibly followed by the move without a space, e.g. 1.e4 or 12...Nf6, but not the 0 of 0-0
+                let after_number = token.trim_start_matches(|c: char| c.is_ascii_digit());
+                let san = match after_number.strip_prefix('.') {
+                    Some(rest) => rest.trim_start_matches('.'),
+                    None if after_number.is_empty() => after_number,
+                    None => token.as_str(),
+                };
+                let suffix_start = san.find(['!', '?']).unwrap_or(san.len());
+                let (san, suffix) = san.split_at(suffix_start);
+                if !san.is_empty() {
+                    let mv = game.parse_san(san).map_err(|e| format!("PGN Parsing Error: {} {}", e, san))?;
+                    game.make_move(mv);
+                    let meta = game.move_meta_mut();
+                    if let Some(comment) = comment_before.take() {
+                        // the commands belong to the move, only the text stays in front of it
+                        let after = meta.comment_after.take();
+                        parse_comment(&comment, meta);
+                        meta.comment_before = std::mem::replace(&mut meta.comment_after, after);
+                    }
+                    at_variation_start = false;
+                }
+                if !suffix.is_empty() {
+                    match nag_from_symbol(suffix) {
+                        Some(nag) => game.move_meta_mut().nags.push(nag),
+                        None => return Err(format!("PGN Parsing Error: invalid annotation {}", suffix)),
+                    }
+                }
+            }
+        }
+    }
+    if !variations.is_empty() {
+        return Err(String::from("PGN Parsing Error: unbalanced parentheses"));
     }
 
+    game.go_to_start();
+    game.go_to_end();
+    // keep the reason the rules give for the result, unless the result says otherwise, e.g. a game adjourned in a lost position
+    if game.update_result() != result {
+        game.set_result(result);
+    }
+    game.meta = Some(meta);
+    Ok(game)
+}
 
+/// Splits the text of a PGN file with several games into the text of each game. A game starts with its tags, or its
+/// moves if it has got no tags.
+///
+/// # Examples
+///
+/// ```
+/// let games = rust_chess::parser::split_pgn("[White \"A\"]\n\n1. e4 *\n\n[White \"B\"]\n\n1. d4 *\n");
+/// assert_eq!(vec! ["[White \"A\"]\n\n1. e4 *", "[White \"B\"]\n\n1. d4 *"], games);
+/// ```
+pub fn split_pgn(text: &str) -> Vec<&str> {
+    let mut games = vec! [];
+    let mut start: Option<usize> = None;
+    let mut in_movetext = false;
+    let mut in_comment = false; // comments in braces may span several lines
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if !in_comment {
+            if trimmed.starts_with('[') {
+                // the tags of the next game
+                if let (true, Some(game_start)) = (in_movetext, start) {
+                    games.push(text[game_start..offset].trim_end());
+                    start = None;
+                    in_movetext = false;
+                }
+                start.get_or_insert(offset);
+            } else if !trimmed.is_empty() && !trimmed.starts_with('%') {
+                start.get_or_insert(offset);
+                in_movetext = true;
+            }
+        }
+        for c in line.chars() {
+            match c {
+                '{' => in_comment = true,
+                '}' => in_comment = false,
+                _ => {},
+            }
+        }
+        offset += line.len();
+    }
+    if let Some(game_start) = start {
+        games.push(text[game_start..].trim_end());
+    }
+    games
 }
 
+/// Returns the game as PGN text with all its variations and annotations.
+///
+/// The tags of the game come first, starting with the Seven Tag Roster, where missing tags are filled in with
+/// placeholders. Games of other variants than standard chess get the `Variant` tag, and games which do not start
+/// from the starting position of their variant the `SetUp` and `FEN` tags. Unless the game has got a `Termination` tag,
+/// the reason it ended is written there, if it is not checkmate or stalemate which the moves tell anyway.
+/// The movetext is wrapped at 80 columns.
+///
+/// # Examples
+///
+/// ```
+/// let mut game = rust_chess::game::Game::new();
+/// game.make_move(game.parse_san("e4").unwrap());
+/// assert!(rust_chess::parser::export_pgn(&game).ends_with("\n\n1. e4 *\n"));
+/// ```
+pub fn export_pgn(game: &Game) -> String {
+    let empty = GameMeta::new();
+    let meta = game.meta.as_ref().unwrap_or(&empty);
+    let result = match game.result() {
+        Some(GameResult::WhiteWin) => "1-0",
+        Some(GameResult::BlackWin) => "0-1",
+        Some(GameResult::Draw) => "1/2-1/2",
+        None => meta.tag("Result").unwrap_or("*"),
+    };
 
+    let mut tags: Vec<(&str, &str)> = vec! [];
+    for (name, default) in [("Event", "?"), ("Site", "?"), ("Date", "????.??.??"), ("Round", "?"), ("White", "?"), ("Black", "?")] {
+        tags.push((name, meta.tag(name).unwrap_or(default)));
+    }
+    tags.push(("Result", result));
+    let roster = ["Event", "Site", "Date", "Round", "White", "Black", "Result", "Variant", "SetUp", "FEN"];
+    for (name, value) in meta.tags.iter().filter(|(name, _)| !roster.contains(&name.as_str())) {
+        tags.push((name, value));
+    }
+    let variant = game.variant().name();
+    if variant != Standard.name() {
+        tags.push(("Variant", variant));
+    }
+    // the starting position a reader would set up for the variant
+    let start_fen = variant::from_name(variant).map_or(String::from(STANDARD_FEN), |v| v.start_fen());
+    if game.start_fen != start_fen {
+        tags.push(("SetUp", "1"));
+        tags.push(("FEN", &game.start_fen));
+    }
+    let termination = game.termination()
+        .filter(|termination| !matches!(termination, Termination::Checkmate | Termination::Stalemate))
+        .map(|termination| termination.to_string());
+    if let (Some(termination), None) = (&termination, meta.tag("Termination")) {
+        tags.push(("Termination", termination));
+    }
+
+    let mut pgn = String::new();
+    for (name, value) in tags {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    pgn.push('\n');
+
+    let tree = game.tree();
+    let mut tokens: Vec<String> = vec! [];
+    let root = tree.node(GameTree::ROOT).unwrap();
+    tokens.extend(format_comment(root.meta.comment_after.as_deref(), &root.meta));
+    if let Some(first) = root.mainline {
+        let mut start = game.clone();
+        start.go_to_start();
+        export_line(tree, &start, first, &mut tokens);
+    }
+    tokens.push(String::from(result));
+
+    // wrap the movetext, a move stays on the same line as its number
+    let mut line = String::new();
+    for word in tokens {
+        if !line.is_empty() && line.len() + 1 + word.len() > 80 {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+// writes the move of the node and the mainline following it, with the variations branching off on the way
+fn export_line(tree: &GameTree, position: &Game, node: NodeId, tokens: &mut Vec<String>) {
+    let mut position = position.clone();
+    let mut node = node;
+    // move numbers are written for white moves and black moves following comments or variations
+    let mut needs_number = true;
+    loop {
+        let current = tree.node(node).unwrap();
+        let move_ = current.move_.unwrap();
+        if let Some(comment) = &current.meta.comment_before {
+            tokens.extend(format_comment(Some(comment), &MoveMeta::default()));
+            needs_number = true;
+        }
+        let mut san = match position.current_color() {
+            Color::White => format!("{}. ", position.fullmove_number()),
+            Color::Black if needs_number => format!("{}... ", position.fullmove_number()),
+            Color::Black => String::new(),
+        };
+
+        san.push_str(&position.move_to_san(&move_));
+        let mut nags = current.meta.nags.iter();
+        if let Some(symbol) = current.meta.nags.first().and_then(|&nag| nag_symbol(nag)) {
+            san.push_str(symbol);
+            nags.next();
+        }
+        tokens.push(san);
+        tokens.extend(nags.map(|nag| format!("${}", nag)));
+        let comment = format_comment(current.meta.comment_after.as_deref(), &current.meta);
+        needs_number = !comment.is_empty();
+        tokens.extend(comment);
+
+        // the alternatives to the move follow right after it
+        if tree.is_mainline_child(node) {
+            for variation in &tree.node(current.parent.unwrap()).unwrap().variations {
+                let start = tokens.len();
+                export_line(tree, &position, variation.start, tokens);
+                tokens[start].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+                needs_number = true;
+            }
+        }
+
+        position.make_move(move_);
+        match current.mainline {
+            Some(next) => node = next,
+            None => break,
+        }
+    }
+}
+
+// the words of the comment with its commands, so it can be wrapped without breaking up a command
+fn format_comment(text: Option<&str>, meta: &MoveMeta) -> Vec<String> {
+    let mut parts: Vec<String> = vec! [];
+    if let Some(score) = meta.evaluation {
+        let score = match score {
+            Score::Centipawns(cp) => format!("{:.2}", cp as f64 / 100.0),
+            Score::Mate(moves) => format!("#{}", moves),
+        };
+        match meta.depth {
+            Some(depth) => parts.push(format!("[%eval {},{}]", score, depth)),
+            None => parts.push(format!("[%eval {}]", score)),
+        }
+    }
+    if let Some(clock) = meta.clock {
+        parts.push(format!("[%clk {}]", format_duration(clock)));
+    }
+    if let Some(elapsed) = meta.elapsed {
+        parts.push(format!("[%emt {}]", format_duration(elapsed)));
+    }
+    if !meta.highlights.is_empty() {
+        let squares: Vec<String> = meta.highlights.iter()
+            .map(|h| format!("{}{}", mark_color_letter(h.color), h.square))
+            .collect();
+        parts.push(format!("[%csl {}]", squares.join(",")));
+    }
+    if !meta.arrows.is_empty() {
+        let arrows: Vec<String> = meta.arrows.iter()
+            .map(|a| format!("{}{}{}", mark_color_letter(a.color), a.from, a.to))
+            .collect();
+        parts.push(format!("[%cal {}]", arrows.join(",")));
+    }
+    parts.extend(text.iter().flat_map(|text| text.split_whitespace()).map(String::from));
+
+    if let Some(first) = parts.first_mut() {
+        first.insert(0, '{');
+    }
+    if let Some(last) = parts.last_mut() {
+        last.push('}');
+    }
+    parts
+}
+
+// reads the commands embedded in a comment into the meta, the remaining text is added to the comment after the move
+fn parse_comment(comment: &str, meta: &mut MoveMeta) {
+    let mut text = String::new();
+    let mut rest = comment;
+    while let Some(start) = rest.find("[%") {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find(']') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let command = &rest[start + 2..end];
+        if !parse_command(command, meta) {
+            // keep what we do not understand
+            text.push_str(&rest[start..=end]);
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if !text.is_empty() {
+        meta.comment_after = match meta.comment_after.take() {
+            Some(previous) => Some(format!("{} {}", previous, text)),
+            None => Some(text),
+        };
+    }
+}
+
+fn parse_command(command: &str, meta: &mut MoveMeta) -> bool {
+    let (name, args) = match command.trim().split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => return false,
+    };
+    match name {
+        "clk" => parse_duration(args).map(|clock| meta.clock = Some(clock)).is_some(),
+        "emt" => parse_duration(args).map(|elapsed| meta.elapsed = Some(elapsed)).is_some(),
+        "eval" => {
+            let (score, depth) = match args.split_once(',') {
+                Some((score, depth)) => (score, depth.trim().parse::<u32>().ok()),
+                None => (args, None),
+            };
+            let score = match score.strip_prefix('#') {
+                Some(mate) => mate.parse::<i32>().ok().map(Score::Mate),
+                None => score.parse::<f64>().ok().map(|pawns| Score::Centipawns((pawns * 100.0).round() as i32)),
+            };
+            meta.evaluation = score;
+            meta.depth = depth;
+            score.is_some()
+        },
+        "csl" => {
+            let highlights: Option<Vec<Highlight>> = args.split(',')
+                .map(|arg| {
+                    let arg = arg.trim();
+                    match (arg.chars().next().and_then(mark_color_from_letter), arg.get(1..3).and_then(parse_square)) {
+                        (Some(color), Some(square)) if arg.len() == 3 => Some(Highlight { color, square }),
+                        _ => None,
+                    }
+                })
+                .collect();
+            highlights.map(|highlights| meta.highlights.extend(highlights)).is_some()
+        },
+        "cal" => {
+            let arrows: Option<Vec<Arrow>> = args.split(',')
+                .map(|arg| {
+                    let arg = arg.trim();
+                    match (arg.chars().next().and_then(mark_color_from_letter), arg.get(1..3).and_then(parse_square),
+                           arg.get(3..5).and_then(parse_square)) {
+                        (Some(color), Some(from), Some(to)) if arg.len() == 5 => Some(Arrow { color, from, to }),
+                        _ => None,
+                    }
+                })
+                .collect();
+            arrows.map(|arrows| meta.arrows.extend(arrows)).is_some()
+        },
+        _ => false,
+    }
+}
+
+fn parse_square(s: &str) -> Option<Square> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(f @ 'a'..='h'), Some(r @ '1'..='8'), None) => Some(Square { pos: (f, r) }),
+        _ => None,
+    }
+}
+
+// h:mm:ss with optional fractions of a second
+fn parse_duration(s: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for part in s.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    if seconds >= 0.0 {
+        Some(Duration::from_secs_f64(seconds))
+    } else {
+        None
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let millis = duration.subsec_millis();
+    let mut s = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    if millis > 0 {
+        s.push_str(format!(".{:03}", millis).trim_end_matches('0'));
+    }
+    s
+}
+
+fn mark_color_letter(color: MarkColor) -> char {
+    match color {
+        MarkColor::Green => 'G',
+        MarkColor::Red => 'R',
+        MarkColor::Yellow => 'Y',
+        MarkColor::Blue => 'B',
+    }
+}
+
+fn mark_color_from_letter(c: char) -> Option<MarkColor> {
+    match c {
+        'G' => Some(MarkColor::Green),
+        'R' => Some(MarkColor::Red),
+        'Y' => Some(MarkColor::Yellow),
+        'B' => Some(MarkColor::Blue),
+        _ => None,
+    }
+}
+
+/// Returns the symbol of the move assessment NAGs 1 to 6, e.g. `!?` for NAG 5.
+pub fn nag_symbol(nag: u8) -> Option<&'static str> {
+    match nag {
+        1 => Some("!"),
+        2 => Some("?"),
+        3 => Some("!!"),
+        4 => Some("??"),
+        5 => Some("!?"),
+        6 => Some("?!"),
+        _ => None,
+    }
+}
+
+/// Returns the NAG of a move assessment symbol, e.g. 1 for `!`.
+pub fn nag_from_symbol(symbol: &str) -> Option<u8> {
+    (1..=6).find(|&nag| nag_symbol(nag) == Some(symbol))
+}
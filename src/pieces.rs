@@ -22,26 +22,55 @@ impl Piece {
     }
 
     // Get the squares which can be reached by this piece when performing a capturing move
+    // Only squares with an opposing piece count, see `attacks` and `Board::attacked_squares` for empty and defended squares
     pub fn get_attacked_squares(&self, board: &Board) -> HashSet<Square> {
-        self.get_available_squares(&board).iter()
+        self.get_available_squares(board).iter()
             .filter_map(|sq| board.check_square_for_piece(sq))
             .filter(|p| p.color != self.color)
             .map(|p| p.square)
             .collect::<HashSet<Square>>()
-}
+    }
+
+    /// Whether the piece could capture on the square if an opposing piece stood there, no matter what is on it now.
+    /// Pawns attack the two squares diagonally in front of them, rooks, bishops and queens up to the first piece in their way.
+    pub fn attacks(&self, board: &Board, square: &Square) -> bool {
+        let file = square.file_index() as i8 - self.square.file_index() as i8;
+        let rank = square.rank_index() as i8 - self.square.rank_index() as i8;
+        let forward = if self.color == Color::White { 1 } else { -1 };
+        let straight = (file == 0) != (rank == 0);
+        let diagonal = file != 0 && file.abs() == rank.abs();
+        match self.figure {
+            Figure::Pawn => rank == forward && file.abs() == 1,
+            Figure::Knight => file.abs() * rank.abs() == 2,
+            Figure::King => (file, rank) != (0, 0) && file.abs() <= 1 && rank.abs() <= 1,
+            Figure::Rook if !straight => false,
+            Figure::Bishop if !diagonal => false,
+            Figure::Queen if !straight && !diagonal => false,
+            _ => {
+                // nothing may stand between the piece and the square
+                let direction = (file.signum(), rank.signum());
+                let mut curr_square = self.square.move_by(direction);
+                while let Some(between) = curr_square.filter(|sq| sq != square) {
+                    if board.check_square_for_piece(&between).is_some() {
+                        return false;
+                    }
+                    curr_square = between.move_by(direction);
+                }
+                true
+            },
+        }
+    }
 
     fn get_available_squares_pawn_white(&self, board: &Board) ->  HashSet<Square> {
         let mut results = HashSet::new();
-        let move_up_one = self.square.move_by((0,1)); 
-        if move_up_one.is_some() {
+        if let Some(move_up_one) = self.square.move_by((0,1)) {
             // check there is no piece on that square
-            if board.check_square_for_piece(board.get_from_square_unchecked(&move_up_one.unwrap())).is_none() {
-                results.insert(*board.get_from_square_unchecked(&move_up_one.unwrap()));
+            if board.check_square_for_piece(&move_up_one).is_none() {
+                results.insert(move_up_one);
 
-                let move_up_two = move_up_one.unwrap().move_by((0,1));
-                if self.square.pos.1 == '2' && move_up_two.is_some() {
-                    if board.check_square_for_piece(board.get_from_square_unchecked(&move_up_two.unwrap())).is_none() {
-                        results.insert(*board.get_from_square_unchecked(&move_up_two.unwrap()));
+                if let Some(move_up_two) = move_up_one.move_by((0,1)) {
+                    if self.square.pos.1 == '2' && board.check_square_for_piece(&move_up_two).is_none() {
+                        results.insert(move_up_two);
                     }
                 }
             }
@@ -51,31 +80,26 @@ impl Piece {
         for direction in [(1,1), (-1,1)] {
             let candidate_square = self.square.move_by(direction);
             if candidate_square.is_none() {continue;}
-            match board.check_square_for_piece(&candidate_square.unwrap()) {
-                Some(piece) => {
-                    if piece.color != self.color {
-                        results.insert(*board.get_from_square_unchecked(&candidate_square.unwrap()));
-                    }
-                },
-                None => continue,
+            if let Some(piece) = board.check_square_for_piece(&candidate_square.unwrap()) {
+                if piece.color != self.color {
+                    results.insert(piece.square);
+                }
             }
         }
 
-        results.clone()
+        results
     }
 
     fn get_available_squares_pawn_black(&self, board: &Board) ->  HashSet<Square> {
         let mut results = HashSet::new();
-        let move_up_one = self.square.move_by((0,-1)); 
-        if move_up_one.is_some() {
+        if let Some(move_up_one) = self.square.move_by((0,-1)) {
             // check there is no piece on that square
-            if board.check_square_for_piece(board.get_from_square_unchecked(&move_up_one.unwrap())).is_none() {
-                results.insert(*board.get_from_square_unchecked(&move_up_one.unwrap()));
+            if board.check_square_for_piece(&move_up_one).is_none() {
+                results.insert(move_up_one);
 
-                let move_up_two = move_up_one.unwrap().move_by((0,-1));
-                if self.square.pos.1 == '2' && move_up_two.is_some() {
-                    if board.check_square_for_piece(board.get_from_square_unchecked(&move_up_two.unwrap())).is_none() {
-                        results.insert(*board.get_from_square_unchecked(&move_up_two.unwrap()));
+                if let Some(move_up_two) = move_up_one.move_by((0,-1)) {
+                    if self.square.pos.1 == '7' && board.check_square_for_piece(&move_up_two).is_none() {
+                        results.insert(move_up_two);
                     }
                 }
             }
@@ -85,17 +109,14 @@ impl Piece {
         for direction in [(1,-1), (-1,-1)] {
             let candidate_square = self.square.move_by(direction);
             if candidate_square.is_none() {continue;}
-            match board.check_square_for_piece(&candidate_square.unwrap()) {
-                Some(piece) => {
-                    if piece.color != self.color {
-                        results.insert(*board.get_from_square_unchecked(&candidate_square.unwrap()));
-                    }
-                },
-                None => continue,
+            if let Some(piece) = board.check_square_for_piece(&candidate_square.unwrap()) {
+                if piece.color != self.color {
+                    results.insert(piece.square);
+                }
             }
         }
 
-        results.clone()
+        results
     }
 
     fn get_available_squares_rook(&self, board: &Board) ->  HashSet<Square> {
@@ -146,17 +167,11 @@ impl Piece {
         
         // testing how far the piece can move in either direction
         for direction in directions {
-            let mut curr_square = self.square;
-            if let Some(next_square) = curr_square.move_by(direction) {
-                // check that the square is not occupied
+            if let Some(next_square) = self.square.move_by(direction) {
+                // check that the square is not occupied by a piece of the same color
                 if let Some(other_piece) = board.check_square_for_piece(&next_square) {
-                    match other_piece.color == self.color {
-                        false => {
-                            result.insert(next_square);
-                        },
-                        _ => {
-                            continue
-                        } 
+                    if other_piece.color == self.color {
+                        continue
                     }
                 }    
                 result.insert(next_square);
@@ -176,11 +191,8 @@ impl Piece {
             while let Some(next_square) = curr_square.move_by(direction) {
                 // check that the square is not occupied
                 if let Some(other_piece) = board.check_square_for_piece(&next_square) {
-                    match other_piece.color == self.color {
-                        false => {
-                            result.insert(next_square);
-                        },
-                        _ => {} 
+                    if other_piece.color != self.color {
+                        result.insert(next_square);
                     }
                     break;
                 }
@@ -192,21 +204,4 @@ impl Piece {
 
         result
     }
-
-    fn print_piece(&self) -> String {
-        let str_repr = match self.figure {
-            Figure::Pawn => String::from("p"),
-            Figure::Rook => String::from("r"),
-            Figure::Knight => String::from("n"),
-            Figure::Bishop => String::from("b"),
-            Figure::Queen => String::from("q"),
-            Figure::King =>String::from( "k")
-        };
-
-        if self.color == Color::White {
-            return str_repr.to_ascii_uppercase()
-        }
-        
-        str_repr
-    }
 }
@@ -0,0 +1,115 @@
+//! Renders a board as text for the terminal.
+//!
+//! ```
+//! use rust_chess::parser::parse_fen;
+//! use rust_chess::render::BoardRenderer;
+//!
+//! let board = parse_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+//! let renderer = BoardRenderer { unicode: false, ..BoardRenderer::default() };
+//! assert_eq!(" 8  .  .  .  .  k  .  .  . ", renderer.render(&board).lines().next().unwrap());
+//! ```
+
+use crate::core::{Board, Color, Piece, Square};
+
+use std::collections::HashSet;
+
+const RESET: &str = "\x1b[0m";
+
+/// Options for rendering a board as text. Every square takes three characters, with the piece in the middle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardRenderer {
+    /// The chess symbols like ♔ for the pieces, otherwise their FEN letters.
+    pub unicode: bool,
+    /// Colors the squares and pieces with ANSI escape codes.
+    pub ansi_colors: bool,
+    /// Backgrounds of the squares, as colors of the 256-color palette of ANSI terminals.
+    pub light_color: u8,
+    pub dark_color: u8,
+    pub highlight_color: u8,
+    /// Shows the board from Black's side.
+    pub flipped: bool,
+    /// Labels the ranks on the left and the files below the board.
+    pub coordinates: bool,
+    /// Squares to mark, e.g. the squares a piece can move to. Without colors they are put in brackets.
+    pub highlights: HashSet<Square>,
+}
+
+impl Default for BoardRenderer {
+    fn default() -> Self {
+        BoardRenderer {
+            unicode: true,
+            ansi_colors: false,
+            light_color: 180,
+            dark_color: 137,
+            highlight_color: 107,
+            flipped: false,
+            coordinates: true,
+            highlights: HashSet::new(),
+        }
+    }
+}
+
+impl BoardRenderer {
+    /// Renders the board, one line per rank.
+    pub fn render(&self, board: &Board) -> String {
+        let mut ranks: Vec<usize> = (0..8).collect();
+        let mut files: Vec<usize> = (0..8).collect();
+        if self.flipped {
+            files.reverse();
+        } else {
+            ranks.reverse();
+        }
+
+        let mut text = String::new();
+        for &rank in ranks.iter() {
+            if self.coordinates {
+                text.push_str(&format!(" {} ", rank + 1));
+            }
+            for &file in files.iter() {
+                let square = &board.squares[rank][file];
+                text.push_str(&self.square(square, board.check_square_for_piece(square)));
+            }
+            if self.ansi_colors {
+                text.push_str(RESET);
+            }
+            text.push('\n');
+        }
+        if self.coordinates {
+            text.push_str("   ");
+            for &file in files.iter() {
+                text.push_str(&format!(" {} ", (b'a' + file as u8) as char));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn square(&self, square: &Square, piece: Option<&Piece>) -> String {
+        let highlighted = self.highlights.contains(square);
+        let symbol = match piece {
+            // with colors the filled symbols are used for both sides, which are told apart by their color
+            Some(piece) if self.unicode && self.ansi_colors => Piece { color: Color::Black, ..*piece }.to_symbol(),
+            Some(piece) if self.unicode => piece.to_symbol(),
+            Some(piece) => piece.to_fen_letter(),
+            None if self.ansi_colors => String::from(" "),
+            None if self.unicode => String::from("·"),
+            None => String::from("."),
+        };
+
+        if !self.ansi_colors {
+            return if highlighted { format!("[{}]", symbol) } else { format!(" {} ", symbol) };
+        }
+        let light = (square.file_index() + square.rank_index()) % 2 == 1;
+        let background = match (highlighted, light) {
+            (true, _) => self.highlight_color,
+            (false, true) => self.light_color,
+            (false, false) => self.dark_color,
+        };
+        // bright white and black
+        let foreground = match piece.map(|p| p.color) {
+            Some(Color::White) => 97,
+            _ => 30,
+        };
+        format!("\x1b[48;5;{};{}m {} ", background, foreground, symbol)
+    }
+}
@@ -0,0 +1,594 @@
+//! Distance-to-mate tables for endings with up to four pieces and no pawns, e.g. KQvK, KRvK or KBNvK. They are
+//! computed by retrograde analysis: starting from the checkmates, positions are solved backwards by taking back the
+//! moves of the side which has just moved. Captures lead into the tables of the smaller endings, which are generated
+//! along the way. A table can be saved to a compact file and probed later.
+//!
+//! ```
+//! use rust_chess::core::Color;
+//! use rust_chess::parser::parse_fen;
+//! use rust_chess::retrograde::{Dtm, DtmTable};
+//!
+//! let table = DtmTable::generate("KRvK").unwrap();
+//! let board = parse_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+//! assert_eq!(Some(Dtm::Win(1)), table.probe(&board, Color::White));
+//! ```
+
+use crate::core::{step, Board, Color, Figure, Piece, Square, KING_STEPS, KNIGHT_JUMPS};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const MAGIC: [u8; 4] = *b"RDTM";
+const VERSION: u8 = 1;
+
+const MAX_PIECES: usize = 4;
+
+// values of the positions in a table: draws, positions which can not occur and otherwise the plies to mate plus one
+const DRAW: u8 = 0;
+const INVALID: u8 = 255;
+const MAX_PLIES: usize = 253;
+
+// the squares a1, b1, c1, d1, b2, c2, d2, c3, d3 and d4: every position can be turned so that the white king is on one
+const TRIANGLE: [usize; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0,1), (0,-1), (1,0), (-1,0)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1,1), (1,-1), (-1,1), (-1,-1)];
+
+/// The distance to mate in plies for the side to move, with best play of both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dtm {
+    /// The side to move mates in the given number of plies, which is always odd.
+    Win(u32),
+    /// The side to move gets mated in the given number of plies, 0 if it is checkmated already.
+    Loss(u32),
+    Draw,
+}
+
+/// The distances to mate of all positions of one ending, for both sides to move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DtmTable {
+    // the figures of each side in the order of the table, starting with the king
+    white: Vec<Figure>,
+    black: Vec<Figure>,
+    values: Vec<u8>,
+}
+
+// the pieces of a position of a table, white ones first and each with its square
+type Position = Vec<(Color, Figure, usize)>;
+
+impl DtmTable {
+    /// Solves the ending with the given material, e.g. `KQvKR`, where the pieces before the `v` are White's.
+    /// The tables of the endings reached by captures are solved first.
+    pub fn generate(material: &str) -> Result<DtmTable, String> {
+        let (white, black) = parse_material(material)?;
+        let mut subtables = HashMap::new();
+        Ok(generate(white, black, &mut subtables))
+    }
+
+    /// Reads a table saved by `save`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        DtmTable::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        if bytes.len() < 6 || bytes[0..4] != MAGIC || bytes[4] != VERSION {
+            return Err(invalid("not a distance to mate table"));
+        }
+        let name_length = bytes[5] as usize;
+        let name = bytes.get(6..6 + name_length).and_then(|name| std::str::from_utf8(name).ok())
+            .ok_or_else(|| invalid("truncated table"))?;
+        let (white, black) = parse_material(name).map_err(|e| invalid(&e))?;
+        let size = table_size(white.len() + black.len());
+
+        let lengths: [u8; 256] = bytes.get(6 + name_length..6 + name_length + 256)
+            .ok_or_else(|| invalid("truncated table"))?.try_into().unwrap();
+        if lengths.iter().any(|&length| length > 56) {
+            return Err(invalid("invalid code lengths"));
+        }
+        let symbols = canonical_order(&lengths);
+        let mut counts = [0u64; 57];
+        for &symbol in symbols.iter() {
+            counts[lengths[symbol] as usize] += 1;
+        }
+
+        // the codes are read bit by bit, as the canonical codes of each length follow the ones of the shorter lengths
+        let mut bits = bytes[6 + name_length + 256..].iter().flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) as u64 & 1));
+        let mut values = Vec::with_capacity(size);
+        while values.len() < size {
+            let (mut code, mut first, mut index) = (0u64, 0u64, 0usize);
+            let mut value = None;
+            for &count in counts.iter().skip(1) {
+                code |= bits.next().ok_or_else(|| invalid("truncated table"))?;
+                if code < first + count {
+                    value = Some(symbols[index + (code - first) as usize] as u8);
+                    break;
+                }
+                index += count as usize;
+                first = (first + count) << 1;
+                code <<= 1;
+            }
+            values.push(value.ok_or_else(|| invalid("invalid code"))?);
+        }
+        Ok(DtmTable { white, black, values })
+    }
+
+    /// The table in the file format: a header with the material and the lengths of the Huffman codes for the values,
+    /// followed by the codes of the values of all positions.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let name = self.material();
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(name.len() as u8);
+        bytes.extend(name.bytes());
+
+        let mut counts = [0u64; 256];
+        for &value in self.values.iter() {
+            counts[value as usize] += 1;
+        }
+        let lengths = code_lengths(&counts);
+        bytes.extend(lengths);
+        let mut codes = [0u64; 256];
+        let mut code = 0u64;
+        let mut previous = 0;
+        for symbol in canonical_order(&lengths) {
+            code <<= lengths[symbol] - previous;
+            previous = lengths[symbol];
+            codes[symbol] = code;
+            code += 1;
+        }
+
+        let (mut buffer, mut buffered) = (0u64, 0);
+        for &value in self.values.iter() {
+            let length = lengths[value as usize];
+            for bit in (0..length).rev() {
+                buffer = buffer << 1 | (codes[value as usize] >> bit & 1);
+                buffered += 1;
+                if buffered == 8 {
+                    bytes.push(buffer as u8);
+                    (buffer, buffered) = (0, 0);
+                }
+            }
+        }
+        if buffered > 0 {
+            bytes.push((buffer << (8 - buffered)) as u8);
+        }
+        bytes
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    /// The material of the table, e.g. `KBNvK`.
+    pub fn material(&self) -> String {
+        let letters = |figures: &[Figure]| figures.iter().map(|&f| f.letter()).collect::<String>();
+        format!("{}v{}", letters(&self.white), letters(&self.black))
+    }
+
+    /// The number of positions in the table, including the ones which can not occur.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The longest win in the table, in plies until the mate.
+    pub fn longest_mate(&self) -> u32 {
+        self.values.iter().filter(|&&v| v != INVALID && v != DRAW && v % 2 == 0).map(|&v| v as u32 - 1).max().unwrap_or(0)
+    }
+
+    /// Looks up the position on the board with the given side to move. The table also answers for the material with
+    /// the colors swapped. Returns None if the material does not match, or the side which is not to move is in check.
+    pub fn probe(&self, board: &Board, to_move: Color) -> Option<Dtm> {
+        let position: Position = board.pieces.iter().map(|p| (p.color, p.figure, p.square.index())).collect();
+        match self.value(&position, to_move)? {
+            INVALID => None,
+            DRAW => Some(Dtm::Draw),
+            value if value % 2 == 0 => Some(Dtm::Win(value as u32 - 1)),
+            value => Some(Dtm::Loss(value as u32 - 1)),
+        }
+    }
+
+    fn value(&self, position: &[(Color, Figure, usize)], to_move: Color) -> Option<u8> {
+        if position.len() != self.white.len() + self.black.len() {
+            return None;
+        }
+        let lookup = |swapped: bool| {
+            let color = |c: Color| if swapped { c.opposite() } else { c };
+            let mut squares = [0; MAX_PIECES];
+            let mut count = 0;
+            let mut used = [false; MAX_PIECES];
+            for (side, figures) in [(Color::White, &self.white), (Color::Black, &self.black)] {
+                for &figure in figures.iter() {
+                    let found = (0..position.len())
+                        .find(|&i| !used[i] && color(position[i].0) == side && position[i].1 == figure)?;
+                    used[found] = true;
+                    // with the colors swapped, the board is mirrored from top to bottom as well
+                    squares[count] = if swapped { position[found].2 ^ 56 } else { position[found].2 };
+                    count += 1;
+                }
+            }
+            (count == position.len()).then(|| self.values[index(&squares[..count], color(to_move) == Color::White)])
+        };
+        lookup(false).or_else(|| lookup(true))
+    }
+}
+
+/// The moves the given color could have made to reach the position on the board, as the piece on the board and the
+/// square it came from. Captures and pawn moves are not taken back, and the position before the move is not checked for
+/// legality.
+pub fn unmoves(board: &Board, color: Color) -> Vec<(Piece, Square)> {
+    let occupied = board.pieces.iter().fold(0u64, |occupied, p| occupied | 1 << p.square.index());
+    board.pieces.iter()
+        .filter(|p| p.color == color && p.figure != Figure::Pawn)
+        .flat_map(|p| {
+            squares(attacks(p.figure, p.square.index(), occupied) & !occupied)
+                .map(move |sq| (*p, Square::from_index(sq)))
+        })
+        .collect()
+}
+
+fn parse_material(material: &str) -> Result<(Vec<Figure>, Vec<Figure>), String> {
+    let error = || format!("invalid material {}, expected e.g. KQvK", material);
+    let (white, black) = material.split_once('v').ok_or_else(error)?;
+    let parse = |side: &str| -> Result<Vec<Figure>, String> {
+        let mut figures = side.chars().map(|c| match c {
+            'K' => Ok(Figure::King),
+            'Q' => Ok(Figure::Queen),
+            'R' => Ok(Figure::Rook),
+            'B' => Ok(Figure::Bishop),
+            'N' => Ok(Figure::Knight),
+            'P' => Err(String::from("endings with pawns are not supported")),
+            _ => Err(error()),
+        }).collect::<Result<Vec<Figure>, String>>()?;
+        figures.sort_by_key(|&f| f.order());
+        if figures.iter().filter(|&&f| f == Figure::King).count() != 1 {
+            return Err(error());
+        }
+        Ok(figures)
+    };
+    let (white, black) = (parse(white)?, parse(black)?);
+    if white.len() + black.len() > MAX_PIECES {
+        return Err(format!("{} has more than {} pieces", material, MAX_PIECES));
+    }
+    Ok((white, black))
+}
+
+/// The lengths of the Huffman codes for the byte values which occur as often as given, 0 for the ones which do not.
+fn code_lengths(counts: &[u64; 256]) -> [u8; 256] {
+    let mut lengths = [0; 256];
+    let mut groups: Vec<(u64, Vec<usize>)> = (0..256).filter(|&s| counts[s] > 0).map(|s| (counts[s], vec! [s])).collect();
+    if let [(_, single)] = groups.as_slice() {
+        lengths[single[0]] = 1;
+    }
+    // the two rarest groups are merged, which makes the codes of all their values one bit longer
+    while groups.len() > 1 {
+        groups.sort_by_key(|group| std::cmp::Reverse(group.0));
+        let (count, rarest) = groups.pop().unwrap();
+        let (other_count, other) = groups.pop().unwrap();
+        for &symbol in rarest.iter().chain(other.iter()) {
+            lengths[symbol] += 1;
+        }
+        groups.push((count + other_count, [rarest, other].concat()));
+    }
+    lengths
+}
+
+/// The values with a code, in the order the canonical codes are assigned: by length, then by value.
+fn canonical_order(lengths: &[u8; 256]) -> Vec<usize> {
+    let mut symbols: Vec<usize> = (0..256).filter(|&s| lengths[s] > 0).collect();
+    symbols.sort_by_key(|&s| (lengths[s], s));
+    symbols
+}
+
+fn table_size(pieces: usize) -> usize {
+    TRIANGLE.len() * 64usize.pow(pieces as u32 - 1) * 2
+}
+
+/// The index of a position with the squares of the pieces in the order of the table. All positions which are the same
+/// but for mirroring or turning the board get the same index.
+fn index(squares: &[usize], white_to_move: bool) -> usize {
+    let mirror_file = if squares[0] % 8 > 3 { 7 } else { 0 };
+    let mirror_rank = if squares[0] / 8 > 3 { 56 } else { 0 };
+    let index_of = |flip: bool| {
+        let transform = |sq: usize| {
+            let sq = sq ^ mirror_file ^ mirror_rank;
+            if flip { (sq % 8) * 8 + sq / 8 } else { sq }
+        };
+        let king = TRIANGLE.iter().position(|&sq| sq == transform(squares[0])).unwrap();
+        let index = squares[1..].iter().fold(king, |index, &sq| index * 64 + transform(sq));
+        index * 2 + white_to_move as usize
+    };
+
+    let king = squares[0] ^ mirror_file ^ mirror_rank;
+    match (king / 8).cmp(&(king % 8)) {
+        Ordering::Greater => index_of(true),
+        // on the diagonal the king stays in the triangle either way, take the smaller index
+        Ordering::Equal => index_of(false).min(index_of(true)),
+        Ordering::Less => index_of(false),
+    }
+}
+
+fn decode(mut index: usize, pieces: usize) -> ([usize; MAX_PIECES], bool) {
+    let white_to_move = index % 2 == 1;
+    index /= 2;
+    let mut squares = [0; MAX_PIECES];
+    for sq in squares[1..pieces].iter_mut().rev() {
+        *sq = index % 64;
+        index /= 64;
+    }
+    squares[0] = TRIANGLE[index];
+    (squares, white_to_move)
+}
+
+/// The squares a figure attacks from the square, which are the same it could have come from. Sliders stop at the first
+/// occupied square, which is included.
+fn attacks(figure: Figure, square: usize, occupied: u64) -> u64 {
+    let slide = |directions: &[(i8, i8)]| {
+        let mut bits = 0;
+        for &direction in directions {
+            let mut current = square;
+            while let Some(next) = step(current, direction) {
+                bits |= 1 << next;
+                if occupied & 1 << next != 0 {
+                    break;
+                }
+                current = next;
+            }
+        }
+        bits
+    };
+    match figure {
+        Figure::King | Figure::Knight | Figure::Pawn => rays().empty_board[figure.order()][square],
+        Figure::Rook => slide(&ROOK_DIRECTIONS),
+        Figure::Bishop => slide(&BISHOP_DIRECTIONS),
+        Figure::Queen => slide(&ROOK_DIRECTIONS) | slide(&BISHOP_DIRECTIONS),
+    }
+}
+
+/// Whether a figure attacks the target square, cheaper than looking at all its attacks.
+fn attacks_square(figure: Figure, square: usize, target: usize, occupied: u64) -> bool {
+    let rays = rays();
+    rays.empty_board[figure.order()][square] & 1 << target != 0 && rays.between[square][target] & occupied == 0
+}
+
+struct Rays {
+    // the attacks of each figure in the order of `Figure::order` on an empty board
+    empty_board: [[u64; 64]; 6],
+    // the squares on the line between two squares, empty if they are not on a line
+    between: [[u64; 64]; 64],
+}
+
+fn rays() -> &'static Rays {
+    static RAYS: OnceLock<Box<Rays>> = OnceLock::new();
+    RAYS.get_or_init(|| {
+        let mut rays = Box::new(Rays { empty_board: [[0; 64]; 6], between: [[0; 64]; 64] });
+        for square in 0..64 {
+            let leap = |steps: &[(i8, i8)]| steps.iter().filter_map(|&d| step(square, d)).fold(0, |bits, sq| bits | 1 << sq);
+            rays.empty_board[Figure::King.order()][square] = leap(&KING_STEPS);
+            rays.empty_board[Figure::Knight.order()][square] = leap(&KNIGHT_JUMPS);
+            for figure in [Figure::Queen, Figure::Rook, Figure::Bishop] {
+                rays.empty_board[figure.order()][square] = attacks(figure, square, 0);
+            }
+            for direction in ROOK_DIRECTIONS.iter().chain(BISHOP_DIRECTIONS.iter()) {
+                let mut squares = 0;
+                let mut current = square;
+                while let Some(next) = step(current, *direction) {
+                    rays.between[square][next] = squares;
+                    squares |= 1 << next;
+                    current = next;
+                }
+            }
+        }
+        rays
+    })
+}
+
+fn squares(mut bits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        let square = bits.trailing_zeros() as usize;
+        bits &= bits.wrapping_sub(1);
+        (square < 64).then_some(square)
+    })
+}
+
+/// The colors and figures of the pieces of a table, for positions given by their squares. A captured piece is left
+/// out.
+struct Layout {
+    colors: Vec<Color>,
+    figures: Vec<Figure>,
+}
+
+impl Layout {
+    fn present(&self, captured: Option<usize>) -> impl Iterator<Item = usize> {
+        (0..self.colors.len()).filter(move |&i| Some(i) != captured)
+    }
+
+    fn occupied(&self, squares: &[usize], captured: Option<usize>) -> u64 {
+        self.present(captured).fold(0, |occupied, i| occupied | 1 << squares[i])
+    }
+
+    fn in_check(&self, squares: &[usize], captured: Option<usize>, color: Color) -> bool {
+        let Some(king) = self.present(captured).find(|&i| self.colors[i] == color && self.figures[i] == Figure::King) else {
+            return false;
+        };
+        let occupied = self.occupied(squares, captured);
+        self.present(captured)
+            .any(|i| self.colors[i] != color && attacks_square(self.figures[i], squares[i], squares[king], occupied))
+    }
+
+    fn position(&self, squares: &[usize], captured: Option<usize>) -> Position {
+        self.present(captured).map(|i| (self.colors[i], self.figures[i], squares[i])).collect()
+    }
+}
+
+/// Solves the ending with the positions in the order of `index`, with the subtables of the smaller endings in `subtables`.
+fn generate(white: Vec<Figure>, black: Vec<Figure>, subtables: &mut HashMap<String, DtmTable>) -> DtmTable {
+    let pieces = white.len() + black.len();
+    let size = table_size(pieces);
+    let layout = Layout {
+        colors: white.iter().map(|_| Color::White).chain(black.iter().map(|_| Color::Black)).collect(),
+        figures: white.iter().chain(black.iter()).copied().collect(),
+    };
+    let (colors, figures) = (&layout.colors, &layout.figures);
+
+    // every capture leads into a smaller ending, which is solved first
+    let mut names = vec! [None; pieces];
+    for captured in (1..pieces).filter(|&captured| captured != white.len()) {
+        let (mut smaller_white, mut smaller_black) = (white.clone(), black.clone());
+        if captured < white.len() {
+            smaller_white.remove(captured);
+        } else {
+            smaller_black.remove(captured - white.len());
+        }
+        if smaller_white.len() + smaller_black.len() > 2 {
+            let name = canonical_name(&smaller_white, &smaller_black);
+            if !subtables.contains_key(&name) {
+                let (w, b) = parse_material(&name).unwrap();
+                let table = generate(w, b, subtables);
+                subtables.insert(name.clone(), table);
+            }
+            names[captured] = Some(name);
+        }
+    }
+    let capture_tables: Vec<Option<&DtmTable>> = names.iter().map(|name| name.as_ref().map(|name| &subtables[name])).collect();
+    let probe_capture = |squares: &[usize], captured: usize, to_move: Color| match capture_tables[captured] {
+        Some(table) => table.value(&layout.position(squares, Some(captured)), to_move).unwrap(),
+        // only the kings are left
+        None => DRAW,
+    };
+
+    let mut values = vec! [DRAW; size];
+    // the moves which do not lose yet, the longest loss by a capture, and whether a capture saves the position
+    let mut remaining = vec! [0u8; size];
+    let mut capture_loss = vec! [0u8; size];
+    let mut saved = vec! [false; size];
+    let mut buckets: Vec<Vec<u32>> = vec! [vec! []; MAX_PLIES + 1];
+    let mut duplicates = vec! [];
+    let mut children = vec! [];
+
+    for index in 0..size {
+        let (mut squares, white_to_move) = decode(index, pieces);
+        let squares = &mut squares[..pieces];
+        let to_move = if white_to_move { Color::White } else { Color::Black };
+        if (1..pieces).any(|i| squares[..i].contains(&squares[i])) || layout.in_check(squares, None, to_move.opposite()) {
+            values[index] = INVALID;
+            continue;
+        }
+        let canonical = self::index(squares, white_to_move);
+        if canonical != index {
+            // with the king on the diagonal the position is also in the table mirrored, it gets the value from there
+            duplicates.push((index, canonical));
+            continue;
+        }
+
+        let occupied = layout.occupied(squares, None);
+        let own = (0..pieces).filter(|&i| colors[i] == to_move).fold(0, |own, i| own | 1 << squares[i]);
+        children.clear();
+        let mut legal_moves = 0;
+        for i in (0..pieces).filter(|&i| colors[i] == to_move) {
+            let from = squares[i];
+            for to in self::squares(attacks(figures[i], from, occupied) & !own) {
+                let captured = (0..pieces).find(|&t| squares[t] == to);
+                squares[i] = to;
+                let legal = !layout.in_check(squares, captured, to_move);
+                let capture = captured.filter(|_| legal).map(|captured| probe_capture(squares, captured, to_move.opposite()));
+                let child = (legal && captured.is_none()).then(|| self::index(squares, !white_to_move));
+                squares[i] = from;
+                if !legal {
+                    continue;
+                }
+                legal_moves += 1;
+                match (child, capture) {
+                    (Some(child), _) if !children.contains(&child) => children.push(child),
+                    (_, Some(DRAW)) => saved[index] = true,
+                    // the opponent gets mated
+                    (_, Some(value)) if value % 2 == 1 => {
+                        saved[index] = true;
+                        buckets[value as usize].push(index as u32);
+                    },
+                    (_, Some(value)) => capture_loss[index] = capture_loss[index].max(value),
+                    _ => {},
+                }
+            }
+        }
+
+        remaining[index] = children.len() as u8;
+        if legal_moves == 0 {
+            if layout.in_check(squares, None, to_move) {
+                buckets[0].push(index as u32);
+            }
+            saved[index] = true;
+        } else if children.is_empty() && !saved[index] {
+            buckets[capture_loss[index] as usize].push(index as u32);
+        }
+    }
+
+    // positions are solved by the number of plies to mate, from the checkmates on
+    let mut solved = vec! [false; size];
+    let mut parents = vec! [];
+    for plies in 0..MAX_PLIES {
+        for index in std::mem::take(&mut buckets[plies]) {
+            let index = index as usize;
+            if solved[index] {
+                continue;
+            }
+            solved[index] = true;
+            values[index] = plies as u8 + 1;
+
+            let (mut squares, white_to_move) = decode(index, pieces);
+            let squares = &mut squares[..pieces];
+            let occupied = layout.occupied(squares, None);
+            let moved = if white_to_move { Color::Black } else { Color::White };
+            parents.clear();
+            for i in (0..pieces).filter(|&i| colors[i] == moved) {
+                let to = squares[i];
+                for from in self::squares(attacks(figures[i], to, occupied) & !occupied) {
+                    squares[i] = from;
+                    let parent = self::index(squares, !white_to_move);
+                    squares[i] = to;
+                    if values[parent] != INVALID && !solved[parent] && !parents.contains(&parent) {
+                        parents.push(parent);
+                    }
+                }
+            }
+
+            for &parent in parents.iter() {
+                if plies % 2 == 0 {
+                    // the move to a lost position wins
+                    buckets[plies + 1].push(parent as u32);
+                } else {
+                    remaining[parent] -= 1;
+                    if remaining[parent] == 0 && !saved[parent] {
+                        buckets[(plies + 1).max(capture_loss[parent] as usize)].push(parent as u32);
+                    }
+                }
+            }
+        }
+    }
+
+    for (index, canonical) in duplicates {
+        values[index] = values[canonical];
+    }
+    DtmTable { white, black, values }
+}
+
+/// The name of the table which holds the material, with the stronger side as White.
+fn canonical_name(white: &[Figure], black: &[Figure]) -> String {
+    let strength = |figures: &[Figure]| {
+        (figures.iter().map(|&f| crate::search::figure_value(f)).sum::<i32>(), figures.len(),
+            figures.iter().map(|&f| 5 - f.order()).collect::<Vec<_>>())
+    };
+    let letters = |figures: &[Figure]| figures.iter().map(|&f| f.letter()).collect::<String>();
+    if strength(white) >= strength(black) {
+        format!("{}v{}", letters(white), letters(black))
+    } else {
+        format!("{}v{}", letters(black), letters(white))
+    }
+}
@@ -0,0 +1,240 @@
+//! A simple alpha-beta search on top of the game's move generation.
+//! It is meant to give the engine front-ends something to play with, not to play strong chess.
+
+use crate::core::{Color, Figure, Piece};
+use crate::endgame;
+use crate::game::{Game, Move, MoveMetaFlag};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const MATE: i32 = 100_000;
+const MAX_DEPTH: u32 = 64;
+
+/// Limits for a search, mirroring the parameters of the UCI `go` command.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub infinite: bool,
+}
+
+/// The score of a position from the point of view of the side to move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Mate in the given number of moves, negative if the side to move gets mated.
+    Mate(i32),
+}
+
+/// Progress information which is reported after each completed iteration.
+#[derive(Debug, Clone)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub time: Duration,
+    pub pv: Vec<Move>,
+}
+
+struct Searcher {
+    stop: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    nodes: u64,
+    aborted: bool,
+}
+
+/// Searches the current position and returns the best move found, or `None` if there are no legal moves.
+/// The search runs until the depth limit is reached, the time is up or `stop` is set; `on_info` is called after every completed depth.
+pub fn search<F: FnMut(&SearchInfo)>(game: &Game, limits: &SearchLimits, stop: Arc<AtomicBool>, mut on_info: F) -> Option<Move> {
+    let start = Instant::now();
+    let mut searcher = Searcher { stop, deadline: deadline(game, limits, start), nodes: 0, aborted: false };
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+    let mut root_moves = ordered_moves(game);
+    let mut best: Option<Move> = root_moves.first().copied();
+
+    for depth in 1..=max_depth {
+        let mut alpha = -MATE - 1;
+        let mut best_at_depth: Option<(Move, Vec<Move>)> = None;
+        for mv in root_moves.iter() {
+            let mut pv = vec! [];
+            let mut child = game.position_copy();
+            child.make_move(*mv);
+            let score = -searcher.negamax(&child, depth - 1, 1, -MATE - 1, -alpha, &mut pv);
+            if searcher.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.insert(0, *mv);
+                best_at_depth = Some((*mv, pv));
+            }
+        }
+        if searcher.aborted {
+            break;
+        }
+
+        if let Some((mv, pv)) = best_at_depth {
+            best = Some(mv);
+            // search the best move first in the next iteration
+            root_moves.retain(|m| *m != mv);
+            root_moves.insert(0, mv);
+            on_info(&SearchInfo { depth, score: to_score(alpha), nodes: searcher.nodes, time: start.elapsed(), pv });
+            if alpha.abs() >= MATE - MAX_DEPTH as i32 {
+                break;
+            }
+        } else {
+            break;
+        }
+    }
+
+    best
+}
+
+/// A static evaluation of the position in centipawns, from the point of view of the side to move.
+pub fn evaluate(game: &Game) -> i32 {
+    let score: i32 = game.board.pieces.iter()
+        .map(|p| {
+            let value = figure_value(p.figure) + positional_bonus(p);
+            if p.color == Color::White { value } else { -value }
+        })
+        .sum();
+    let score = endgame::adjust(&game.board, game.current_color(), score);
+
+    match game.current_color() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// The material value of a figure in centipawns.
+pub fn figure_value(figure: Figure) -> i32 {
+    match figure {
+        Figure::Pawn => 100,
+        Figure::Knight => 300,
+        Figure::Bishop => 320,
+        Figure::Rook => 500,
+        Figure::Queen => 900,
+        Figure::King => 0,
+    }
+}
+
+fn positional_bonus(piece: &Piece) -> i32 {
+    // pieces like to be in the center, pawns like to advance
+    let file = piece.square.file_index() as i32;
+    let rank = piece.square.rank_index() as i32;
+    let centrality = 6 - ((2 * file - 7).abs() + (2 * rank - 7).abs()) / 2;
+    match piece.figure {
+        Figure::Pawn => {
+            let advance = if piece.color == Color::White { rank - 1 } else { 6 - rank };
+            advance * 5 + if (2..=5).contains(&file) { centrality } else { 0 }
+        },
+        Figure::Knight | Figure::Bishop => centrality * 3,
+        Figure::Queen => centrality,
+        Figure::Rook | Figure::King => 0,
+    }
+}
+
+impl Searcher {
+
+    fn negamax(&mut self, game: &Game, depth: u32, ply: u32, mut alpha: i32, beta: i32, pv: &mut Vec<Move>) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(256) && self.should_stop() {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        let mut moves = ordered_moves(game);
+        if moves.is_empty() {
+            return if game.in_check() { -MATE + ply as i32 } else { 0 };
+        }
+        if depth == 0 {
+            // quiescence: the side to move may stand pat or continue with captures, to avoid stopping in the middle of an exchange
+            let stand_pat = evaluate(game);
+            if stand_pat >= beta {
+                return beta;
+            }
+            alpha = alpha.max(stand_pat);
+            moves.retain(|mv| is_capture(game, mv));
+        }
+
+        for mv in moves {
+            let mut child_pv = vec! [];
+            let mut child = game.position_copy();
+            child.make_move(mv);
+            let score = -self.negamax(&child, depth.saturating_sub(1), ply + 1, -beta, -alpha, &mut child_pv);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(mv);
+                pv.extend(child_pv);
+            }
+        }
+        alpha
+    }
+
+    fn should_stop(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+}
+
+fn deadline(game: &Game, limits: &SearchLimits, start: Instant) -> Option<Instant> {
+    if limits.infinite {
+        return None;
+    }
+    if let Some(movetime) = limits.movetime {
+        return Some(start + movetime);
+    }
+    let (time, inc) = match game.current_color() {
+        Color::White => (limits.wtime, limits.winc),
+        Color::Black => (limits.btime, limits.binc),
+    };
+    // spend a fraction of the remaining time plus most of the increment
+    time.map(|t| start + t / 30 + inc.unwrap_or_default() * 3 / 4)
+}
+
+fn ordered_moves(game: &Game) -> Vec<Move> {
+    // captures first, the most valuable victim by the least valuable attacker, then a fixed order to keep the search deterministic
+    let mut moves: Vec<(i32, String, Move)> = game.get_legal_moves(game.current_color()).into_iter()
+        .map(|mv| {
+            let victim = game.board.check_square_for_piece(&mv.target_square())
+                .filter(|p| p.color != mv.piece().color)
+                .map_or(0, |p| figure_value(p.figure));
+            let promotion = mv.promotion().map_or(0, figure_value);
+            let order = if victim > 0 { 10 * victim - figure_value(mv.piece().figure) } else { 0 } + promotion;
+            (-order, format!("{}{}", mv.piece().square, mv.target_square()), mv)
+        })
+        .collect();
+    moves.sort_by(|a, b| (a.0, &a.1, a.2.promotion().map(figure_value)).cmp(&(b.0, &b.1, b.2.promotion().map(figure_value))));
+    moves.into_iter().map(|(_, _, mv)| mv).collect()
+}
+
+fn is_capture(game: &Game, mv: &Move) -> bool {
+    game.board.check_square_for_piece(&mv.target_square()).is_some_and(|p| p.color != mv.piece().color)
+        || matches!(mv.flag(), Some(MoveMetaFlag::EnPassant(_)))
+}
+
+fn to_score(score: i32) -> Score {
+    if score >= MATE - MAX_DEPTH as i32 {
+        Score::Mate((MATE - score + 1) / 2)
+    } else if score <= -MATE + MAX_DEPTH as i32 {
+        Score::Mate(-(MATE + score) / 2)
+    } else {
+        Score::Centipawns(score)
+    }
+}
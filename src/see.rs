@@ -0,0 +1,127 @@
+//! Static exchange evaluation: what a sequence of captures on one square wins or loses, without searching the rest of
+//! the position. Both sides capture with their least valuable piece first, see `Board::attackers_of`, and x-ray attackers
+//! join in once the piece in front of them has captured. Pins and checks are not considered.
+//!
+//! ```
+//! use rust_chess::core::{Color, Square};
+//! use rust_chess::parser::parse_fen;
+//!
+//! // the rook takes a pawn which is defended by another pawn
+//! let board = parse_fen("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1").unwrap();
+//! let exchange = board.see(Square::from_str("e5"), Color::White);
+//! assert_eq!(0, exchange.score);
+//! assert_eq!(1, exchange.attackers.len());
+//! assert_eq!(1, exchange.defenders.len());
+//! ```
+
+use crate::core::{Board, Color, Figure, Piece, Square};
+use crate::game::{Game, Move, MoveMetaFlag};
+use crate::search::figure_value;
+
+// high enough that capturing a defended piece with the king never pays
+const KING_VALUE: i32 = 20000;
+
+/// The outcome of the captures on a square.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    /// The material in centipawns the side starting the captures wins, with both sides stopping as soon as capturing
+    /// on does not pay any more.
+    pub score: i32,
+    /// The pieces of the side starting the captures, in the order they capture, x-ray attackers after the pieces in
+    /// front of them.
+    pub attackers: Vec<Piece>,
+    /// The pieces of the other side, in the order they capture back.
+    pub defenders: Vec<Piece>,
+}
+
+impl Board {
+
+    /// The exchange when the color starts capturing on the square, which holds a piece of the other color or is empty.
+    /// The score is never negative, as the color does not have to start capturing at all.
+    pub fn see(&self, square: Square, attacker_color: Color) -> Exchange {
+        let captured = self.check_square_for_piece(&square).filter(|p| p.color != attacker_color).map_or(0, |p| value(p.figure));
+        let mut exchange = exchange(self, square, attacker_color, None, captured, vec! []);
+        exchange.score = exchange.score.max(0);
+        exchange
+    }
+
+}
+
+impl Game {
+
+    /// The exchange which starts with the move, for the side making it. The score is negative if the move loses
+    /// material, e.g. a queen taking a defended pawn. A promotion gains the difference to the pawn, unless the new piece
+    /// is taken. Castling and drops start no exchange.
+    pub fn see(&self, move_: &Move) -> Exchange {
+        let piece = move_.piece();
+        let (captured, removed) = match move_.flag() {
+            Some(MoveMetaFlag::Castling(_)) | Some(MoveMetaFlag::Drop) => {
+                return Exchange { score: 0, attackers: vec! [], defenders: vec! [] };
+            },
+            Some(MoveMetaFlag::EnPassant(pawn)) => (value(Figure::Pawn), vec! [pawn.square]),
+            _ => (self.board.check_square_for_piece(&move_.target_square())
+                .filter(|p| p.color != piece.color).map_or(0, |p| value(p.figure)), vec! []),
+        };
+        exchange(&self.board, move_.target_square(), piece.color, Some((piece, move_.promotion())), captured, removed)
+    }
+
+}
+
+// plays out the captures on the square, starting with the given piece if any, and finds the best point to stop for
+// either side by going backwards through the gains
+fn exchange(board: &Board, square: Square, color: Color, first: Option<(Piece, Option<Figure>)>, captured: i32,
+    removed: Vec<Square>) -> Exchange {
+    // every piece which captures leaves its square, which uncovers the pieces behind it
+    let mut board = board.clone();
+    for square in removed.iter() {
+        board.remove_piece_by_square(square);
+    }
+    let next_capture = |board: &mut Board, side: Color| {
+        let piece = board.attackers_of(square, side).into_iter().min_by_key(|p| value(p.figure))?;
+        board.remove_piece_by_square(&piece.square);
+        Some(piece)
+    };
+
+    let promotion = first.and_then(|(_, promotion)| promotion);
+    let mut first = first.map(|(piece, _)| piece);
+    if let Some(piece) = first {
+        board.remove_piece_by_square(&piece.square);
+    }
+    let mut captures: Vec<Piece> = vec! [];
+    let mut side = color;
+    while let Some(piece) = first.take().or_else(|| next_capture(&mut board, side)) {
+        captures.push(piece);
+        side = side.opposite();
+    }
+
+    // gains[i] is what the side making capture i wins, if the captures end with it
+    let mut gains = vec! [];
+    let mut on_square = captured;
+    for (index, piece) in captures.iter().enumerate() {
+        let mut gain = on_square - gains.last().copied().unwrap_or(0);
+        on_square = value(piece.figure);
+        if let (0, Some(figure)) = (index, promotion) {
+            gain += value(figure) - value(Figure::Pawn);
+            on_square = value(figure);
+        }
+        gains.push(gain);
+    }
+    // a side only captures if that is better than stopping
+    for index in (1..gains.len()).rev() {
+        gains[index - 1] = -(-gains[index - 1]).max(gains[index]);
+    }
+
+    // the side which made the last capture may have pieces left which could have joined in
+    while let Some(piece) = next_capture(&mut board, side.opposite()) {
+        captures.push(piece);
+    }
+    let (attackers, defenders): (Vec<Piece>, Vec<Piece>) = captures.into_iter().partition(|p| p.color == color);
+    Exchange { score: gains.first().copied().unwrap_or(0), attackers, defenders }
+}
+
+fn value(figure: Figure) -> i32 {
+    match figure {
+        Figure::King => KING_VALUE,
+        _ => figure_value(figure),
+    }
+}
@@ -0,0 +1,210 @@
+//! Renders a board to a standalone SVG image, e.g. for diagrams on a web page.
+//!
+//! The pieces are drawn as the Unicode chess symbols in a serif font, so the image needs no further files, but the
+//! viewer needs a font with these symbols, which all common systems have.
+//!
+//! ```
+//! use rust_chess::core::Square;
+//! use rust_chess::parser::parse_fen;
+//! use rust_chess::svg::{Arrow, SvgOptions};
+//!
+//! let board = parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+//! let options = SvgOptions {
+//!     last_move: Some((Square::from_str("e2"), Square::from_str("e4"))),
+//!     arrows: vec! [Arrow::new(Square::from_str("g8"), Square::from_str("f6"))],
+//!     ..SvgOptions::default()
+//! };
+//! let svg = rust_chess::svg::render(&board, &options);
+//! assert_eq!(true, svg.starts_with("<svg"));
+//! ```
+
+use crate::core::{Board, Color, Square};
+
+use std::fmt::Write;
+
+const SQUARE_SIZE: f64 = 45.0;
+const MARGIN: f64 = 20.0;
+
+/// An arrow from one square to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrow {
+    pub from: Square,
+    pub to: Square,
+    pub color: String,
+}
+
+/// A ring around a square.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle {
+    pub square: Square,
+    pub color: String,
+}
+
+/// What to draw besides the pieces, and in which colors. Colors are anything SVG understands, e.g. `#f0d9b5` or
+/// `rgba(21, 120, 27, 0.8)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// The width and height of the image in pixels.
+    pub size: u32,
+    /// Shows the board from Black's side, with a1 in the top right corner.
+    pub flipped: bool,
+    pub light_color: String,
+    pub dark_color: String,
+    /// Labels the files and ranks on the border around the board.
+    pub coordinates: bool,
+    pub last_move: Option<(Square, Square)>,
+    pub last_move_color: String,
+    /// The square of a king in check, which gets a red glow.
+    pub check: Option<Square>,
+    pub arrows: Vec<Arrow>,
+    pub circles: Vec<Circle>,
+}
+
+impl Arrow {
+    /// An arrow in the default green.
+    pub fn new(from: Square, to: Square) -> Arrow {
+        Arrow { from, to, color: String::from("rgba(21, 120, 27, 0.8)") }
+    }
+}
+
+impl Circle {
+    /// A ring in the default green.
+    pub fn new(square: Square) -> Circle {
+        Circle { square, color: String::from("rgba(21, 120, 27, 0.8)") }
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            size: 400,
+            flipped: false,
+            light_color: String::from("#f0d9b5"),
+            dark_color: String::from("#b58863"),
+            coordinates: true,
+            last_move: None,
+            last_move_color: String::from("rgba(205, 210, 106, 0.8)"),
+            check: None,
+            arrows: vec! [],
+            circles: vec! [],
+        }
+    }
+}
+
+/// Renders the board as an SVG document.
+pub fn render(board: &Board, options: &SvgOptions) -> String {
+    let margin = if options.coordinates { MARGIN } else { 0.0 };
+    let width = 8.0 * SQUARE_SIZE + 2.0 * margin;
+    // the top left corner of a square, and its center
+    let corner = |square: &Square| {
+        let (file, rank) = (square.file_index() as f64, square.rank_index() as f64);
+        let (column, row) = if options.flipped { (7.0 - file, rank) } else { (file, 7.0 - rank) };
+        (margin + column * SQUARE_SIZE, margin + row * SQUARE_SIZE)
+    };
+    let center = |square: &Square| {
+        let (x, y) = corner(square);
+        (x + SQUARE_SIZE / 2.0, y + SQUARE_SIZE / 2.0)
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" version="1.1" viewBox="0 0 {w} {w}" width="{s}" height="{s}">"#,
+        w = width, s = options.size);
+    if options.check.is_some() {
+        svg.push_str(concat!(
+            r#"<defs><radialGradient id="check_gradient">"#,
+            r##"<stop offset="0%" stop-color="#ff0000" stop-opacity="1.0"/>"##,
+            r##"<stop offset="50%" stop-color="#e70000" stop-opacity="1.0"/>"##,
+            r##"<stop offset="100%" stop-color="#9e0000" stop-opacity="0.0"/>"##,
+            "</radialGradient></defs>\n"));
+    }
+    if options.coordinates {
+        let _ = writeln!(svg, r##"<rect x="0" y="0" width="{w}" height="{w}" fill="#212121"/>"##, w = width);
+    }
+
+    for rank in board.squares.iter() {
+        for square in rank.iter() {
+            let (x, y) = corner(square);
+            let light = (square.file_index() + square.rank_index()) % 2 == 1;
+            let (class, color) = if light { ("light", &options.light_color) } else { ("dark", &options.dark_color) };
+            let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}" class="square {} {}" fill="{}"/>"#,
+                x, y, class, square, escape(color), size = SQUARE_SIZE);
+            if options.last_move.is_some_and(|(from, to)| from == *square || to == *square) {
+                let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}" class="lastmove {}" fill="{}"/>"#,
+                    x, y, square, escape(&options.last_move_color), size = SQUARE_SIZE);
+            }
+            if options.check == Some(*square) {
+                let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}" class="check" fill="url(#check_gradient)"/>"#,
+                    x, y, size = SQUARE_SIZE);
+            }
+        }
+    }
+
+    for piece in board.pieces.iter() {
+        let (x, y) = center(&piece.square);
+        // the filled symbols for both sides, so that the white pieces are not see-through
+        let symbol = crate::core::Piece { color: Color::Black, ..*piece }.to_symbol();
+        let (fill, stroke) = if piece.color == Color::White { ("#ffffff", "#000000") } else { ("#000000", "#000000") };
+        let _ = writeln!(svg, concat!(r#"<text x="{}" y="{}" class="piece {} {}" font-family="serif" font-size="38" "#,
+            r#"text-anchor="middle" dominant-baseline="central" fill="{}" stroke="{}" stroke-width="1">{}</text>"#),
+            x, y + 2.0, color_name(piece.color), piece.to_fen_letter(), fill, stroke, symbol);
+    }
+
+    if options.coordinates {
+        for index in 0..8u8 {
+            let file = (b'a' + index) as char;
+            let rank = (b'1' + index) as char;
+            let (x, _) = center(&Square { pos: (file, '1') });
+            let (_, y) = center(&Square { pos: ('a', rank) });
+            for (label, x, y) in [(file, x, MARGIN / 2.0), (file, x, width - MARGIN / 2.0), (rank, MARGIN / 2.0, y), (rank, width - MARGIN / 2.0, y)] {
+                let _ = writeln!(svg, concat!(r##"<text x="{}" y="{}" class="coord" font-family="sans-serif" font-size="14" "##,
+                    r##"text-anchor="middle" dominant-baseline="central" fill="#e5e5e5">{}</text>"##), x, y, label);
+            }
+        }
+    }
+
+    for circle in options.circles.iter() {
+        let (x, y) = center(&circle.square);
+        let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" class="circle" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            x, y, SQUARE_SIZE / 2.0 - 3.5, escape(&circle.color), SQUARE_SIZE * 0.1);
+    }
+    for arrow in options.arrows.iter() {
+        let (from, to) = (center(&arrow.from), center(&arrow.to));
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0.0 {
+            continue;
+        }
+        let (ux, uy) = (dx / length, dy / length);
+        // the head ends at the far edge of the target square, the shaft stops at the base of the head
+        let head_length = SQUARE_SIZE * 0.75;
+        let head_width = SQUARE_SIZE * 0.5;
+        let tip = (to.0 + ux * SQUARE_SIZE * 0.4, to.1 + uy * SQUARE_SIZE * 0.4);
+        let base = (tip.0 - ux * head_length, tip.1 - uy * head_length);
+        let color = escape(&arrow.color);
+        let _ = writeln!(svg, r#"<line x1="{}" y1="{}" x2="{}" y2="{}" class="arrow" stroke="{}" stroke-width="{}" stroke-linecap="butt"/>"#,
+            round(from.0), round(from.1), round(base.0), round(base.1), color, SQUARE_SIZE * 0.2);
+        let corners = [tip, (base.0 - uy * head_width / 2.0, base.1 + ux * head_width / 2.0), (base.0 + uy * head_width / 2.0, base.1 - ux * head_width / 2.0)];
+        let points = corners.iter().map(|(x, y)| format!("{},{}", round(*x), round(*y))).collect::<Vec<_>>().join(" ");
+        let _ = writeln!(svg, r#"<polygon points="{}" class="arrow" fill="{}"/>"#, points, color);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn color_name(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+// two decimals are plenty for a drawing
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Escapes a value for an attribute in double quotes.
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
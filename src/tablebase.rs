@@ -0,0 +1,988 @@
+//! Probing Syzygy endgame tablebases. For positions with few pieces the tables tell whether the side to move wins,
+//! draws or loses with best play, and the distance to zero (DTZ): the number of plies to the next capture, pawn move
+//! or mate on the way. A directory holds a `.rtbw` file with the win/draw/loss results and a `.rtbz` file with the
+//! distances for each combination of material, e.g. `KRvK.rtbw` and `KRvK.rtbz`. See <https://syzygy-tables.info>.
+//!
+//! ```no_run
+//! use rust_chess::game::Game;
+//! use rust_chess::tablebase::{Tablebase, Wdl};
+//!
+//! let tablebase = Tablebase::open("syzygy").unwrap();
+//! let game = Game::from_fen("8/8/8/8/8/2k5/8/KQ6 w - - 0 1").unwrap();
+//! assert_eq!(Some(Wdl::Win), tablebase.probe_wdl(&game));
+//! println!("{}", tablebase.rank_root_moves(&game).unwrap()[0].move_);
+//! ```
+
+use crate::core::{Color, Figure, Piece, FIGURE_LETTERS};
+use crate::game::{Game, Move, MoveMetaFlag};
+use crate::variant::{Standard, Variant};
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::Neg;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+
+const MAX_PIECES: usize = 7;
+
+// flags of the compressed tables: all but SINGLE_VALUE are used by DTZ tables only
+const STM: u8 = 1; // the side to move the DTZ table holds, 1 for Black
+const MAPPED: u8 = 2; // the values are indices into the map of the table
+const WIN_PLIES: u8 = 4; // the distances of wins are in plies, not in moves
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16; // the map holds 16-bit values
+const SINGLE_VALUE: u8 = 128; // all positions of the table have the same value
+
+/// The result of a position for the side to move with best play. A cursed win can not be won within the fifty-move
+/// rule, a blessed loss is saved by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Wdl { Loss, BlessedLoss, Draw, CursedWin, Win }
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            i32::MIN..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+
+    fn value(self) -> i32 {
+        self as i32 - 2
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    /// The result for the other side.
+    fn neg(self) -> Wdl {
+        Wdl::from_value(-self.value())
+    }
+}
+
+/// A legal move in the probed position, see `Tablebase::rank_root_moves`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootMove {
+    pub move_: Move,
+    /// The result of the move for the side making it.
+    pub wdl: Wdl,
+    /// The distance to zero in plies counted from before the move: positive for wins, negative for losses and 0 for draws.
+    pub dtz: i32,
+}
+
+/// The tables found in one or more directories. They are read on first use and kept in memory.
+#[derive(Default)]
+#[allow(clippy::type_complexity)]
+pub struct Tablebase {
+    paths: HashMap<(String, TableType), PathBuf>,
+    tables: Mutex<HashMap<(String, TableType), Option<Arc<Table>>>>, // None if the file is broken
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TableType { Wdl, Dtz }
+
+// The outcome of looking up a position in a single table: DTZ tables hold only one side to move.
+enum Probe {
+    Value(i32),
+    ChangeStm,
+}
+
+impl Tablebase {
+
+    /// Finds the tables in the directory.
+    pub fn open<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        let mut tablebase = Tablebase::default();
+        tablebase.add_directory(directory)?;
+        Ok(tablebase)
+    }
+
+    /// Adds the tables in another directory and returns how many files were found there.
+    /// Files named other than the tables, e.g. `KRvK.rtbw`, are ignored.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, directory: P) -> io::Result<usize> {
+        let mut found = 0;
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let table_type = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("rtbw") => TableType::Wdl,
+                Some("rtbz") => TableType::Dtz,
+                _ => continue,
+            };
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) if is_table_name(name) => name.to_string(),
+                _ => continue,
+            };
+            self.tables.get_mut().unwrap().remove(&(name.clone(), table_type));
+            self.paths.insert((name, table_type), path);
+            found += 1;
+        }
+        Ok(found)
+    }
+
+    /// The number of pieces, kings included, of the largest win/draw/loss table found.
+    pub fn max_pieces(&self) -> usize {
+        self.paths.keys()
+            .filter(|(_, table_type)| *table_type == TableType::Wdl)
+            .map(|(name, _)| name.len() - 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The result for the side to move, None if the tables for the position or for the positions after its captures
+    /// are missing, or castling is still possible.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rust_chess::game::Game;
+    /// use rust_chess::tablebase::{Tablebase, Wdl};
+    ///
+    /// let tablebase = Tablebase::open("syzygy").unwrap();
+    /// // Black can take the queen
+    /// let game = Game::from_fen("8/8/8/8/8/8/1k6/KQ6 b - - 0 1").unwrap();
+    /// assert_eq!(Some(Wdl::Draw), tablebase.probe_wdl(&game));
+    /// ```
+    pub fn probe_wdl(&self, game: &Game) -> Option<Wdl> {
+        let game = self.prepare(game)?;
+        self.search(&game, false).map(|(wdl, _)| wdl)
+    }
+
+    /// The distance to zero for the side to move: the plies to the next capture, pawn move or mate of the winning
+    /// side, positive if the side to move wins and negative if it loses, -1 if it is checkmated. Draws give 0.
+    /// Cursed wins and blessed losses count 100 plies more.
+    pub fn probe_dtz(&self, game: &Game) -> Option<i32> {
+        let game = self.prepare(game)?;
+        if game.in_check() && game.get_legal_moves(game.current_color()).is_empty() {
+            return Some(-1);
+        }
+        self.dtz(&game)
+    }
+
+    /// Probes every legal move and ranks them, best first: wins by their distance to zero, then draws,
+    /// then losses, the longest first. A win or loss which the fifty-move rule turns into a draw is ranked
+    /// next to the draws, counting the plies since the last capture or pawn move.
+    pub fn rank_root_moves(&self, game: &Game) -> Option<Vec<RootMove>> {
+        let game = self.prepare(game)?;
+        let mut moves = vec! [];
+        for move_ in game.get_legal_moves(game.current_color()) {
+            let after = game.simulate_move(move_);
+            let wdl = -self.search(&after, false)?.0;
+            let dtz = if after.halfmove_clock() == 0 {
+                dtz_before_zeroing(wdl)
+            } else {
+                let dtz = -self.dtz(&after)?;
+                dtz + dtz.signum()
+            };
+            // a mate is a zeroing move of its own
+            let dtz = if dtz == 2 && after.in_check() && after.get_legal_moves(after.current_color()).is_empty() { 1 } else { dtz };
+            moves.push(RootMove { move_, wdl, dtz });
+        }
+        let halfmove_clock = game.halfmove_clock() as i32;
+        let rank = |root_move: &RootMove| match root_move.dtz {
+            dtz if dtz > 0 && dtz + halfmove_clock <= 100 => 3000 - dtz,
+            dtz if dtz > 0 => 1000 - dtz,
+            0 => 0,
+            dtz if -dtz + halfmove_clock <= 100 => -3000 - dtz,
+            dtz => -1000 - dtz,
+        };
+        moves.sort_by(|a, b| rank(b).cmp(&rank(a)).then_with(|| a.move_.to_uci().cmp(&b.move_.to_uci())));
+        Some(moves)
+    }
+
+    // A copy of the position without history, if it can be probed at all.
+    fn prepare(&self, game: &Game) -> Option<Game> {
+        if game.variant().name() != Standard.name() || game.board.pieces.len() > self.max_pieces().max(2) {
+            return None;
+        }
+        let castling = [(Color::White, true), (Color::White, false), (Color::Black, true), (Color::Black, false)];
+        if castling.iter().any(|&(color, kingside)| game.has_castling_privilege(color, kingside)) {
+            return None;
+        }
+        Game::from_fen(&game.to_fen()).ok()
+    }
+
+    // The tables do not hold the results of positions where a capture is the best move or where en passant is
+    // possible, so the captures are searched first. With `zeroing_pawn_moves` pawn moves are searched as well,
+    // as the DTZ tables do not hold them either. Returns whether a zeroing move is the best move.
+    fn search(&self, game: &Game, zeroing_pawn_moves: bool) -> Option<(Wdl, bool)> {
+        let moves = game.get_legal_moves(game.current_color());
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for move_ in moves.iter() {
+            if !(is_capture(game, move_) || zeroing_pawn_moves && move_.piece().figure == Figure::Pawn) {
+                continue;
+            }
+            searched += 1;
+            let value = -self.search(&game.simulate_move(*move_), false)?.0;
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+        let no_more_moves = searched > 0 && searched == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(game, TableType::Wdl, Wdl::Draw)? {
+                Probe::Value(value) => Wdl::from_value(value),
+                Probe::ChangeStm => return None,
+            }
+        };
+        if best >= value {
+            return Some((best, best > Wdl::Draw || no_more_moves));
+        }
+        Some((value, false))
+    }
+
+    fn dtz(&self, game: &Game) -> Option<i32> {
+        let (wdl, zeroing) = self.search(game, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        if let Probe::Value(dtz) = self.probe_table(game, TableType::Dtz, wdl)? {
+            let cursed = if wdl == Wdl::CursedWin || wdl == Wdl::BlessedLoss { 100 } else { 0 };
+            return Some((dtz + cursed) * wdl.value().signum());
+        }
+
+        // the table holds the other side to move: look one move ahead
+        let mut best = i32::MAX;
+        for move_ in game.get_legal_moves(game.current_color()) {
+            let zeroing = is_capture(game, &move_) || move_.piece().figure == Figure::Pawn;
+            let after = game.simulate_move(move_);
+            let mut dtz = if zeroing {
+                -dtz_before_zeroing(self.search(&after, false)?.0)
+            } else {
+                -self.dtz(&after)?
+            };
+            if dtz == 1 && after.in_check() && after.get_legal_moves(after.current_color()).is_empty() {
+                best = 1;
+            }
+            if !zeroing {
+                dtz += dtz.signum();
+            }
+            if dtz < best && dtz.signum() == wdl.value().signum() {
+                best = dtz;
+            }
+        }
+        Some(if best == i32::MAX { -1 } else { best })
+    }
+
+    fn probe_table(&self, game: &Game, table_type: TableType, wdl: Wdl) -> Option<Probe> {
+        let white = side_letters(game, Color::White);
+        let black = side_letters(game, Color::Black);
+        if white.len() + black.len() == 2 {
+            return Some(Probe::Value(0));
+        }
+        self.table(&table_name(&white, &black), table_type)?.probe(game, &white, wdl)
+    }
+
+    fn table(&self, name: &str, table_type: TableType) -> Option<Arc<Table>> {
+        let key = (name.to_string(), table_type);
+        let mut tables = self.tables.lock().unwrap();
+        if let Some(table) = tables.get(&key) {
+            return table.clone();
+        }
+        let table = self.paths.get(&key)
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|data| Table::new(name, table_type, data))
+            .map(Arc::new);
+        tables.insert(key, table.clone());
+        table
+    }
+}
+
+// A table as read from a file: the pairs data of each side to move and, with pawns, each file of the leading pawn.
+struct Table {
+    data: Vec<u8>,
+    table_type: TableType,
+    white: String, // the pieces of the side the table calls white
+    symmetric: bool, // both sides have the same pieces
+    has_pawns: bool,
+    has_unique_pieces: bool, // a side has exactly one piece of a kind other than the king
+    pawn_count: [usize; 2], // the pawns of the leading color and of the other one
+    map: usize, // where the map of the DTZ values starts
+    items: Vec<Vec<PairsData>>, // [side to move][file of the leading pawn]
+}
+
+// The layout of a compressed table, following the Syzygy probing code.
+#[derive(Debug, Clone, Default)]
+struct PairsData {
+    flags: u8,
+    min_sym_len: u8, // the value of all positions with SINGLE_VALUE
+    block_size: usize,
+    span: usize, // the distance of the indices in the sparse index
+    num_blocks: usize,
+    block_length_size: usize,
+    sparse_index_size: usize,
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<usize>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    map_idx: [usize; 4],
+}
+
+impl Table {
+
+    fn new(name: &str, table_type: TableType, data: Vec<u8>) -> Option<Table> {
+        let magic = match table_type {
+            TableType::Wdl => WDL_MAGIC,
+            TableType::Dtz => DTZ_MAGIC,
+        };
+        if data.len() < 5 || data[..4] != magic {
+            return None;
+        }
+        let (white, black) = name.split_once('v')?;
+        let count = |side: &str, letter: char| side.chars().filter(|&c| c == letter).count();
+        let has_pawns = name.contains('P');
+        if (data[4] & 2 != 0) != has_pawns {
+            return None;
+        }
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        // the side with fewer pawns leads, as this compresses better
+        let pawn_count = if black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns) {
+            [white_pawns, black_pawns]
+        } else {
+            [black_pawns, white_pawns]
+        };
+        let mut table = Table {
+            data,
+            table_type,
+            white: white.to_string(),
+            symmetric: white == black,
+            has_pawns,
+            has_unique_pieces: "QRBNP".chars().any(|letter| count(white, letter) == 1 || count(black, letter) == 1),
+            pawn_count,
+            map: 0,
+            items: vec! [],
+        };
+        let piece_count = name.len() - 1;
+        let sides = if table_type == TableType::Wdl && !table.symmetric { 2 } else { 1 };
+        let files = if has_pawns { 4 } else { 1 };
+        table.items = vec! [vec! [PairsData::default(); files]; sides];
+
+        let both_pawns = has_pawns && pawn_count[1] > 0;
+        let mut at = 5;
+        for file in 0..files {
+            let first = byte(&table.data, at);
+            let second = if both_pawns { byte(&table.data, at + 1) } else { 0xFF };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            at += 1 + both_pawns as usize;
+            for k in 0..piece_count {
+                let pieces = byte(&table.data, at);
+                for side in 0..sides {
+                    table.items[side][file].pieces[k] = if side == 0 { pieces & 0xF } else { pieces >> 4 };
+                }
+                at += 1;
+            }
+            for (side, &order) in order.iter().enumerate().take(sides) {
+                table.set_groups(side, file, order, piece_count);
+            }
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                at = table.items[side][file].set_sizes(&table.data, at);
+            }
+        }
+
+        if table_type == TableType::Dtz {
+            table.map = at;
+            for file in 0..files {
+                let pairs = &mut table.items[0][file];
+                if pairs.flags & MAPPED == 0 {
+                    continue;
+                }
+                if pairs.flags & WIDE != 0 {
+                    at += at & 1;
+                    for i in 0..4 {
+                        pairs.map_idx[i] = (at - table.map) / 2 + 1;
+                        at += 2 * read_le(&table.data, at, 2) as usize + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        pairs.map_idx[i] = at - table.map + 1;
+                        at += byte(&table.data, at) as usize + 1;
+                    }
+                }
+            }
+            at += at & 1;
+        }
+
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.items[side][file];
+                pairs.sparse_index = at;
+                at += pairs.sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.items[side][file];
+                pairs.block_length = at;
+                at += pairs.block_length_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.items[side][file];
+                at = (at + 0x3F) & !0x3F;
+                pairs.data = at;
+                at += pairs.num_blocks * pairs.block_size;
+            }
+        }
+        if at > table.data.len() {
+            return None;
+        }
+        Some(table)
+    }
+
+    // Splits the pieces into the groups which are encoded together: the leading group is formed by the pawns of the
+    // leading color, or by three unique pieces, or else by the kings; the others by pieces of the same kind.
+    // `order` tells where the leading group and the remaining pawns come in the index.
+    fn set_groups(&mut self, side: usize, file: usize, order: [u8; 2], piece_count: usize) {
+        let maps = maps();
+        let (has_pawns, has_unique_pieces) = (self.has_pawns, self.has_unique_pieces);
+        let both_pawns = has_pawns && self.pawn_count[1] > 0;
+        let pairs = &mut self.items[side][file];
+
+        let mut first_len = if has_pawns { 0 } else if has_unique_pieces { 3 } else { 2 };
+        let mut n = 0;
+        pairs.group_len[0] = 1;
+        for i in 1..piece_count {
+            first_len -= 1;
+            if first_len > 0 || pairs.pieces[i] == pairs.pieces[i - 1] {
+                pairs.group_len[n] += 1;
+            } else {
+                n += 1;
+                pairs.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        pairs.group_len[n] = 0;
+
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free_squares = 64 - pairs.group_len[0] - if both_pawns { pairs.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+        let mut k = 0;
+        while next < n || k == order[0] as usize || k == order[1] as usize {
+            if k == order[0] as usize {
+                pairs.group_idx[0] = idx;
+                idx *= if has_pawns {
+                    maps.lead_pawns_size[pairs.group_len[0]][file]
+                } else if has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+            } else if k == order[1] as usize {
+                pairs.group_idx[1] = idx;
+                idx *= maps.binomial[pairs.group_len[1]][48 - pairs.group_len[0]];
+            } else {
+                pairs.group_idx[next] = idx;
+                idx *= maps.binomial[pairs.group_len[next]][free_squares];
+                free_squares -= pairs.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        pairs.group_idx[n] = idx;
+    }
+
+    fn probe(&self, game: &Game, white: &str, wdl: Wdl) -> Option<Probe> {
+        let maps = maps();
+        // the tables hold the positions with the stronger side as White, and only White to move if both sides
+        // have the same pieces: otherwise the colors are swapped and the board is mirrored
+        let black_to_move = game.current_color() == Color::Black;
+        let flip = if self.symmetric { black_to_move } else { white != self.white };
+        let stm = (flip ^ black_to_move) as usize;
+        let (flip_color, flip_squares) = if flip { (8, 56) } else { (0, 0) };
+
+        let mut pieces = [0u8; MAX_PIECES];
+        let mut squares = [0usize; MAX_PIECES];
+        let mut size = 0;
+        let on_board = game.board.pieces.iter()
+            .map(|piece| (piece_code(piece) ^ flip_color, piece.square.index() ^ flip_squares))
+            .collect::<Vec<(u8, usize)>>();
+        if on_board.len() > MAX_PIECES {
+            return None;
+        }
+
+        // with pawns, there is a table for each file of the leading pawn: the one nearest to the edge and
+        // the lowest among those
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        if self.has_pawns {
+            let lead = self.items[0][0].pieces[0];
+            for &(piece, square) in on_board.iter().filter(|(piece, _)| *piece == lead) {
+                pieces[size] = piece;
+                squares[size] = square;
+                size += 1;
+            }
+            lead_pawns = size;
+            let leading = (0..lead_pawns).max_by_key(|&i| maps.pawns[squares[i]])?;
+            squares.swap(0, leading);
+            file = (squares[0] & 7).min(7 - (squares[0] & 7));
+        }
+
+        if self.table_type == TableType::Dtz && !self.has_stm(stm, file) {
+            return Some(Probe::ChangeStm);
+        }
+
+        let lead = pieces[0];
+        for &(piece, square) in on_board.iter().filter(|(piece, _)| !self.has_pawns || *piece != lead) {
+            pieces[size] = piece;
+            squares[size] = square;
+            size += 1;
+        }
+
+        let pairs = &self.items[stm % self.items.len()][file];
+        // order the pieces like the table does
+        for i in lead_pawns..size.saturating_sub(1) {
+            if let Some(j) = (i + 1..size).find(|&j| pairs.pieces[i] == pieces[j]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // mirror the board so that the leading piece is on the files a to d
+        if squares[0] & 7 > 3 {
+            for square in squares[..size].iter_mut() {
+                *square ^= 7;
+            }
+        }
+
+        let mut idx: u64;
+        if self.has_pawns {
+            idx = maps.lead_pawn_idx[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|&square| maps.pawns[square]);
+            for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                idx += maps.binomial[i][maps.pawns[square]];
+            }
+        } else {
+            // and on the ranks 1 to 4, and then below the a1-h8 diagonal
+            if squares[0] >> 3 > 3 {
+                for square in squares[..size].iter_mut() {
+                    *square ^= 56;
+                }
+            }
+            for i in 0..pairs.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for square in squares[i..size].iter_mut() {
+                        *square = flip_diagonal(*square);
+                    }
+                }
+                break;
+            }
+
+            if self.has_unique_pieces {
+                let s = [squares[0] as u64, squares[1] as u64, squares[2] as u64];
+                let adjust1 = (s[1] > s[0]) as u64;
+                let adjust2 = (s[2] > s[0]) as u64 + (s[2] > s[1]) as u64;
+                let rank = |square: u64| square >> 3;
+                idx = if off_diagonal(squares[0]) != 0 {
+                    (maps.a1d1d4[squares[0]] * 63 + s[1] - adjust1) * 62 + s[2] - adjust2
+                } else if off_diagonal(squares[1]) != 0 {
+                    (6 * 63 + rank(s[0]) * 28 + maps.b1h1h7[squares[1]]) * 62 + s[2] - adjust2
+                } else if off_diagonal(squares[2]) != 0 {
+                    6 * 63 * 62 + 4 * 28 * 62 + rank(s[0]) * 7 * 28 + (rank(s[1]) - adjust1) * 28 + maps.b1h1h7[squares[2]]
+                } else {
+                    6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s[0]) * 7 * 6 + (rank(s[1]) - adjust1) * 6 + rank(s[2]) - adjust2
+                };
+            } else {
+                idx = maps.kk[maps.a1d1d4[squares[0]] as usize][squares[1]];
+            }
+        }
+
+        // the other groups, each sorted, skipping the squares taken by the groups before
+        idx *= pairs.group_idx[0];
+        let mut start = pairs.group_len[0];
+        let mut remaining_pawns = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = 1;
+        while pairs.group_len[next] != 0 {
+            let len = pairs.group_len[next];
+            squares[start..start + len].sort();
+            let mut n = 0;
+            for i in 0..len {
+                let square = squares[start + i];
+                let adjust = squares[..start].iter().filter(|&&other| square > other).count();
+                n += maps.binomial[i + 1][square - adjust - if remaining_pawns { 8 } else { 0 }];
+            }
+            remaining_pawns = false;
+            idx += n * pairs.group_idx[next];
+            start += len;
+            next += 1;
+        }
+
+        let value = pairs.decompress(&self.data, idx)?;
+        Some(Probe::Value(self.map_score(file, value, wdl)))
+    }
+
+    fn has_stm(&self, stm: usize, file: usize) -> bool {
+        (self.items[0][file].flags & STM) as usize == stm || (self.symmetric && !self.has_pawns)
+    }
+
+    // Turns a stored value into the result, or into the distance to zero in plies.
+    fn map_score(&self, file: usize, value: i32, wdl: Wdl) -> i32 {
+        if self.table_type == TableType::Wdl {
+            return value - 2;
+        }
+        let pairs = &self.items[0][file];
+        let mut value = value as usize;
+        if pairs.flags & MAPPED != 0 {
+            // the maps are in the order win, loss, cursed win, blessed loss
+            let index = pairs.map_idx[[1, 3, 0, 2, 0][(wdl.value() + 2) as usize]] + value;
+            value = if pairs.flags & WIDE != 0 {
+                read_le(&self.data, self.map + 2 * index, 2) as usize
+            } else {
+                byte(&self.data, self.map + index) as usize
+            };
+        }
+        let mut value = value as i32;
+        if (wdl == Wdl::Win && pairs.flags & WIN_PLIES == 0) || (wdl == Wdl::Loss && pairs.flags & LOSS_PLIES == 0)
+            || wdl == Wdl::CursedWin || wdl == Wdl::BlessedLoss {
+            value *= 2;
+        }
+        value + 1
+    }
+}
+
+impl PairsData {
+
+    // Reads the sizes of the compressed data and the canonical Huffman code, returns where the next ones start.
+    fn set_sizes(&mut self, data: &[u8], mut at: usize) -> usize {
+        self.flags = byte(data, at);
+        if self.flags & SINGLE_VALUE != 0 {
+            self.min_sym_len = byte(data, at + 1);
+            return at + 2;
+        }
+        let groups = self.group_len.iter().position(|&len| len == 0).unwrap_or(MAX_PIECES);
+        let size = self.group_idx[groups] as usize;
+
+        self.block_size = 1 << byte(data, at + 1).min(32);
+        self.span = 1 << byte(data, at + 2).min(32);
+        self.sparse_index_size = size.div_ceil(self.span);
+        let padding = byte(data, at + 3) as usize;
+        self.num_blocks = read_le(data, at + 4, 4) as usize;
+        self.block_length_size = self.num_blocks + padding;
+        let max_sym_len = byte(data, at + 8) as usize;
+        self.min_sym_len = byte(data, at + 9);
+        at += 10;
+        self.lowest_sym = at;
+
+        // the lowest code of each length, padded to 64 bits: longer codes have lower values
+        let min_sym_len = self.min_sym_len as usize;
+        let lengths = (max_sym_len + 1).saturating_sub(min_sym_len).max(1);
+        self.base64 = vec! [0; lengths];
+        for i in (0..lengths - 1).rev() {
+            let lowest = |i: usize| read_le(data, self.lowest_sym + 2 * i, 2);
+            self.base64[i] = self.base64[i + 1].wrapping_add(lowest(i)).wrapping_sub(lowest(i + 1)) / 2;
+        }
+        for (i, base) in self.base64.iter_mut().enumerate() {
+            *base = base.checked_shl(64usize.saturating_sub(i + min_sym_len) as u32).unwrap_or(0);
+        }
+        at += 2 * lengths;
+
+        // each symbol stands for one value or for a pair of symbols
+        let symbols = read_le(data, at, 2) as usize;
+        at += 2;
+        self.btree = at;
+        self.symlen = vec! [0; symbols];
+        let mut visited = vec! [false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                self.symlen[symbol] = self.set_symlen(data, symbol, &mut visited);
+            }
+        }
+        at + 3 * symbols + (symbols & 1)
+    }
+
+    // The number of values a symbol stands for, less one.
+    fn set_symlen(&mut self, data: &[u8], symbol: usize, visited: &mut [bool]) -> usize {
+        visited[symbol] = true;
+        let (left, right) = (self.left(data, symbol), self.right(data, symbol));
+        if right == 0xFFF || left >= self.symlen.len() || right >= self.symlen.len() {
+            return 0;
+        }
+        if !visited[left] {
+            self.symlen[left] = self.set_symlen(data, left, visited);
+        }
+        if !visited[right] {
+            self.symlen[right] = self.set_symlen(data, right, visited);
+        }
+        self.symlen[left] + self.symlen[right] + 1
+    }
+
+    fn left(&self, data: &[u8], symbol: usize) -> usize {
+        let at = self.btree + 3 * symbol;
+        (((byte(data, at + 1) & 0xF) as usize) << 8) | byte(data, at) as usize
+    }
+
+    fn right(&self, data: &[u8], symbol: usize) -> usize {
+        let at = self.btree + 3 * symbol;
+        ((byte(data, at + 2) as usize) << 4) | (byte(data, at + 1) >> 4) as usize
+    }
+
+    // The value at the index, None if the data is broken.
+    fn decompress(&self, data: &[u8], idx: u64) -> Option<i32> {
+        if self.flags & SINGLE_VALUE != 0 {
+            return Some(self.min_sym_len as i32);
+        }
+        if self.span == 0 || idx as usize >= self.sparse_index_size * self.span {
+            return None;
+        }
+        let idx = idx as usize;
+        let block_length = |block: usize| read_le(data, self.block_length + 2 * block, 2) as i64;
+
+        // the sparse index tells the block and the offset in it of every span-th value, the blocks
+        // around it are walked from there
+        let entry = self.sparse_index + 6 * (idx / self.span);
+        let mut block = read_le(data, entry, 4) as usize;
+        let mut offset = read_le(data, entry + 4, 2) as i64 + (idx % self.span) as i64 - (self.span / 2) as i64;
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block) + 1;
+        }
+        while offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+            if block >= self.block_length_size {
+                return None;
+            }
+        }
+
+        // find the symbol holding the value in the block, reading the codes into a 64-bit buffer
+        let min_sym_len = self.min_sym_len as usize;
+        let mut at = self.data + block * self.block_size;
+        let mut buffer = read_be(data, at, 8);
+        let mut buffered = 64;
+        at += 8;
+        let mut symbol;
+        loop {
+            let mut len = 0;
+            while len + 1 < self.base64.len() && buffer < self.base64[len] {
+                len += 1;
+            }
+            symbol = (buffer - self.base64[len]).checked_shr((64 - len - min_sym_len) as u32).unwrap_or(0) as usize;
+            symbol += read_le(data, self.lowest_sym + 2 * len, 2) as usize;
+            let values = *self.symlen.get(symbol)? as i64 + 1;
+            if offset < values {
+                break;
+            }
+            offset -= values;
+            let len = len + min_sym_len;
+            buffer = buffer.checked_shl(len as u32).unwrap_or(0);
+            buffered -= len;
+            if buffered <= 32 {
+                buffered += 32;
+                buffer |= read_be(data, at, 4) << (64 - buffered);
+                at += 4;
+            }
+        }
+
+        // and expand the pair the symbol stands for down to the value
+        while self.symlen[symbol] != 0 {
+            let left = self.left(data, symbol);
+            let values = *self.symlen.get(left)? as i64 + 1;
+            if offset < values {
+                symbol = left;
+            } else {
+                offset -= values;
+                symbol = self.right(data, symbol);
+            }
+            self.symlen.get(symbol)?;
+        }
+        Some(self.left(data, symbol) as i32)
+    }
+}
+
+// The tables of the index encoding.
+struct Maps {
+    binomial: [[u64; 64]; MAX_PIECES],
+    pawns: [usize; 64], // a2-h7 to 47..0, the leading pawn has the highest value
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+    a1d1d4: [u64; 64], // the triangle a1-d1-d4 to 0..9, the diagonal last
+    b1h1h7: [u64; 64], // the squares below the a1-h8 diagonal to 0..27
+    kk: [[u64; 64]; 10], // the 462 positions of both kings, the first one in the triangle a1-d1-d4
+}
+
+fn maps() -> &'static Maps {
+    static MAPS: OnceLock<Maps> = OnceLock::new();
+    MAPS.get_or_init(Maps::new)
+}
+
+impl Maps {
+    fn new() -> Maps {
+        let mut maps = Maps {
+            binomial: [[0; 64]; MAX_PIECES],
+            pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+            a1d1d4: [0; 64],
+            b1h1h7: [0; 64],
+            kk: [[0; 64]; 10],
+        };
+
+        for (code, square) in (0..64).filter(|&square| off_diagonal(square) < 0).enumerate() {
+            maps.b1h1h7[square] = code as u64;
+        }
+
+        let triangle = (0..28).filter(|&square| square & 7 <= 3 && off_diagonal(square) <= 0);
+        let (diagonal, below): (Vec<usize>, Vec<usize>) = triangle.partition(|&square| off_diagonal(square) == 0);
+        let triangle = below.into_iter().chain(diagonal).collect::<Vec<usize>>();
+        for (code, &square) in triangle.iter().enumerate() {
+            maps.a1d1d4[square] = code as u64;
+        }
+
+        // with the first king on the diagonal, the other one is not above it; both on the diagonal come last
+        let mut code = 0;
+        let mut both_on_diagonal = vec! [];
+        for (idx, &first) in triangle.iter().enumerate() {
+            for second in 0..64 {
+                let apart = (first & 7).abs_diff(second & 7).max((first >> 3).abs_diff(second >> 3));
+                if apart <= 1 || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                    continue;
+                }
+                if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                    both_on_diagonal.push((idx, second));
+                } else {
+                    maps.kk[idx][second] = code;
+                    code += 1;
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            maps.kk[idx][second] = code;
+            code += 1;
+        }
+
+        maps.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                maps.binomial[k][n] = if k > 0 { maps.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { maps.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 48;
+        for lead_pawns in 1..MAX_PIECES {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..7 {
+                    let square = 8 * rank + file;
+                    if lead_pawns == 1 {
+                        available -= 2;
+                        maps.pawns[square] = available + 1;
+                        maps.pawns[square ^ 7] = available;
+                    }
+                    maps.lead_pawn_idx[lead_pawns][square] = idx;
+                    idx += maps.binomial[lead_pawns - 1][maps.pawns[square]];
+                }
+                maps.lead_pawns_size[lead_pawns][file] = idx;
+            }
+        }
+        maps
+    }
+}
+
+// The distance to zero of the move before a zeroing move with the result after it.
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+fn is_capture(game: &Game, move_: &Move) -> bool {
+    matches!(move_.flag(), Some(MoveMetaFlag::EnPassant(_)))
+        || game.board.check_square_for_piece(&move_.target_square()).is_some_and(|piece| piece.color != move_.piece().color)
+}
+
+fn side_letters(game: &Game, color: Color) -> String {
+    let mut figures = game.board.pieces.iter()
+        .filter(|piece| piece.color == color)
+        .map(|piece| piece.figure)
+        .collect::<Vec<Figure>>();
+    figures.sort_by_key(|figure| figure.order());
+    figures.into_iter().map(|figure| figure.letter()).collect()
+}
+
+// The name of the table for the pieces of both sides, the stronger side first.
+fn table_name(white: &str, black: &str) -> String {
+    let order = |side: &str| side.chars().filter_map(|letter| FIGURE_LETTERS.find(letter)).collect::<Vec<usize>>();
+    if (white.len(), order(black)) < (black.len(), order(white)) {
+        format!("{}v{}", black, white)
+    } else {
+        format!("{}v{}", white, black)
+    }
+}
+
+fn is_table_name(name: &str) -> bool {
+    match name.split_once('v') {
+        Some((white, black)) => [white, black].iter().all(|side| {
+                let order = side.chars().map(|letter| FIGURE_LETTERS.find(letter)).collect::<Option<Vec<usize>>>();
+                side.starts_with('K') && side.matches('K').count() == 1
+                    && order.is_some_and(|order| order.windows(2).all(|pair| pair[0] <= pair[1]))
+            })
+            && name.len() - 1 <= MAX_PIECES
+            && table_name(white, black) == name,
+        None => false,
+    }
+}
+
+// Pieces as the tables number them: 1 to 6 for pawn, knight, bishop, rook, queen and king, plus 8 for Black.
+fn piece_code(piece: &Piece) -> u8 {
+    let code = match piece.figure {
+        Figure::Pawn => 1,
+        Figure::Knight => 2,
+        Figure::Bishop => 3,
+        Figure::Rook => 4,
+        Figure::Queen => 5,
+        Figure::King => 6,
+    };
+    if piece.color == Color::White { code } else { code + 8 }
+}
+
+fn off_diagonal(square: usize) -> i32 {
+    (square >> 3) as i32 - (square & 7) as i32
+}
+
+fn flip_diagonal(square: usize) -> usize {
+    ((square >> 3) | (square << 3)) & 63
+}
+
+fn byte(data: &[u8], at: usize) -> u8 {
+    data.get(at).copied().unwrap_or(0)
+}
+
+fn read_le(data: &[u8], at: usize, len: usize) -> u64 {
+    (0..len).rev().fold(0, |value, i| value << 8 | byte(data, at + i) as u64)
+}
+
+fn read_be(data: &[u8], at: usize, len: usize) -> u64 {
+    (0..len).fold(0, |value, i| value << 8 | byte(data, at + i) as u64)
+}
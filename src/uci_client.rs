@@ -0,0 +1,424 @@
+//! Client side of the Universal Chess Interface (UCI).
+//! Runs an external engine as a child process and translates between its text protocol and the crate's `Game` and `Move` types.
+//!
+//! ```no_run
+//! use rust_chess::game::Game;
+//! use rust_chess::search::SearchLimits;
+//! use rust_chess::uci_client::Engine;
+//!
+//! let mut engine = Engine::spawn("stockfish", &[]).unwrap();
+//! let mut game = Game::new();
+//! game.move_from_str("e2-e4");
+//! engine.set_position(&game).unwrap();
+//! let result = engine.go(&SearchLimits { depth: Some(12), ..Default::default() }).unwrap();
+//! println!("{}", result.best_move.unwrap());
+//! ```
+
+use crate::core::Color;
+use crate::game::{Game, Move, STANDARD_FEN};
+use crate::search::{Score, SearchLimits};
+use crate::variant::{Chess960, Variant};
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Keywords which may follow each other in an `info` line.
+const INFO_KEYWORDS: [&str; 17] = ["depth", "seldepth", "time", "nodes", "pv", "multipv", "score", "currmove", "currmovenumber",
+    "hashfull", "nps", "tbhits", "sbhits", "cpuload", "string", "refutation", "currline"];
+
+/// A running engine process.
+pub struct Engine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    position: Option<Game>,
+    search: Option<RunningSearch>,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub options: Vec<EngineOption>,
+    /// How long to wait for the engine to answer `uci` and `isready`, and to send its best move once the search
+    /// should be over. Searches without a time limit may not stay silent for longer.
+    pub timeout: Duration,
+}
+
+// a search which was started and not yet answered with `bestmove`
+struct RunningSearch {
+    time: Option<Duration>, // the time the engine may use, from `movetime` or its clock
+    started: Option<Instant>, // when the engine's clock started, None while pondering
+}
+
+/// An option as announced by the engine during the handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: String,
+    pub default: Option<String>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub vars: Vec<String>,
+}
+
+/// The data of an `info` line; all fields are optional as engines send them in any combination.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score: Option<Score>,
+    pub bound: Option<Bound>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub time: Option<Duration>,
+    pub pv: Vec<Move>,
+    pub string: Option<String>,
+}
+
+/// Marks a score which is only a lower or upper bound of the real score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound { Lower, Upper }
+
+/// What the engine sent for a search: its `bestmove`, the optional `ponder` move and all `info` lines on the way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub ponder: Option<Move>,
+    pub infos: Vec<EngineInfo>,
+}
+
+#[derive(Debug)]
+pub enum UciError {
+    Io(io::Error),
+    /// The engine did not answer within `Engine::timeout`.
+    Timeout,
+    /// The engine closed its output, most likely it has terminated.
+    Disconnected,
+    /// A search was started before a position was set.
+    NoPosition,
+    /// The engine sent a move which is not legal in the position.
+    IllegalMove(String),
+    /// A search was waited for or stopped, but none is running.
+    NoSearch,
+}
+
+impl Engine {
+
+    /// Starts the engine and performs the `uci` handshake.
+    pub fn spawn<S: AsRef<OsStr>>(program: S, args: &[&str]) -> Result<Self, UciError> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // read the engine's output on a separate thread, so waiting for an answer can time out
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() { break },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut engine = Engine {
+            child, stdin, lines,
+            position: None,
+            search: None,
+            name: None,
+            author: None,
+            options: vec! [],
+            timeout: Duration::from_secs(10),
+        };
+        engine.handshake()?;
+        Ok(engine)
+    }
+
+    fn handshake(&mut self) -> Result<(), UciError> {
+        self.send("uci")?;
+        loop {
+            let line = self.read_line(Some(self.timeout))?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                ["uciok", ..] => return Ok(()),
+                ["id", "name", name @ ..] => self.name = Some(name.join(" ")),
+                ["id", "author", author @ ..] => self.author = Some(author.join(" ")),
+                ["option", rest @ ..] => self.options.extend(parse_option(rest)),
+                _ => {},
+            }
+        }
+    }
+
+    /// Sends a raw command to the engine.
+    pub fn send(&mut self, command: &str) -> Result<(), UciError> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    fn read_line(&mut self, timeout: Option<Duration>) -> Result<String, UciError> {
+        match timeout {
+            Some(timeout) => self.lines.recv_timeout(timeout).map_err(|e| match e {
+                RecvTimeoutError::Timeout => UciError::Timeout,
+                RecvTimeoutError::Disconnected => UciError::Disconnected,
+            }),
+            None => self.lines.recv().map_err(|_| UciError::Disconnected),
+        }
+    }
+
+    /// Sends `isready` and waits for `readyok`.
+    pub fn is_ready(&mut self) -> Result<(), UciError> {
+        self.send("isready")?;
+        while self.read_line(Some(self.timeout))? != "readyok" {}
+        Ok(())
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), UciError> {
+        if value.is_empty() {
+            self.send(&format!("setoption name {}", name))
+        } else {
+            self.send(&format!("setoption name {} value {}", name, value))
+        }
+    }
+
+    /// Tells the engine that the next position belongs to a different game.
+    pub fn new_game(&mut self) -> Result<(), UciError> {
+        self.send("ucinewgame")?;
+        self.is_ready()
+    }
+
+    /// Sends the game's starting position and the moves played since then.
+    /// In Chess960 castling is sent as the king taking its own rook, as engines expect with `UCI_Chess960` set.
+    pub fn set_position(&mut self, game: &Game) -> Result<(), UciError> {
+        let mut command = if game.start_fen == STANDARD_FEN {
+            String::from("position startpos")
+        } else {
+            format!("position fen {}", game.start_fen)
+        };
+        let moves = game.moves();
+        if !moves.is_empty() {
+            let moves: Vec<String> = moves.iter().map(|mv| mv.to_uci_with(king_takes_rook(game))).collect();
+            command.push_str(" moves ");
+            command.push_str(&moves.join(" "));
+        }
+        self.send(&command)?;
+        self.position = Some(game.clone());
+        Ok(())
+    }
+
+    /// Starts a search on the current position and blocks until the engine sends its best move.
+    /// Infinite searches have to be started with `start` and ended with `stop` instead.
+    pub fn go(&mut self, limits: &SearchLimits) -> Result<SearchResult, UciError> {
+        self.start(limits, false)?;
+        self.wait()
+    }
+
+    /// Starts a search without waiting for its result, see `wait` and `stop`.
+    /// With `ponder` the engine searches the position while it is the opponent's turn, until `ponderhit` or `stop`.
+    pub fn start(&mut self, limits: &SearchLimits, ponder: bool) -> Result<(), UciError> {
+        if self.position.is_none() {
+            return Err(UciError::NoPosition);
+        }
+        let mut command = String::from("go");
+        if ponder {
+            command.push_str(" ponder");
+        }
+        let millis = [("wtime", limits.wtime), ("btime", limits.btime), ("winc", limits.winc), ("binc", limits.binc), ("movetime", limits.movetime)];
+        for (name, value) in millis {
+            if let Some(value) = value {
+                command.push_str(&format!(" {} {}", name, value.as_millis()));
+            }
+        }
+        if let Some(depth) = limits.depth {
+            command.push_str(&format!(" depth {}", depth));
+        }
+        if limits.infinite {
+            command.push_str(" infinite");
+        }
+        self.send(&command)?;
+
+        let color = self.position.as_ref().unwrap().current_color();
+        let time = match (limits.movetime, color) {
+            _ if limits.infinite => None,
+            (Some(movetime), _) => Some(movetime),
+            (None, Color::White) => limits.wtime.map(|t| t + limits.winc.unwrap_or_default()),
+            (None, Color::Black) => limits.btime.map(|t| t + limits.binc.unwrap_or_default()),
+        };
+        self.search = Some(RunningSearch { time, started: (!ponder).then(Instant::now) });
+        Ok(())
+    }
+
+    /// Tells a pondering engine that the opponent played the expected move, the search continues as a normal one.
+    pub fn ponderhit(&mut self) -> Result<(), UciError> {
+        if let Some(search) = self.search.as_mut() {
+            search.started = Some(Instant::now());
+        }
+        self.send("ponderhit")
+    }
+
+    /// Stops the running search and returns its result.
+    pub fn stop(&mut self) -> Result<SearchResult, UciError> {
+        // the engine has to answer right away now
+        let search = self.search.as_mut().ok_or(UciError::NoSearch)?;
+        search.time = None;
+        self.send("stop")?;
+        self.wait()
+    }
+
+    /// Collects the `info` lines of the running search until the engine sends `bestmove`.
+    /// Fails with `UciError::Timeout` if the engine does not send it within `timeout` after its time is up,
+    /// or stays silent for longer than `timeout` in a search without a time limit.
+    pub fn wait(&mut self) -> Result<SearchResult, UciError> {
+        let position = self.position.clone().ok_or(UciError::NoPosition)?;
+        let deadline = match self.search.as_ref().ok_or(UciError::NoSearch)? {
+            RunningSearch { time: Some(time), started: Some(started) } => Some(*started + *time + self.timeout),
+            _ => None,
+        };
+        let mut result = SearchResult::default();
+        loop {
+            let timeout = deadline.map_or(self.timeout, |d| d.saturating_duration_since(Instant::now()));
+            let line = self.read_line(Some(timeout))?;
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens.as_slice() {
+                ["info", rest @ ..] => result.infos.push(parse_info(rest, &position)),
+                ["bestmove", best, rest @ ..] => {
+                    self.search = None;
+                    if *best != "0000" && *best != "(none)" {
+                        let mut game = position.clone();
+                        let best_move = game.parse_uci_move_with(best, king_takes_rook(&position)).map_err(|_| UciError::IllegalMove(best.to_string()))?;
+                        game.make_move(best_move);
+                        result.best_move = Some(best_move);
+                        if let ["ponder", ponder, ..] = rest {
+                            result.ponder = game.parse_uci_move_with(ponder, king_takes_rook(&game)).ok();
+                        }
+                    }
+                    return Ok(result);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Asks the engine to terminate and waits for the process to end.
+    pub fn quit(mut self) -> Result<(), UciError> {
+        self.send("quit")?;
+        self.child.wait()?;
+        Ok(())
+    }
+
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // do not leave the process running if the engine was not quit properly
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+fn parse_option(tokens: &[&str]) -> Option<EngineOption> {
+    // option name <id> type <t> [default <x>] [min <x>] [max <x>] [var <x>]*
+    let keywords = ["name", "type", "default", "min", "max", "var"];
+    let mut option = EngineOption { name: String::new(), kind: String::new(), default: None, min: None, max: None, vars: vec! [] };
+    let mut i = 0;
+    while i < tokens.len() {
+        let keyword = tokens[i];
+        let end = tokens[i + 1..].iter().position(|t| keywords.contains(t)).map_or(tokens.len(), |p| i + 1 + p);
+        let value = tokens[i + 1..end].join(" ");
+        match keyword {
+            "name" => option.name = value,
+            "type" => option.kind = value,
+            "default" => option.default = Some(value),
+            "min" => option.min = value.parse().ok(),
+            "max" => option.max = value.parse().ok(),
+            "var" => option.vars.push(value),
+            _ => {},
+        }
+        i = end;
+    }
+    if option.name.is_empty() { None } else { Some(option) }
+}
+
+// Chess960 engines write castling as the king taking its own rook, as the king's target square alone can be ambiguous
+fn king_takes_rook(game: &Game) -> bool {
+    game.variant().name() == Chess960::default().name()
+}
+
+fn parse_info(tokens: &[&str], position: &Game) -> EngineInfo {
+    let mut info = EngineInfo::default();
+    let number = |i: usize| tokens.get(i + 1).and_then(|v| v.parse::<i64>().ok());
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            "depth" => info.depth = number(i).map(|v| v as u32),
+            "seldepth" => info.seldepth = number(i).map(|v| v as u32),
+            "multipv" => info.multipv = number(i).map(|v| v as u32),
+            "nodes" => info.nodes = number(i).map(|v| v as u64),
+            "nps" => info.nps = number(i).map(|v| v as u64),
+            "time" => info.time = number(i).map(|v| Duration::from_millis(v.max(0) as u64)),
+            "score" => {
+                info.score = match (tokens.get(i + 1), number(i + 1)) {
+                    (Some(&"cp"), Some(cp)) => Some(Score::Centipawns(cp as i32)),
+                    (Some(&"mate"), Some(moves)) => Some(Score::Mate(moves as i32)),
+                    _ => None,
+                };
+                i += 2;
+                info.bound = match tokens.get(i + 1) {
+                    Some(&"lowerbound") => Some(Bound::Lower),
+                    Some(&"upperbound") => Some(Bound::Upper),
+                    _ => None,
+                };
+            },
+            "pv" => {
+                // convert the moves one after the other, the engine's moves are only legal in sequence
+                let mut game = position.clone();
+                for token in tokens[i + 1..].iter().take_while(|t| !INFO_KEYWORDS.contains(t)) {
+                    match game.parse_uci_move_with(token, king_takes_rook(position)) {
+                        Ok(mv) => {
+                            game.make_move(mv);
+                            info.pv.push(mv);
+                        },
+                        Err(_) => break,
+                    }
+                }
+            },
+            "string" => {
+                info.string = Some(tokens[i + 1..].join(" "));
+                break;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    info
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciError::Io(e) => write!(f, "engine i/o failed: {}", e),
+            UciError::Timeout => write!(f, "engine did not answer in time"),
+            UciError::Disconnected => write!(f, "engine closed the connection"),
+            UciError::NoPosition => write!(f, "no position set"),
+            UciError::IllegalMove(mv) => write!(f, "engine sent an illegal move: {}", mv),
+            UciError::NoSearch => write!(f, "no search running"),
+        }
+    }
+}
+
+impl std::error::Error for UciError {}
+
+impl From<io::Error> for UciError {
+    fn from(e: io::Error) -> Self {
+        UciError::Io(e)
+    }
+}
@@ -0,0 +1,420 @@
+//! Chess variants. A `Variant` customizes the rules a `Game` is played by: the starting position, the moves which can be
+//! played and how they are carried out, and when and how the game ends. `Standard` implements the regular rules of chess,
+//! which the other variants build on.
+
+use crate::core::{Board, Color, Figure, Piece, Square};
+use crate::game::{self, Game, GameResult, Move, MoveMetaFlag, Termination, CRAZYHOUSE_FEN, STANDARD_FEN};
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The rules of a game. All methods have default implementations following the standard rules.
+pub trait Variant: Send + Sync {
+
+    /// The name of the variant, as used in the PGN `Variant` tag.
+    fn name(&self) -> &str;
+
+    /// The Fen record of the starting position.
+    fn start_fen(&self) -> String {
+        String::from(STANDARD_FEN)
+    }
+
+    /// The figures a pawn can be promoted to.
+    fn promotion_figures(&self) -> &[Figure] {
+        &[Figure::Queen, Figure::Rook, Figure::Bishop, Figure::Knight]
+    }
+
+    /// Adds to or removes from the pseudo-legal moves of the color, which follow the standard rules at first.
+    fn generate_moves(&self, _game: &Game, _color: Color, _moves: &mut HashSet<Move>) {}
+
+    /// Moves the pieces on the board as described by the move.
+    fn apply_move(&self, board: &mut Board, move_: &Move) {
+        game::apply_move(board, move_);
+    }
+
+    /// Checks whether a pseudo-legal move may be played. By default the own king may not be left in check.
+    fn is_legal(&self, game: &Game, move_: &Move) -> bool {
+        let mut board = game.board.clone();
+        self.apply_move(&mut board, move_);
+        !self.in_check(&board, move_.piece().color)
+    }
+
+    /// Checks whether the king of the color is in check on the board, by default whether it is attacked.
+    /// A side without a king never is.
+    fn in_check(&self, board: &Board, color: Color) -> bool {
+        board.king(color).is_some_and(|king| board.is_attacked(*king))
+    }
+
+    /// Ends the game by a rule of the variant. This is checked before the standard ways a game ends:
+    /// no legal moves, the fifty-move rule and insufficient material.
+    fn game_end(&self, _game: &Game) -> Option<(GameResult, Termination)> {
+        None
+    }
+
+    /// The number of checks which win the game, if they are counted. The game keeps track of the checks given then,
+    /// see `Game::checks_given`.
+    fn check_limit(&self) -> Option<u8> {
+        None
+    }
+
+    /// The result when the side to move has no legal moves: checkmate wins, stalemate is a draw.
+    fn no_moves_result(&self, game: &Game) -> GameResult {
+        match (game.in_check(), game.current_color()) {
+            (true, Color::White) => GameResult::BlackWin,
+            (true, Color::Black) => GameResult::WhiteWin,
+            (false, _) => GameResult::Draw,
+        }
+    }
+
+    /// Checks whether the color can not checkmate the opponent by any sequence of legal moves:
+    /// a bare king, a king and knight against a bare king or bishops all on squares of the same color,
+    /// with no opposing bishops on the other color, pawns or knights to help out.
+    fn has_insufficient_material(&self, game: &Game, color: Color) -> bool {
+        let own: Vec<&Piece> = game.board.pieces.iter().filter(|p| p.color == color && p.figure != Figure::King).collect();
+        let opposing: Vec<&Piece> = game.board.pieces.iter().filter(|p| p.color != color && p.figure != Figure::King).collect();
+        let square_color = |p: &Piece| (p.square.file_index() + p.square.rank_index()) % 2;
+
+        match own.as_slice() {
+            [] => true,
+            [knight] if knight.figure == Figure::Knight => opposing.is_empty(),
+            bishops if bishops.iter().all(|p| p.figure == Figure::Bishop) => {
+                let color_of_bishops = square_color(bishops[0]);
+                bishops.iter().chain(opposing.iter())
+                    .all(|p| p.figure == Figure::Bishop && square_color(p) == color_of_bishops)
+            },
+            _ => false,
+        }
+    }
+
+}
+
+/// The regular rules of chess.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Standard;
+
+/// Fischer Random chess: standard rules from one of 960 starting positions, see `Board::new_chess960`.
+#[derive(Debug, Clone, Copy)]
+pub struct Chess960 {
+    pub index: u16,
+}
+
+/// Captured pieces change sides and can be dropped back on the board instead of making a move.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crazyhouse;
+
+impl Variant for Standard {
+    fn name(&self) -> &str {
+        "Standard"
+    }
+}
+
+impl Variant for Chess960 {
+    fn name(&self) -> &str {
+        "Chess960"
+    }
+
+    fn start_fen(&self) -> String {
+        let board = Board::new_chess960(self.index).unwrap_or_else(Board::new_in_standard_position);
+        format!("{} w KQkq - 0 1", crate::parser::export_fen_position(&board))
+    }
+}
+
+impl Default for Chess960 {
+    /// The standard starting position, which is number 518.
+    fn default() -> Self {
+        Chess960 { index: 518 }
+    }
+}
+
+impl Variant for Crazyhouse {
+    fn name(&self) -> &str {
+        "Crazyhouse"
+    }
+
+    fn start_fen(&self) -> String {
+        String::from(CRAZYHOUSE_FEN)
+    }
+
+    // with pieces to drop, there is always material to mate
+    fn has_insufficient_material(&self, _game: &Game, _color: Color) -> bool {
+        false
+    }
+}
+
+/// Captures explode: the capturing piece and all pieces but pawns next to the target square are removed as well.
+/// Kings can not capture and may stand next to each other, which also protects them from checks.
+/// Whoever blows up the opposing king wins.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Atomic;
+
+impl Variant for Atomic {
+    fn name(&self) -> &str {
+        "Atomic"
+    }
+
+    fn generate_moves(&self, game: &Game, _color: Color, moves: &mut HashSet<Move>) {
+        moves.retain(|mv| mv.piece().figure != Figure::King || !is_capture(&game.board, mv));
+    }
+
+    fn apply_move(&self, board: &mut Board, move_: &Move) {
+        let capture = is_capture(board, move_);
+        game::apply_move(board, move_);
+        if capture {
+            let center = move_.target_square();
+            board.remove_piece_by_square(&center);
+            board.pieces.retain(|p| p.figure == Figure::Pawn
+                || p.square.file_index().abs_diff(center.file_index()) > 1
+                || p.square.rank_index().abs_diff(center.rank_index()) > 1);
+        }
+    }
+
+    fn is_legal(&self, game: &Game, move_: &Move) -> bool {
+        let mut board = game.board.clone();
+        self.apply_move(&mut board, move_);
+        let color = move_.piece().color;
+        match (board.king(color), board.king(color.opposite())) {
+            // blowing up the own king is never allowed, blowing up the opposing one always is
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(_), Some(_)) => !self.in_check(&board, color),
+        }
+    }
+
+    fn in_check(&self, board: &Board, color: Color) -> bool {
+        match (board.king(color), board.king(color.opposite())) {
+            (Some(king), Some(_)) => atomic_check(board, king),
+            _ => false,
+        }
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        match (game.board.king(Color::White), game.board.king(Color::Black)) {
+            (None, _) => Some((GameResult::BlackWin, Termination::KingExploded)),
+            (_, None) => Some((GameResult::WhiteWin, Termination::KingExploded)),
+            _ => None,
+        }
+    }
+}
+
+/// Losing chess: captures are compulsory and whoever loses all pieces or is stalemated wins. The king is an ordinary
+/// piece which can be captured and promoted to, there is no check and no castling.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn name(&self) -> &str {
+        "Antichess"
+    }
+
+    fn start_fen(&self) -> String {
+        String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1")
+    }
+
+    fn promotion_figures(&self) -> &[Figure] {
+        &[Figure::Queen, Figure::Rook, Figure::Bishop, Figure::Knight, Figure::King]
+    }
+
+    fn generate_moves(&self, game: &Game, _color: Color, moves: &mut HashSet<Move>) {
+        moves.retain(|mv| !mv.is_castling());
+        if moves.iter().any(|mv| is_capture(&game.board, mv)) {
+            moves.retain(|mv| is_capture(&game.board, mv));
+        }
+    }
+
+    fn is_legal(&self, _game: &Game, _move_: &Move) -> bool {
+        true
+    }
+
+    fn in_check(&self, _board: &Board, _color: Color) -> bool {
+        false
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        [Color::White, Color::Black].into_iter()
+            .find(|color| !game.board.pieces.iter().any(|p| p.color == *color))
+            .map(|color| (win(color), Termination::NoPiecesLeft))
+    }
+
+    // being stalemated wins
+    fn no_moves_result(&self, game: &Game) -> GameResult {
+        win(game.current_color())
+    }
+
+    fn has_insufficient_material(&self, _game: &Game, _color: Color) -> bool {
+        false
+    }
+}
+
+/// Standard rules, but giving the third check wins. The checks given are part of the Fen record, e.g. `+1+0`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreeCheck;
+
+impl Variant for ThreeCheck {
+    fn name(&self) -> &str {
+        "Three-check"
+    }
+
+    fn start_fen(&self) -> String {
+        format!("{} +0+0", STANDARD_FEN)
+    }
+
+    fn check_limit(&self) -> Option<u8> {
+        Some(3)
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        [Color::White, Color::Black].into_iter()
+            .find(|&color| game.checks_given(color) >= self.check_limit())
+            .map(|color| (win(color), Termination::ThirdCheck))
+    }
+
+    // any piece besides the king can give checks
+    fn has_insufficient_material(&self, game: &Game, color: Color) -> bool {
+        game.board.pieces.iter().all(|p| p.color != color || p.figure == Figure::King)
+    }
+}
+
+/// Standard rules, but a king reaching one of the four center squares wins.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KingOfTheHill;
+
+impl Variant for KingOfTheHill {
+    fn name(&self) -> &str {
+        "King of the Hill"
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        game.board.pieces.iter()
+            .find(|p| p.figure == Figure::King && (3..=4).contains(&p.square.file_index()) && (3..=4).contains(&p.square.rank_index()))
+            .map(|king| (win(king.color), Termination::KingInCenter))
+    }
+
+    // the king can always walk to the center
+    fn has_insufficient_material(&self, _game: &Game, _color: Color) -> bool {
+        false
+    }
+}
+
+/// White has 36 pawns and no king against a regular Black army. Pawns on the first rank may move two squares as well.
+/// Black wins by capturing all White pieces, White by checkmate.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Horde;
+
+impl Variant for Horde {
+    fn name(&self) -> &str {
+        "Horde"
+    }
+
+    fn start_fen(&self) -> String {
+        String::from("rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1")
+    }
+
+    fn generate_moves(&self, game: &Game, color: Color, moves: &mut HashSet<Move>) {
+        if color != Color::White {
+            return;
+        }
+        let empty = |square: Option<Square>| square.is_some_and(|sq| game.board.check_square_for_piece(&sq).is_none());
+        for pawn in game.board.pieces.iter().filter(|p| p.color == color && p.figure == Figure::Pawn && p.square.pos.1 == '1') {
+            let (passed, target) = (pawn.square.move_by((0, 1)), pawn.square.move_by((0, 2)));
+            if empty(passed) && empty(target) {
+                moves.insert(Move::new(*pawn, target.unwrap()));
+            }
+        }
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        match game.board.pieces.iter().any(|p| p.color == Color::White) {
+            true => None,
+            false => Some((GameResult::BlackWin, Termination::NoPiecesLeft)),
+        }
+    }
+
+    // Black can always win by capturing the horde
+    fn has_insufficient_material(&self, game: &Game, color: Color) -> bool {
+        color == Color::White && Standard.has_insufficient_material(game, color)
+    }
+}
+
+/// Both sides start on the first two ranks and race their kings to the eighth rank; giving check is not allowed.
+/// If the White king gets there first, Black can still draw by reaching it with the next move.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RacingKings;
+
+impl Variant for RacingKings {
+    fn name(&self) -> &str {
+        "Racing Kings"
+    }
+
+    fn start_fen(&self) -> String {
+        String::from("8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1")
+    }
+
+    fn is_legal(&self, game: &Game, move_: &Move) -> bool {
+        let mut board = game.board.clone();
+        self.apply_move(&mut board, move_);
+        !self.in_check(&board, Color::White) && !self.in_check(&board, Color::Black)
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        let at_goal = |color| game.board.king(color).is_some_and(|king| king.square.pos.1 == '8');
+        match (at_goal(Color::White), at_goal(Color::Black)) {
+            (true, true) => Some((GameResult::Draw, Termination::KingReachedGoal)),
+            (false, true) => Some((GameResult::BlackWin, Termination::KingReachedGoal)),
+            (false, false) => None,
+            // Black gets one more move to catch up
+            (true, false) if game.current_color() == Color::Black && game.get_moves(Color::Black).iter()
+                .any(|mv| mv.piece().figure == Figure::King && mv.target_square().pos.1 == '8' && self.is_legal(game, mv)) => None,
+            (true, false) => Some((GameResult::WhiteWin, Termination::KingReachedGoal)),
+        }
+    }
+
+    // the kings can always race
+    fn has_insufficient_material(&self, _game: &Game, _color: Color) -> bool {
+        false
+    }
+}
+
+fn win(color: Color) -> GameResult {
+    match color {
+        Color::White => GameResult::WhiteWin,
+        Color::Black => GameResult::BlackWin,
+    }
+}
+
+// a king next to the opposing king can not be captured, as that would blow up the capturing side's king as well
+fn atomic_check(board: &Board, king: &Piece) -> bool {
+    let kings_adjacent = board.king(king.color.opposite())
+        .is_some_and(|other| other.square.file_index().abs_diff(king.square.file_index()) <= 1
+            && other.square.rank_index().abs_diff(king.square.rank_index()) <= 1);
+    !kings_adjacent && board.is_attacked(*king)
+}
+
+fn is_capture(board: &Board, move_: &Move) -> bool {
+    match move_.flag() {
+        Some(MoveMetaFlag::EnPassant(_)) => true,
+        Some(MoveMetaFlag::Castling(_)) | Some(MoveMetaFlag::Drop) => false,
+        _ => board.check_square_for_piece(&move_.target_square()).is_some(),
+    }
+}
+
+/// Returns the variant for a name as used in the PGN `Variant` tag, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!("Crazyhouse", rust_chess::variant::from_name("crazyhouse").unwrap().name());
+/// ```
+pub fn from_name(name: &str) -> Option<Arc<dyn Variant>> {
+    match name.to_ascii_lowercase().as_str() {
+        "standard" | "chess" => Some(Arc::new(Standard)),
+        "chess960" | "fischerandom" | "fischer random" | "chess 960" => Some(Arc::new(Chess960::default())),
+        "crazyhouse" => Some(Arc::new(Crazyhouse)),
+        "atomic" => Some(Arc::new(Atomic)),
+        "antichess" | "losing chess" | "giveaway" => Some(Arc::new(Antichess)),
+        "three-check" | "threecheck" | "three check" | "3-check" => Some(Arc::new(ThreeCheck)),
+        "king of the hill" | "kingofthehill" | "koth" => Some(Arc::new(KingOfTheHill)),
+        "horde" => Some(Arc::new(Horde)),
+        "racing kings" | "racingkings" => Some(Arc::new(RacingKings)),
+        _ => None,
+    }
+}
@@ -0,0 +1,74 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::{Color, Figure};
+use rust_chess::game::{Game, GameResult};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::variant::Antichess;
+use std::sync::Arc;
+
+fn antichess(fen: &str) -> Game {
+    Game::from_fen_with_variant(fen, Arc::new(Antichess)).unwrap()
+}
+
+#[test]
+fn captures_are_compulsory() {
+    let mut game = Game::with_variant(Arc::new(Antichess));
+    common::play(&mut game, "e3 b5");
+    // the bishop has to take
+    let moves = game.get_legal_moves(Color::White);
+    assert_eq!(1, moves.len());
+    assert_eq!("Bxb5", game.move_to_san(moves.iter().next().unwrap()));
+    assert_eq!(true, game.parse_san("Nf3").is_err());
+
+    // including en passant
+    let game = antichess("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1");
+    assert_eq!(vec! ["exd6"], game.get_legal_moves(Color::White).iter().map(|mv| game.move_to_san(mv)).collect::<Vec<String>>());
+}
+
+#[test]
+fn the_king_is_an_ordinary_piece() {
+    // no check: the king may move into the queen's range and be captured, there is no castling
+    let game = antichess("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    assert_eq!(false, game.get_legal_moves(Color::White).iter().any(|mv| mv.is_castling()));
+    let mut game = antichess("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+    assert_eq!(false, game.in_check());
+    common::play(&mut game, "Kd8");
+    common::play(&mut game, "Qxd8");
+    assert_eq!(None, game.board.king(Color::Black));
+
+    // pawns promote to kings as well
+    let game = antichess("8/P7/8/8/8/8/8/7k w - - 0 1");
+    let promotion = game.parse_san("a8=K").unwrap();
+    assert_eq!(Some(Figure::King), promotion.promotion());
+    assert_eq!("a7a8k", promotion.to_uci());
+    assert_eq!(promotion, game.parse_uci_move("a7a8k").unwrap());
+    assert_eq!(5, game.get_legal_moves(Color::White).len());
+}
+
+#[test]
+fn losing_everything_wins() {
+    let mut game = antichess("8/8/8/8/8/8/1p6/R7 b - - 0 1");
+    common::play(&mut game, "bxa1=N");
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result());
+
+    // being stalemated wins as well
+    let mut game = antichess("8/8/8/8/8/p7/P7/8 w - - 0 1");
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result());
+}
+
+#[test]
+fn antichess_pgn() {
+    let mut game = Game::with_variant(Arc::new(Antichess));
+    common::play(&mut game, "e3 b5 Bxb5 c6 Bxc6 Nxc6");
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"Antichess\"]"));
+    assert_eq!(false, pgn.contains("[FEN"));
+    let imported = parse_pgn(&pgn).unwrap();
+    assert_eq!(game.to_fen(), imported.to_fen());
+}
+
+#[test]
+fn antichess_perft() {
+    let game = Game::with_variant(Arc::new(Antichess));
+    assert_eq!(vec! [20, 400, 8067, 153299], (1..=4).map(|depth| common::perft(&game, depth)).collect::<Vec<u64>>());
+}
@@ -0,0 +1,61 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::{Color, Figure};
+use rust_chess::game::{Game, GameResult};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::variant::Atomic;
+use std::sync::Arc;
+
+fn atomic(fen: &str) -> Game {
+    Game::from_fen_with_variant(fen, Arc::new(Atomic)).unwrap()
+}
+
+#[test]
+fn captures_explode() {
+    let mut game = Game::with_variant(Arc::new(Atomic));
+    common::play(&mut game, "e4 d5 exd5");
+    // the capturing pawn and the captured pawn are gone, the pawns around them stay
+    assert_eq!("rnbqkbnr/ppp1pppp/8/8/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", game.to_fen());
+
+    common::play(&mut game, "Qxd2+");
+    // the queen takes the knight, bishop, queen and king with her, the pawns survive
+    assert_eq!(None, game.board.king(Color::White));
+    assert_eq!(false, game.board.pieces.iter().any(|p| p.figure == Figure::Queen));
+    assert_eq!(Some(GameResult::BlackWin), game.update_result());
+    assert_eq!(true, game.get_legal_moves(Color::White).is_empty());
+}
+
+#[test]
+fn kings_in_atomic() {
+    // kings can not capture
+    let game = atomic("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1");
+    assert_eq!(true, game.parse_san("Kxd2").is_err());
+
+    // kings next to each other can not be checked, but may not blow up their own king
+    let game = atomic("8/8/8/8/8/3k4/3K4/3r4 w - - 0 1");
+    assert_eq!(false, game.in_check());
+    let game = atomic("8/8/8/8/3k4/8/3K4/3r4 w - - 0 1");
+    assert_eq!(true, game.in_check());
+    assert_eq!(true, game.parse_san("Kd3").is_ok());
+
+    // a capture next to the own king is illegal
+    let game = atomic("4k3/8/8/8/8/8/3nP3/3QK3 w - - 0 1");
+    assert_eq!(true, game.parse_san("Qxd2").is_err());
+}
+
+#[test]
+fn atomic_pgn() {
+    let mut game = Game::with_variant(Arc::new(Atomic));
+    common::play(&mut game, "Nf3 f6 Ng5 fxg5");
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"Atomic\"]"));
+    let imported = parse_pgn(&pgn).unwrap();
+    assert_eq!("Atomic", imported.variant().name());
+    assert_eq!(game.to_fen(), imported.to_fen());
+}
+
+#[test]
+fn atomic_perft() {
+    let game = Game::with_variant(Arc::new(Atomic));
+    assert_eq!(vec! [20, 400, 8902, 197326], (1..=4).map(|depth| common::perft(&game, depth)).collect::<Vec<u64>>());
+}
@@ -0,0 +1,152 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::book::{self, Book, BookBuilder, BookEntry, Choice, MoveStats, Rng};
+use rust_chess::game::Game;
+
+fn entry(game: &Game, san: &str, weight: u16) -> BookEntry {
+    let mv = game.parse_san(san).unwrap();
+    BookEntry { key: book::polyglot_key(game), move_: book::encode_move(&mv), weight, learn: 0 }
+}
+
+#[test]
+fn polyglot_keys() {
+    // the test positions of the Polyglot book format
+    let expected: [(&str, u64); 9] = [
+        ("", 0x463b96181691fc9c),
+        ("e4", 0x823c9b50fd114196),
+        ("e4 d5", 0x0756b94461c50fb0),
+        ("e4 d5 e5", 0x662fafb965db29d4),
+        ("e4 d5 e5 f5", 0x22a48b5a8e47ff78),
+        ("e4 d5 e5 f5 Ke2", 0x652a607ca3f242c1),
+        ("e4 d5 e5 f5 Ke2 Kf7", 0x00fdd303c946bdd9),
+        ("a4 b5 h4 b4 c4", 0x3c8123ea7b067637),
+        ("a4 b5 h4 b4 c4 bxc3 Ra3", 0x5c3f9b829b279560),
+    ];
+    for (moves, key) in expected {
+        let mut game = Game::new();
+        common::play(&mut game, moves);
+        assert_eq!(key, book::polyglot_key(&game), "after {}", moves);
+    }
+}
+
+#[test]
+fn decoding_moves() {
+    // castling is the king taking its own rook
+    let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castling = game.parse_san("O-O").unwrap();
+    assert_eq!(0x0107, book::encode_move(&castling));
+    assert_eq!(Some(castling), book::decode_move(&game, 0x0107));
+    assert_eq!(Some(game.parse_san("O-O-O").unwrap()), book::decode_move(&game, 0x0100));
+
+    let game = Game::from_fen("8/4P3/8/8/8/8/k7/4K3 w - - 0 1").unwrap();
+    let promotion = game.parse_san("e8=N").unwrap();
+    assert_eq!(Some(promotion), book::decode_move(&game, book::encode_move(&promotion)));
+    // no legal move, or no valid encoding
+    assert_eq!(None, book::decode_move(&game, 0x0000));
+    assert_eq!(None, book::decode_move(&game, 0x7000 | book::encode_move(&promotion)));
+}
+
+#[test]
+fn reading_and_choosing() {
+    let start = Game::new();
+    let mut after_e4 = Game::new();
+    common::play(&mut after_e4, "e4");
+    let entries = vec! [entry(&after_e4, "c5", 3), entry(&start, "e4", 10), entry(&start, "d4", 5), entry(&start, "a3", 0)];
+    let book = Book::from_bytes(&Book::from_entries(entries).to_bytes());
+    assert_eq!(4, book.len());
+    assert_eq!(3, book.lookup(book::polyglot_key(&start)).len());
+    assert_eq!(vec! [(after_e4.parse_san("c5").unwrap(), 3)], book.moves(&after_e4));
+    assert_eq!(true, book.moves(&Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap()).is_empty());
+
+    assert_eq!(Some(start.parse_san("e4").unwrap()), book.choose(&start, Choice::Best, &mut Rng::new(0)));
+
+    // weighted choices follow the weights and never take moves of weight 0, the same seed gives the same moves
+    let mut rng = Rng::new(7);
+    let choices: Vec<String> = (0..300).map(|_| start.move_to_san(&book.choose(&start, Choice::Weighted, &mut rng).unwrap())).collect();
+    let e4 = choices.iter().filter(|san| *san == "e4").count();
+    assert_eq!(false, choices.contains(&String::from("a3")));
+    assert!((170..230).contains(&e4), "{} times e4", e4);
+    let mut rng = Rng::new(7);
+    assert_eq!(choices[0], start.move_to_san(&book.choose(&start, Choice::Weighted, &mut rng).unwrap()));
+
+    let mut rng = Rng::new(1);
+    let uniform: Vec<String> = (0..100).map(|_| start.move_to_san(&book.choose(&start, Choice::Uniform, &mut rng).unwrap())).collect();
+    assert_eq!(true, uniform.contains(&String::from("a3")));
+    assert_eq!(None, book.choose(&after_e4.clone(), Choice::Weighted, &mut rng).filter(|mv| after_e4.move_to_san(mv) != "c5"));
+}
+
+const GAMES: &str = r#"[White "A"]
+[Black "B"]
+[Result "1-0"]
+[WhiteElo "2200"]
+[BlackElo "2100"]
+
+1. e4 e5 2. Nf3 Nc6 1-0
+
+[White "C"]
+[Black "D"]
+[Result "1/2-1/2"]
+[WhiteElo "2300"]
+[BlackElo "1800"]
+
+1. e4 c5 {[%clk 0:05:00]
+[this comment spans lines]} 2. Nf3 1/2-1/2
+
+[White "E"]
+[Black "F"]
+[Result "0-1"]
+
+1. d4 d5 0-1
+"#;
+
+#[test]
+fn building_books() {
+    let mut builder = BookBuilder::new(3);
+    assert_eq!(3, builder.add_pgn(GAMES));
+    let start = Game::new();
+    let stats = builder.stats(&start);
+    assert_eq!("e2e4", stats[0].0.to_uci());
+    assert_eq!(MoveStats { games: 2, wins: 1, draws: 1, losses: 0 }, stats[0].1);
+    assert_eq!(MoveStats { games: 1, wins: 0, draws: 0, losses: 1 }, stats[1].1);
+
+    // weighted by points, two for a win and one for a draw, moves after the ply limit are left out
+    let book = builder.build();
+    assert_eq!(7, book.len());
+    assert_eq!(vec! [(start.parse_san("e4").unwrap(), 3), (start.parse_san("d4").unwrap(), 0)].into_iter().collect::<std::collections::HashSet<_>>(),
+        book.moves(&start).into_iter().collect());
+    let mut after_e4_e5 = Game::new();
+    common::play(&mut after_e4_e5, "e4 e5 Nf3");
+    assert_eq!(true, book.moves(&after_e4_e5).is_empty());
+
+    let dump = builder.tree_dump();
+    assert_eq!("1. e4  games 2  +1 =1 -0  66.7%\n  1... c5  games 1  +0 =1 -0  50.0%\n    2. Nf3  games 1  +0 =1 -0  100.0%\n\
+        \x20 1... e5  games 1  +0 =0 -1  50.0%\n    2. Nf3  games 1  +1 =0 -0  100.0%\n1. d4  games 1  +0 =0 -1  33.3%\n\
+        \x20 1... d5  games 1  +1 =0 -0  100.0%\n", dump);
+
+    // filters
+    let mut builder = BookBuilder::new(10);
+    builder.min_rating = Some(2000);
+    assert_eq!(1, builder.add_pgn(GAMES));
+    let mut builder = BookBuilder::new(10);
+    builder.min_games = 2;
+    builder.add_pgn(GAMES);
+    assert_eq!(1, builder.build().len());
+    assert_eq!("1. e4  games 2  +1 =1 -0  100.0%\n", builder.tree_dump());
+}
+
+#[test]
+fn make_book_tool() {
+    let dir = std::env::temp_dir().join(format!("rust_chess_book_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("games.pgn"), GAMES).unwrap();
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_make_book"))
+        .args(["--ply", "2", "--dump", "tree.txt", "-o", "book.bin", "games.pgn"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert_eq!(true, status.status.success());
+    let book = Book::open(dir.join("book.bin")).unwrap();
+    assert_eq!(5, book.len());
+    assert!(std::fs::read_to_string(dir.join("tree.txt")).unwrap().contains("1... c5"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
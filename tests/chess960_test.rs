@@ -0,0 +1,95 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::{chess960_back_rank, Board, Figure};
+use rust_chess::game::{Game, STANDARD_FEN};
+use rust_chess::parser::export_fen_position;
+
+use std::collections::HashSet;
+
+#[test]
+fn start_positions() {
+    let mut positions = HashSet::new();
+    for index in 0..960 {
+        let rank = chess960_back_rank(index).unwrap();
+        let files = |figure: Figure| -> Vec<usize> { (0..8).filter(|&f| rank[f] == figure).collect() };
+
+        let bishops = files(Figure::Bishop);
+        assert_eq!(1, (bishops[0] + bishops[1]) % 2);
+        let rooks = files(Figure::Rook);
+        let king = files(Figure::King)[0];
+        assert_eq!(true, rooks[0] < king && king < rooks[1]);
+        assert_eq!(2, files(Figure::Knight).len());
+        assert_eq!(1, files(Figure::Queen).len());
+        positions.insert(rank);
+    }
+    assert_eq!(960, positions.len());
+    assert_eq!(None, chess960_back_rank(960));
+
+    assert_eq!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR", export_fen_position(&Board::new_chess960(518).unwrap()));
+    assert_eq!("bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR", export_fen_position(&Board::new_chess960(0).unwrap()));
+    assert_eq!("rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB", export_fen_position(&Board::new_chess960(959).unwrap()));
+    assert_eq!(STANDARD_FEN, Game::new_chess960(518).unwrap().to_fen());
+}
+
+#[test]
+fn castling_fields() {
+    // Shredder-FEN
+    let game = Game::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9").unwrap();
+    assert_eq!("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", game.to_shredder_fen());
+    assert_eq!("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9", game.to_fen());
+
+    // X-FEN names the file of a rook which is not the outermost one on its side
+    let game = Game::from_fen("1r2k1r1/8/8/8/8/8/8/RR2K2R w KBk - 0 1").unwrap();
+    assert_eq!("1r2k1r1/8/8/8/8/8/8/RR2K2R w KBk - 0 1", game.to_fen());
+    assert_eq!("1r2k1r1/8/8/8/8/8/8/RR2K2R w HBg - 0 1", game.to_shredder_fen());
+    assert_eq!(Game::from_fen("1r2k1r1/8/8/8/8/8/8/RR2K2R w HBg - 0 1").unwrap().to_fen(), game.to_fen());
+
+    assert!(Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w C - 0 1").is_err());
+}
+
+#[test]
+fn generalized_castling() {
+    // the king stays on g1 and only the rook moves, written as king takes rook in UCI
+    let mut game = Game::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+    let castling = game.parse_uci_move_with("g1h1", true).unwrap();
+    assert_eq!("O-O", game.move_to_san(&castling));
+    assert_eq!("g1h1", castling.to_uci_with(true));
+    // also without king takes rook, as g1g1 would not be a move
+    assert_eq!("g1h1", castling.to_uci());
+    assert_eq!(Ok(castling), game.parse_uci_move("g1h1"));
+    assert_eq!(true, game.parse_uci_move("g1g1").is_err());
+    game.make_move(castling);
+    assert_eq!("1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 1 1", game.to_fen());
+
+    // long castling with the king moving right, from b8 to c8
+    let mut game = Game::from_fen("rk5r/8/8/8/8/8/8/4K3 b ha - 0 1").unwrap();
+    game.make_move(game.parse_san("O-O-O").unwrap());
+    assert_eq!("2kr3r/8/8/8/8/8/8/4K3 w - - 1 2", game.to_fen());
+
+    // the squares between king and rook have to be empty, apart from the castling pieces themselves
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/2RK1N2 w C - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("O-O-O").is_ok());
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/1R1KN3 w B - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("O-O-O").is_ok());
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/1RNK4 w B - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("O-O-O").is_err());
+    // the king may not pass through an attacked square, the rook may
+    let game = Game::from_fen("1r2k3/8/8/8/8/8/8/1R2K3 w B - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("O-O-O").is_ok());
+    let game = Game::from_fen("3rk3/8/8/8/8/8/8/1R2K3 w B - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("O-O-O").is_err());
+}
+
+#[test]
+fn chess960_perft() {
+    for (fen, counts) in [
+        ("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9", [21, 528, 12189]),
+        ("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9", [21, 807, 18002]),
+        ("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9", [20, 479, 10471]),
+    ] {
+        let game = Game::from_fen(fen).unwrap();
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(*count, common::perft(&game, depth as u32 + 1), "{} at depth {}", fen, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use rust_chess::clock::{Clock, ManualTime, Stage, TimeControl, Timing};
+use rust_chess::core::Color;
+use rust_chess::game::{Game, GameResult, Termination};
+
+use std::time::Duration;
+
+fn secs(s: u64) -> Duration {
+    Duration::from_secs(s)
+}
+
+fn started_clock(control: TimeControl) -> (Clock, ManualTime) {
+    let time = ManualTime::new();
+    let mut clock = Clock::with_time_source(control, time.clone());
+    clock.start();
+    (clock, time)
+}
+
+#[test]
+fn fischer_increment() {
+    let (mut clock, time) = started_clock(TimeControl::new(secs(300), secs(2)));
+    time.advance(secs(10));
+    assert_eq!(secs(290), clock.remaining(Color::White));
+    assert_eq!(secs(10), clock.press());
+    assert_eq!(secs(292), clock.remaining(Color::White));
+    assert_eq!(Color::Black, clock.turn());
+
+    time.advance(secs(1));
+    clock.press();
+    assert_eq!(secs(301), clock.remaining(Color::Black));
+}
+
+#[test]
+fn bronstein_and_simple_delay() {
+    let bronstein = TimeControl { stages: vec! [Stage::new(None, secs(60)).with_timing(Timing::Bronstein(secs(5)))] };
+    let (mut clock, time) = started_clock(bronstein);
+    time.advance(secs(3));
+    assert_eq!(secs(57), clock.remaining(Color::White));
+    clock.press();
+    // the time used is given back, up to the delay
+    assert_eq!(secs(60), clock.remaining(Color::White));
+    time.advance(secs(8));
+    clock.press();
+    assert_eq!(secs(57), clock.remaining(Color::Black));
+
+    let simple = TimeControl { stages: vec! [Stage::new(None, secs(60)).with_timing(Timing::SimpleDelay(secs(5)))] };
+    let (mut clock, time) = started_clock(simple);
+    time.advance(secs(4));
+    assert_eq!(secs(60), clock.remaining(Color::White));
+    time.advance(secs(4));
+    assert_eq!(secs(57), clock.remaining(Color::White));
+
+    // stopping the clock does not grant the delay again
+    clock.stop();
+    time.advance(secs(100));
+    assert_eq!(secs(57), clock.remaining(Color::White));
+    clock.start();
+    time.advance(secs(1));
+    clock.press();
+    assert_eq!(secs(56), clock.remaining(Color::White));
+}
+
+#[test]
+fn multi_stage_time_control() {
+    // 40 moves in 90 minutes, then 30 minutes for the rest of the game, 30 seconds increment from move one
+    let control = TimeControl::from_pgn("40/5400+30:1800+30").unwrap();
+    assert_eq!(Some(40), control.stages[0].moves);
+    assert_eq!(Timing::Increment(secs(30)), control.stages[1].timing);
+
+    let (mut clock, time) = started_clock(control);
+    for _ in 0..39 {
+        time.advance(secs(60));
+        clock.press();
+        clock.press();
+    }
+    assert_eq!(secs(5400 - 39 * 30), clock.remaining(Color::White));
+    time.advance(secs(60));
+    clock.press();
+    assert_eq!(secs(5400 - 40 * 30 + 1800), clock.remaining(Color::White));
+    assert_eq!(None, clock.stage(Color::White).moves);
+    assert_eq!(Some(40), clock.stage(Color::Black).moves);
+
+    // a repeating stage
+    let (mut clock, _) = started_clock(TimeControl::from_pgn("2/60").unwrap());
+    clock.press();
+    clock.press();
+    clock.press();
+    assert_eq!(secs(120), clock.remaining(Color::White));
+
+    assert!(TimeControl::from_pgn("40/").is_err());
+    assert!(TimeControl::from_pgn("5+x").is_err());
+}
+
+#[test]
+fn flag_fall() {
+    let (mut clock, time) = started_clock(TimeControl::new(secs(60), Duration::ZERO));
+    let mut game = Game::new();
+    let mv = game.parse_san("e4").unwrap();
+    assert_eq!(None, clock.make_move(&mut game, mv));
+    assert_eq!(Some(secs(60)), game.move_meta().clock);
+
+    time.advance(secs(20));
+    let mv = game.parse_san("e5").unwrap();
+    clock.make_move(&mut game, mv);
+    assert_eq!(Some(secs(40)), game.move_meta().clock);
+    assert_eq!(Some(secs(20)), game.move_meta().elapsed);
+
+    time.advance(secs(61));
+    assert_eq!(Some(Color::White), clock.flagged());
+    assert_eq!(Duration::ZERO, clock.remaining(Color::White));
+    let mv = game.parse_san("Nf3").unwrap();
+    assert_eq!(Some(GameResult::BlackWin), clock.make_move(&mut game, mv));
+    assert_eq!(2, game.moves().len());
+    assert_eq!(Duration::ZERO, clock.press());
+}
+
+#[test]
+fn flag_fall_against_insufficient_material() {
+    let (mut clock, time) = started_clock(TimeControl::new(secs(60), Duration::ZERO));
+    let mut game = Game::from_fen("8/8/4k3/8/8/3NK3/8/8 w - - 0 1").unwrap();
+    clock.press();
+    time.advance(secs(60));
+    assert_eq!(Some(Color::Black), clock.flagged());
+    assert_eq!(Some(GameResult::Draw), clock.apply_flag(&mut game));
+
+    // with a pawn left, a mate is still possible
+    let (mut clock, time) = started_clock(TimeControl::new(secs(60), Duration::ZERO));
+    let mut game = Game::from_fen("8/8/4k3/4p3/8/3NK3/8/8 w - - 0 1").unwrap();
+    time.advance(secs(60));
+    assert_eq!(Some(GameResult::BlackWin), clock.apply_flag(&mut game));
+    assert_eq!(Some(Termination::TimeForfeit), game.termination());
+}
+
+#[test]
+fn no_moves_after_the_end() {
+    let (mut clock, time) = started_clock(TimeControl::new(secs(60), Duration::ZERO));
+    let mut game = Game::new();
+    for san in ["f3", "e5", "g4"] {
+        let mv = game.parse_san(san).unwrap();
+        assert_eq!(None, clock.make_move(&mut game, mv));
+    }
+    let mv = game.parse_san("Qh4").unwrap();
+    assert_eq!(Some(GameResult::BlackWin), clock.make_move(&mut game, mv));
+    assert_eq!(Some(Termination::Checkmate), game.termination());
+    assert!(!clock.is_running());
+
+    // the clock stays stopped, nobody can lose on time anymore and no move is played
+    time.advance(secs(120));
+    assert_eq!(Some(GameResult::BlackWin), clock.make_move(&mut game, mv));
+    assert_eq!(4, game.moves().len());
+    assert_eq!(secs(60), clock.remaining(Color::White));
+}
@@ -1,6 +1,8 @@
-/// Module for setup and teardown of test configurations
+//! Module for setup and teardown of test configurations
+#![allow(dead_code)]
 
-use rust_chess::core::{Board, Square, Color, Figure, Piece};
+use rust_chess::core::{Board, Square};
+use rust_chess::game::Game;
 use std::collections::HashSet;
 
 pub fn empty_board() -> Board {
@@ -17,7 +19,7 @@ pub fn setup_board_in_std_start_position() -> Board {
 pub fn sqrs(board: &Board, squares: &str) -> HashSet<Square> {
     
     let sq_split = squares.split(",").collect::<Vec<&str>>();
-    if sq_split.len() == 0 {
+    if sq_split.is_empty() {
         return HashSet::new();
     }
 
@@ -25,3 +27,37 @@ pub fn sqrs(board: &Board, squares: &str) -> HashSet<Square> {
         .map(|sq| *board.get_unchecked(sq.trim())).collect();
     sq_refs
 }
+
+// count the leaf nodes of the tree of legal moves up to the given depth, see https://www.chessprogramming.org/Perft_Results
+pub fn perft(game: &Game, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = game.get_legal_moves(game.current_color());
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.into_iter()
+        .map(|mv| {
+            let mut next = game.position_copy();
+            next.make_move(mv);
+            perft(&next, depth - 1)
+        })
+        .sum()
+}
+
+// play a ' '-separated list of moves in SAN
+pub fn play(game: &mut Game, moves: &str) {
+    for san in moves.split_whitespace() {
+        let mv = game.parse_san(san).unwrap();
+        game.make_move(mv);
+    }
+}
+
+// play a ' '-separated list of moves in UCI notation
+pub fn play_uci(game: &mut Game, moves: &str) {
+    for mv in moves.split_whitespace() {
+        let mv = game.parse_uci_move(mv).unwrap();
+        game.make_move(mv);
+    }
+}
@@ -0,0 +1,92 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::{Color, Figure};
+use rust_chess::game::{Game, CRAZYHOUSE_FEN};
+use rust_chess::parser::{export_pgn, parse_fen, parse_pgn};
+use rust_chess::variant::Crazyhouse;
+use std::sync::Arc;
+
+#[test]
+fn captures_fill_the_pockets() {
+    let mut game = Game::new_crazyhouse();
+    assert_eq!(CRAZYHOUSE_FEN, game.to_fen());
+    assert_eq!(true, game.pocket(Color::White).unwrap().is_empty());
+    assert_eq!(None, Game::new().pocket(Color::White));
+
+    common::play(&mut game, "e4 d5 exd5 Qxd5");
+    assert_eq!(1, game.pocket(Color::White).unwrap().count(Figure::Pawn));
+    assert_eq!(1, game.pocket(Color::Black).unwrap().count(Figure::Pawn));
+    assert_eq!("rnb1kbnr/ppp1pppp/8/3q4/8/8/PPPP1PPP/RNBQKBNR[Pp] w KQkq - 0 3", game.to_fen());
+
+    // drops, written as in SAN and UCI
+    let drop = game.parse_san("P@e4").unwrap();
+    assert_eq!(true, drop.is_drop());
+    assert_eq!("P@e4", game.move_to_san(&drop));
+    assert_eq!("P@e4", drop.to_uci());
+    assert_eq!(drop, game.parse_san("@e4").unwrap());
+    assert_eq!(drop, game.parse_uci_move("P@e4").unwrap());
+    game.make_move(drop);
+    assert_eq!(true, game.pocket(Color::White).unwrap().is_empty());
+    assert_eq!("rnb1kbnr/ppp1pppp/8/3q4/4P3/8/PPPP1PPP/RNBQKBNR[p] b KQkq - 0 3", game.to_fen());
+
+    // no drops without pieces in the pocket, nor on occupied squares
+    assert_eq!(true, game.parse_san("N@f6").is_err());
+    assert_eq!(true, game.parse_san("P@e4").is_err());
+    common::play(&mut game, "Qxe4+ Be2 P@d3");
+    assert_eq!("rnb1kbnr/ppp1pppp/8/8/4q3/3p4/PPPPBPPP/RNBQK1NR[p] w KQkq - 0 5", game.to_fen());
+}
+
+#[test]
+fn pawn_drops_and_checks() {
+    // no pawns on the first and last rank
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3[Pp] w - - 0 1").unwrap();
+    assert_eq!(true, game.parse_san("P@e8").is_err());
+    assert_eq!(true, game.parse_san("P@a1").is_err());
+    assert_eq!("P@d7+", game.move_to_san(&game.parse_san("P@d7").unwrap()));
+
+    // a drop can block a check, or give mate
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/r3K3[Np] w - - 0 1").unwrap();
+    assert_eq!(false, game.get_legal_moves(Color::White).iter().any(|mv| mv.is_drop() && mv.target_square().pos.1 != '1'));
+    let game = Game::from_fen("3rkr2/3ppp2/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+    assert_eq!("N@g7#", game.move_to_san(&game.parse_san("N@g7").unwrap()));
+
+    // perft with full pockets
+    let game = Game::from_fen("2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1").unwrap();
+    assert_eq!(301, common::perft(&game, 1));
+    assert_eq!(75353, common::perft(&game, 2));
+}
+
+#[test]
+fn promoted_pieces_revert_to_pawns() {
+    let mut game = Game::from_fen("4k3/1P6/8/8/8/8/7r/4K3[] w - - 0 1").unwrap();
+    common::play(&mut game, "b8=Q+ Kd7");
+    assert_eq!("1Q~6/3k4/8/8/8/8/7r/4K3[] w - - 1 2", game.to_fen());
+    common::play(&mut game, "Qb5+ Kd6 Qb4+ Kd5 Qb2");
+    assert_eq!("8/8/8/3k4/8/8/1Q~5r/4K3[] b - - 6 4", game.to_fen());
+    common::play(&mut game, "Rxb2");
+    assert_eq!(1, game.pocket(Color::Black).unwrap().count(Figure::Pawn));
+    assert_eq!(0, game.pocket(Color::Black).unwrap().count(Figure::Queen));
+
+    // the marker is read from Fen records, in both pocket notations
+    let game = Game::from_fen("1Q~6/3k4/8/8/8/8/8/r3K3/Rq w - - 1 2").unwrap();
+    assert_eq!("1Q~6/3k4/8/8/8/8/8/r3K3[Rq] w - - 1 2", game.to_fen());
+    assert_eq!(true, Game::from_fen("4k3/8/8/8/8/8/8/4K3[K] w - - 0 1").is_err());
+    assert_eq!(true, Game::from_fen("4k3/8/8/8/8/8/8/4K3[Q w - - 0 1").is_err());
+
+    // but only in Crazyhouse and behind a piece
+    let crazyhouse = Game::from_fen_with_variant("1Q~2k3/8/8/8/8/8/8/4K3 w - - 0 1", Arc::new(Crazyhouse)).unwrap();
+    assert_eq!("1Q~2k3/8/8/8/8/8/8/4K3[] w - - 0 1", crazyhouse.to_fen());
+    assert_eq!(true, Game::from_fen("1Q~2k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert_eq!(true, Game::from_fen("1~Q2k3/8/8/8/8/8/8/4K3[] w - - 0 1").is_err());
+    assert_eq!(true, parse_fen("1Q~2k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+}
+
+#[test]
+fn crazyhouse_pgn() {
+    let mut game = Game::new_crazyhouse();
+    common::play(&mut game, "e4 d5 exd5 Qxd5 Nc3 Qa5 P@d4");
+    let pgn = export_pgn(&game);
+    assert!(pgn.ends_with("1. e4 d5 2. exd5 Qxd5 3. Nc3 Qa5 4. P@d4 *\n"));
+    let imported = parse_pgn(&pgn).unwrap();
+    assert_eq!(game.to_fen(), imported.to_fen());
+}
@@ -0,0 +1,132 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::database::{GameQuery, PgnIndex};
+
+const PGN: &str = r#"[Event "Casual Game"]
+[White "Carlsen, Magnus"]
+[Black "Nakamura, Hikaru"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {The Ruy Lopez} a6 1-0
+
+[Event "Casual Game"]
+[White "Nakamura, Hikaru"]
+[Black "Carlsen, Magnus"]
+[Result "1/2-1/2"]
+
+1. Nf3 e5 2. e4 Nc6 1/2-1/2
+
+[White "Broken"]
+[Black "Game"]
+[Result "*"]
+
+1. e4 e4 *
+
+[Event "Endgame Study"]
+[White "Carlsen, Magnus"]
+[Black "Anand, Viswanathan"]
+[Result "0-1"]
+[SetUp "1"]
+[FEN "4k2r/8/8/8/8/8/7P/R3K3 w - - 0 1"]
+
+1. h4 Rxh4 2. Ra8+ Kd7 0-1
+"#;
+
+fn search(index: &PgnIndex, query: GameQuery) -> Vec<u64> {
+    index.search(&query).unwrap().iter().map(|game| game.offset).collect()
+}
+
+#[test]
+fn searching_games() {
+    let index = PgnIndex::build(PGN);
+    // the broken game is left out
+    assert_eq!(3, index.len());
+    let offsets: Vec<u64> = index.games().iter().map(|game| game.offset).collect();
+    assert_eq!(0, offsets[0]);
+    assert_eq!(true, PGN[offsets[1] as usize..].starts_with("[Event \"Casual Game\"]\n[White \"Nakamura"));
+    assert_eq!(true, PGN[offsets[2] as usize..].starts_with("[Event \"Endgame Study\"]"));
+
+    // the same position after different move orders
+    let fen = String::from("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+    assert_eq!(vec! [offsets[0], offsets[1]], search(&index, GameQuery { fen: Some(fen), ..GameQuery::default() }));
+    // the same placement with the other side to move did not occur
+    let fen = String::from("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 2 3");
+    assert_eq!(0, search(&index, GameQuery { fen: Some(fen), ..GameQuery::default() }).len());
+
+    let player = |name: &str| Some(String::from(name));
+    assert_eq!(3, search(&index, GameQuery { player: player("carlsen"), ..GameQuery::default() }).len());
+    assert_eq!(vec! [offsets[0], offsets[2]], search(&index, GameQuery { white: player("Carlsen"), ..GameQuery::default() }));
+    let query = GameQuery { player: player("Carlsen"), result: Some(String::from("1-0")), ..GameQuery::default() };
+    assert_eq!(vec! [offsets[0]], search(&index, query));
+    let query = GameQuery { white: player("Nakamura"), black: player("Anand"), ..GameQuery::default() };
+    assert_eq!(0, search(&index, query).len());
+}
+
+#[test]
+fn searching_material() {
+    let index = PgnIndex::build(PGN);
+    let study = &index.games()[2];
+    assert_eq!(vec! ["KRPvKR", "KRvKR"], study.signatures());
+    assert_eq!(vec! ["KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"], index.games()[0].signatures());
+
+    let material = |signature: &str| GameQuery { material: Some(String::from(signature)), ..GameQuery::default() };
+    assert_eq!(vec! [study.offset], search(&index, material("KRvKR")));
+    // either side, and the letters in any order
+    assert_eq!(vec! [study.offset], search(&index, material("KRvKPR")));
+    assert_eq!(0, search(&index, material("KQvK")).len());
+
+    assert_eq!(true, index.search(&material("KXvK")).is_err());
+    assert_eq!(true, index.search(&material("KRK")).is_err());
+    assert_eq!(true, index.search(&GameQuery { fen: Some(String::from("8/8")), ..GameQuery::default() }).is_err());
+}
+
+#[test]
+fn saving_and_reading_games() {
+    let directory = std::env::temp_dir();
+    let pgn_path = directory.join(format!("rust_chess_database_{}.pgn", std::process::id()));
+    let index_path = directory.join(format!("rust_chess_database_{}.idx", std::process::id()));
+    std::fs::write(&pgn_path, PGN).unwrap();
+
+    let index = PgnIndex::build_file(&pgn_path).unwrap();
+    index.save(&index_path).unwrap();
+    let loaded = PgnIndex::open(&index_path).unwrap();
+    std::fs::remove_file(&index_path).unwrap();
+    assert_eq!(index, loaded);
+
+    // only the games found are read from the file
+    let query = GameQuery { result: Some(String::from("0-1")), ..GameQuery::default() };
+    let found = loaded.search(&query).unwrap();
+    let mut file = std::fs::File::open(&pgn_path).unwrap();
+    let game = found[0].read(&mut file).unwrap();
+    std::fs::remove_file(&pgn_path).unwrap();
+    assert_eq!(4, game.moves().len());
+    assert_eq!("4k2r/8/8/8/8/8/7P/R3K3 w - - 0 1", game.start_fen);
+    assert_eq!(Some("Anand, Viswanathan"), game.meta.as_ref().unwrap().tag("Black"));
+
+    let bytes = index.to_bytes();
+    assert_eq!(true, PgnIndex::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+    // an index of the first version, with 16-bit counts
+    assert_eq!(true, PgnIndex::from_bytes(&[b"PGNX".as_slice(), &[1], &bytes[5..]].concat()).is_err());
+    let long = PgnIndex::build(&format!("[Event \"{}\"]\n\n1. e4 *\n", "x".repeat(70000)));
+    assert_eq!(long, PgnIndex::from_bytes(&long.to_bytes()).unwrap());
+    assert_eq!(true, PgnIndex::from_bytes(b"PGN").is_err());
+    assert_eq!(true, PgnIndex::from_bytes(&[]).is_err());
+}
+
+#[test]
+fn latin1_files() {
+    // the names are written in Latin-1, which is not valid UTF-8
+    let pgn = [b"[White \"M\xfcller\"]\n[Black \"G\xf6del\"]\n[Result \"1-0\"]\n\n1. e4 e5 {\xe9cart} 1-0\n\n".as_slice(),
+               b"[White \"Tal\"]\n[Black \"Botvinnik\"]\n[Result \"0-1\"]\n\n1. d4 d5 0-1\n"].concat();
+    let path = std::env::temp_dir().join(format!("rust_chess_latin1_{}.pgn", std::process::id()));
+    std::fs::write(&path, &pgn).unwrap();
+    let index = PgnIndex::build_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(2, index.len());
+
+    let mut file = std::io::Cursor::new(pgn);
+    let first = index.games()[0].read(&mut file).unwrap();
+    assert_eq!(Some("M?ller"), first.meta.as_ref().unwrap().tag("White"));
+    let second = index.games()[1].read(&mut file).unwrap();
+    assert_eq!(Some("Botvinnik"), second.meta.as_ref().unwrap().tag("Black"));
+    assert_eq!(2, second.moves().len());
+}
@@ -0,0 +1,104 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::Color;
+use rust_chess::endgame::{adjust, probe_kpk};
+use rust_chess::game::Game;
+use rust_chess::parser::parse_fen;
+use rust_chess::tablebase::{Tablebase, Wdl};
+
+fn kpk(fen: &str, to_move: Color) -> Option<Wdl> {
+    probe_kpk(&parse_fen(fen).unwrap(), to_move)
+}
+
+#[test]
+fn probing_king_and_pawn_against_king() {
+    // the king in front of its pawn on the sixth rank wins whoever moves
+    assert_eq!(Some(Wdl::Win), kpk("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", Color::White));
+    assert_eq!(Some(Wdl::Loss), kpk("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1", Color::Black));
+    // the defending king in front of the pawn holds with the opposition
+    assert_eq!(Some(Wdl::Draw), kpk("8/8/4k3/8/8/4P3/4K3/8 w - - 0 1", Color::White));
+    // the rook pawn is a draw once the king reaches the corner
+    assert_eq!(Some(Wdl::Draw), kpk("k7/8/8/8/P7/8/8/7K w - - 0 1", Color::White));
+    // the king is outside the square of the pawn
+    assert_eq!(Some(Wdl::Win), kpk("7k/8/8/8/P7/8/8/K7 w - - 0 1", Color::White));
+    assert_eq!(Some(Wdl::Loss), kpk("7k/8/8/8/P7/8/8/K7 b - - 0 1", Color::Black));
+
+    // the same with the colors swapped
+    assert_eq!(Some(Wdl::Loss), kpk("8/8/8/8/3p4/3k4/8/3K4 w - - 0 1", Color::White));
+    assert_eq!(Some(Wdl::Draw), kpk("7k/8/8/8/8/8/p7/K7 w - - 0 1", Color::White));
+
+    assert_eq!(None, kpk("4k3/8/4K3/4P3/8/8/8/3N4 w - - 0 1", Color::White));
+    assert_eq!(None, kpk("4k3/8/4K3/8/8/8/8/8 w - - 0 1", Color::White));
+}
+
+#[test]
+fn kpk_agrees_with_the_tablebase() {
+    let tablebase = Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy")).unwrap();
+    let mut probed = 0;
+    for (pawn, pawn_color) in [(34, Color::White), (15, Color::Black)] {
+        for white_king in 0..64 {
+            for black_king in (0..64).filter(|&sq| distance(sq, white_king) > 1) {
+                if pawn == white_king || pawn == black_king {
+                    continue;
+                }
+                let mut ranks = vec! [[None; 8]; 8];
+                ranks[white_king / 8][white_king % 8] = Some('K');
+                ranks[black_king / 8][black_king % 8] = Some('k');
+                ranks[pawn / 8][pawn % 8] = Some(if pawn_color == Color::White { 'P' } else { 'p' });
+                let position = ranks.iter().rev().map(|rank| {
+                    rank.iter().fold(String::new(), |mut fen, square| {
+                        match (square, fen.pop()) {
+                            (Some(piece), last) => fen.extend(last.into_iter().chain([*piece])),
+                            (None, Some(empty @ '1'..='7')) => fen.push((empty as u8 + 1) as char),
+                            (None, last) => fen.extend(last.into_iter().chain(['1'])),
+                        }
+                        fen
+                    })
+                }).collect::<Vec<_>>().join("/");
+
+                for to_move in [Color::White, Color::Black] {
+                    // the pawn may not give check to the side which is not to move
+                    let defender = if pawn_color == Color::White { black_king } else { white_king };
+                    let forward = if pawn_color == Color::White { 1 } else { -1 };
+                    if to_move == pawn_color && distance(pawn, defender) == 1 && (defender / 8) as i32 == (pawn / 8) as i32 + forward && defender % 8 != pawn % 8 {
+                        continue;
+                    }
+                    let fen = format!("{} {} - - 0 1", position, if to_move == Color::White { "w" } else { "b" });
+                    let game = Game::from_fen(&fen).unwrap();
+                    assert_eq!(tablebase.probe_wdl(&game), probe_kpk(&game.board, to_move), "{}", fen);
+                    probed += 1;
+                }
+            }
+        }
+    }
+    assert_eq!(true, probed > 13_000);
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8))
+}
+
+#[test]
+fn adjusting_scores_of_known_endings() {
+    let adjusted = |fen: &str, score| adjust(&parse_fen(fen).unwrap(), Color::White, score);
+    // lost pawn endings are not worth a pawn
+    assert_eq!(0, adjusted("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1", 120));
+    assert_eq!(120, adjusted("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", 120));
+
+    // the bishop does not control h8, a dark square
+    assert_eq!(0, adjusted("7k/8/7P/7P/8/8/2B5/4K3 w - - 0 1", 520));
+    assert_eq!(520, adjusted("7k/8/7P/7P/8/8/3B4/4K3 w - - 0 1", 520));
+    assert_eq!(520, adjusted("8/8/4k2P/7P/8/8/2B5/4K3 w - - 0 1", 520));
+    assert_eq!(0, adjusted("4k3/8/2b5/8/p7/8/8/1K6 w - - 0 1", -420));
+
+    // with a dark-squared bishop the king is driven to a1 or h8
+    let corner = adjusted("8/8/8/8/8/2K5/8/k1B1N3 w - - 0 1", 620);
+    let wrong_corner = adjusted("k7/8/2K5/8/8/8/8/2B1N3 w - - 0 1", 620);
+    let center = adjusted("8/8/8/3k4/8/2K5/8/2B1N3 w - - 0 1", 620);
+    assert_eq!(true, corner > wrong_corner && wrong_corner > center);
+    assert_eq!(-corner, adjusted("K1b1n3/8/2k5/8/8/8/8/8 w - - 0 1", -620));
+
+    // bishops of opposite colors
+    assert_eq!(100, adjusted("4k3/5p2/4b3/8/8/2B1P3/2P5/4K3 w - - 0 1", 200));
+    assert_eq!(200, adjusted("4k3/5p2/3b4/8/8/2B1P3/2P5/4K3 w - - 0 1", 200));
+    assert_eq!(200, adjusted("4k3/5p2/4b3/8/8/2B1P3/2P5/3RK3 w - - 0 1", 200));
+}
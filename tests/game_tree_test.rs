@@ -0,0 +1,161 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::game::{Game, GameTree, Move};
+
+fn uci(moves: &[Move]) -> Vec<String> {
+    moves.iter().map(|mv| mv.to_uci()).collect()
+}
+
+#[test]
+fn mainline_and_navigation() {
+    let mut game = Game::new();
+    common::play_uci(&mut game, "e2e4 e7e5 g1f3");
+    assert_eq!(vec! ["e2e4", "e7e5", "g1f3"], uci(&game.tree().mainline()));
+    assert_eq!(vec! ["e2e4", "e7e5", "g1f3"], uci(&game.moves()));
+
+    assert_eq!(true, game.back());
+    assert_eq!(true, game.back());
+    assert_eq!(vec! ["e2e4"], uci(&game.moves()));
+    assert_eq!("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1", game.to_fen());
+
+    assert_eq!(true, game.forward());
+    assert_eq!("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", game.to_fen());
+    game.go_to_end();
+    assert_eq!(false, game.forward());
+    assert_eq!(3, game.moves().len());
+
+    game.go_to_start();
+    assert_eq!(false, game.back());
+    assert_eq!(GameTree::ROOT, game.cursor());
+    assert_eq!(Game::new().to_fen(), game.to_fen());
+
+    // replaying a move from the tree follows it instead of adding a variation
+    common::play_uci(&mut game, "e2e4 e7e5");
+    assert_eq!(true, game.variations().is_empty());
+    assert_eq!(3, game.tree().mainline().len());
+}
+
+#[test]
+fn variations() {
+    let mut game = Game::new();
+    common::play_uci(&mut game, "e2e4 e7e5 g1f3");
+    game.back();
+    game.back();
+    common::play_uci(&mut game, "c7c5 g1f3");
+    game.back();
+    game.back();
+    common::play_uci(&mut game, "e7e6");
+    game.back();
+
+    // 1. e4 e5 (1... c5 2. Nf3) (1... e6) 2. Nf3
+    assert_eq!(2, game.variations().len());
+    assert_eq!(vec! ["e2e4", "e7e5", "g1f3"], uci(&game.tree().mainline()));
+
+    assert_eq!(true, game.enter_variation(0));
+    assert_eq!(vec! ["e2e4", "c7c5"], uci(&game.moves()));
+    assert_eq!(true, game.forward());
+    assert_eq!("rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2", game.to_fen());
+    game.back();
+    game.back();
+    assert_eq!(false, game.enter_variation(2));
+    assert_eq!(true, game.enter_variation(1));
+    assert_eq!(vec! ["e2e4", "e7e6"], uci(&game.moves()));
+
+    let node = game.cursor();
+    game.go_to_start();
+    assert_eq!(true, game.go_to(node));
+    assert_eq!(vec! ["e2e4", "e7e6"], uci(&game.moves()));
+    assert_eq!(false, game.tree().is_mainline_child(node));
+}
+
+#[test]
+fn promote_and_delete_variations() {
+    let mut game = Game::new();
+    common::play_uci(&mut game, "e2e4 e7e5 g1f3");
+    game.go_to_start();
+    common::play_uci(&mut game, "d2d4 d7d5 c2c4");
+    game.back();
+
+    // promoting from anywhere inside the variation swaps it with the mainline at its start
+    assert_eq!(false, game.tree().is_mainline_child(game.tree().children(GameTree::ROOT)[1]));
+    assert_eq!(true, game.promote_variation());
+    assert_eq!(vec! ["d2d4", "d7d5", "c2c4"], uci(&game.tree().mainline()));
+    assert_eq!(vec! ["d2d4", "d7d5"], uci(&game.moves()));
+    assert_eq!(false, game.promote_variation());
+
+    game.go_to_start();
+    assert_eq!(1, game.variations().len());
+    game.enter_variation(0);
+    game.forward();
+    common::play_uci(&mut game, "b1c3");
+    game.back();
+    common::play_uci(&mut game, "f1c4");
+
+    // deleting the innermost variation keeps the outer one and its siblings
+    assert_eq!(true, game.delete_variation());
+    assert_eq!(vec! ["e2e4", "e7e5"], uci(&game.moves()));
+    assert_eq!(1, game.variations().len());
+    assert_eq!(true, game.forward());
+
+    assert_eq!(true, game.delete_variation());
+    assert_eq!(GameTree::ROOT, game.cursor());
+    assert_eq!(true, game.variations().is_empty());
+    assert_eq!(vec! ["d2d4", "d7d5", "c2c4"], uci(&game.tree().mainline()));
+    assert_eq!(false, game.delete_variation());
+}
+
+#[test]
+fn from_fen_start_position() {
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    common::play_uci(&mut game, "e2e4 e8d7 e4e5");
+    game.back();
+    assert_eq!("8/3k4/8/8/4P3/8/8/4K3 w - - 1 2", game.to_fen());
+}
+
+#[test]
+fn going_to_deleted_nodes() {
+    let mut game = Game::new();
+    common::play_uci(&mut game, "e2e4 e7e5 g1f3");
+    game.go_to_start();
+    common::play_uci(&mut game, "d2d4 d7d5");
+    let deleted = game.cursor();
+    game.back();
+    let start = game.cursor();
+    assert_eq!(true, game.delete_variation());
+
+    // neither the variation nor any move in it can be reached any more, the position stays as it is
+    assert_eq!(false, game.tree().contains(start));
+    assert_eq!(false, game.tree().contains(deleted));
+    assert_eq!(true, game.tree().node(deleted).is_none());
+    assert_eq!(true, game.tree().node(1000).is_none());
+    assert_eq!(true, game.tree_mut().meta_mut(deleted).is_none());
+    assert_eq!(true, game.tree().children(start).is_empty());
+    assert_eq!(false, game.go_to(deleted));
+    assert_eq!(false, game.go_to(1000));
+    assert_eq!(GameTree::ROOT, game.cursor());
+    assert_eq!(Game::new().to_fen(), game.to_fen());
+
+    let end = game.tree().children(GameTree::ROOT)[0];
+    assert_eq!(true, game.go_to(end));
+    assert_eq!(vec! ["e2e4"], uci(&game.moves()));
+}
+
+#[test]
+fn taking_back_special_moves() {
+    // castling, en-passant, promotion with capture and the crazyhouse pockets are all restored move by move
+    for (fen, moves) in [("r3k2r/6P1/8/8/5p2/8/4P3/R3K2R w KQkq - 0 1", "e1g1 e8c8 e2e4 f4e3 g7h8q d8h8"),
+                         ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1", "e2e4 d7d5 e4d5 d8d5 P@e4 d5e4")] {
+        let mut game = if fen.contains('[') { Game::new_crazyhouse() } else { Game::from_fen(fen).unwrap() };
+        let mut fens = vec! [game.to_fen()];
+        for mv in moves.split_whitespace() {
+            common::play_uci(&mut game, mv);
+            fens.push(game.to_fen());
+        }
+        fens.pop();
+        while let Some(fen) = fens.pop() {
+            assert_eq!(true, game.back());
+            assert_eq!(fen, game.to_fen());
+        }
+        assert_eq!(false, game.back());
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::Color;
+use rust_chess::game::{Game, GameResult, Termination};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::variant::{Horde, RacingKings};
+use std::sync::Arc;
+
+#[test]
+fn horde_rules() {
+    let game = Game::with_variant(Arc::new(Horde));
+    assert_eq!(36, game.board.pieces.iter().filter(|p| p.color == Color::White).count());
+    assert_eq!(None, game.board.king(Color::White));
+    assert_eq!(false, game.in_check_color(Color::White));
+
+    // pawns on the first rank may move two squares, and be taken en passant then
+    let mut game = Game::from_fen_with_variant("4k3/8/8/8/8/8/1p6/P7 w - - 0 1", Arc::new(Horde)).unwrap();
+    assert_eq!(true, game.parse_san("a3").is_ok());
+    let mut blocked = game.clone();
+    common::play(&mut game, "a3 Kd7");
+    assert_eq!(true, game.parse_san("a5").is_err());
+    common::play(&mut blocked, "a2 b1=Q");
+    assert_eq!(true, blocked.parse_san("a4").is_ok());
+
+    // capturing the whole horde wins
+    let mut game = Game::from_fen_with_variant("4k3/8/8/8/8/8/1p6/P7 b - - 0 1", Arc::new(Horde)).unwrap();
+    common::play(&mut game, "bxa1=Q");
+    assert_eq!(Some(GameResult::BlackWin), game.update_result());
+    assert_eq!(Some(Termination::NoPiecesLeft), game.termination());
+
+    let mut game = Game::with_variant(Arc::new(Horde));
+    common::play(&mut game, "b6 axb6 cxb6");
+    let imported = parse_pgn(&export_pgn(&game)).unwrap();
+    assert_eq!("Horde", imported.variant().name());
+    assert_eq!(game.to_fen(), imported.to_fen());
+}
+
+#[test]
+fn racing_kings_rules() {
+    let game = Game::with_variant(Arc::new(RacingKings));
+    assert_eq!("8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1", game.to_fen());
+    // giving check is not allowed
+    assert_eq!(true, game.parse_san("Nc3").is_err());
+
+    // Black may still equalize once the White king got to the eighth rank
+    let mut game = Game::from_fen_with_variant("8/1k4K1/8/8/8/8/8/8 w - - 0 1", Arc::new(RacingKings)).unwrap();
+    common::play(&mut game, "Kg8");
+    assert_eq!(None, game.update_result());
+    common::play(&mut game, "Kb8");
+    assert_eq!(Some(GameResult::Draw), game.update_result());
+    assert_eq!(Some(Termination::KingReachedGoal), game.termination());
+
+    // unless the Black king is too far away
+    let mut game = Game::from_fen_with_variant("8/6K1/1k6/8/8/8/8/8 w - - 0 1", Arc::new(RacingKings)).unwrap();
+    common::play(&mut game, "Kg8");
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result());
+    assert_eq!(true, game.get_legal_moves(Color::Black).is_empty());
+
+    // Black reaching the goal first wins
+    let mut game = Game::from_fen_with_variant("8/1k6/8/6K1/8/8/8/8 b - - 0 1", Arc::new(RacingKings)).unwrap();
+    common::play(&mut game, "Kb8");
+    assert_eq!(Some(GameResult::BlackWin), game.update_result());
+    assert_eq!(true, export_pgn(&game).contains("[Variant \"Racing Kings\"]\n[SetUp \"1\"]"));
+}
+
+#[test]
+fn horde_perft() {
+    let game = Game::with_variant(Arc::new(Horde));
+    assert_eq!(vec! [8, 128, 1274, 23310], (1..=4).map(|depth| common::perft(&game, depth)).collect::<Vec<u64>>());
+}
+
+#[test]
+fn racing_kings_perft() {
+    let game = Game::with_variant(Arc::new(RacingKings));
+    assert_eq!(vec! [21, 421, 11264], (1..=3).map(|depth| common::perft(&game, depth)).collect::<Vec<u64>>());
+}
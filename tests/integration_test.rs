@@ -1,3 +1,4 @@
+#![allow(clippy::bool_assert_comparison)]
 use rust_chess::core::{Board, Square, Color, Figure, Piece};
 use std::collections::HashSet;
 
@@ -156,6 +157,18 @@ fn pieces_pawn_available_squares() {
     }
 }
 
+#[test]
+fn pieces_black_pawn_double_step() {
+    // black pawns move down the board, two squares from the seventh rank only
+    let board = rust_chess::parser::parse_fen("4k3/4p3/2p5/6p1/3p2P1/8/5p2/4K3 b - - 0 1").unwrap();
+    let squares = |name: &str| board.check_square_for_piece(&Square::from_str(name)).unwrap().get_available_squares(&board);
+    assert_eq!(common::sqrs(&board, "e6,e5"), squares("e7"));
+    assert_eq!(common::sqrs(&board, "c5"), squares("c6"));
+    assert_eq!(common::sqrs(&board, "d3"), squares("d4"));
+    assert_eq!(true, squares("g5").is_empty());
+    assert_eq!(common::sqrs(&board, "f1,e1"), squares("f2"));
+}
+
 #[test]
 fn pieces_rook_available_squares() {
     let mut board = common::empty_board();
@@ -264,7 +277,64 @@ fn is_attacked() {
     assert_eq!(true, board.is_attacked(pb2));
     assert_eq!(true, board.is_attacked(kc3));
     assert_eq!(false, board.is_attacked(kd1));
-    
+
+}
+
+#[test]
+fn attackers_and_defenders() {
+    let board = rust_chess::parser::parse_fen("4k3/2p5/8/1N1r4/8/8/3R4/3QK3 w - - 0 1").unwrap();
+    let figures = |pieces: Vec<Piece>| pieces.iter().map(|p| p.figure).collect::<HashSet<Figure>>();
+    // the queen stands behind the rook, the pawn attacks the square diagonally in front of it
+    assert_eq!(HashSet::from([Figure::Rook]), figures(board.attackers_of(Square::from_str("d5"), Color::White)));
+    assert_eq!(HashSet::from([Figure::Knight]), figures(board.attackers_of(Square::from_str("c7"), Color::White)));
+    assert_eq!(HashSet::from([Figure::Pawn]), figures(board.attackers_of(Square::from_str("b6"), Color::Black)));
+    assert_eq!(0, board.attackers_of(Square::from_str("c6"), Color::Black).len());
+    assert_eq!(HashSet::from([Figure::Rook]), figures(board.attackers_of(Square::from_str("d5"), Color::Black).into_iter()
+        .chain(board.attackers_of(Square::from_str("d2"), Color::Black)).collect()));
+
+    let rook = *board.check_square_for_piece(&Square::from_str("d2")).unwrap();
+    assert_eq!(HashSet::from([Figure::Queen, Figure::King]), figures(board.defenders_of(&rook)));
+    let knight = *board.check_square_for_piece(&Square::from_str("b5")).unwrap();
+    assert_eq!(0, board.defenders_of(&knight).len());
+    // the black pawn attacks b6 and d6, and does not defend anything
+    let pawn = *board.check_square_for_piece(&Square::from_str("c7")).unwrap();
+    assert_eq!(0, board.defenders_of(&pawn).len());
+}
+
+#[test]
+fn attacked_squares() {
+    let board = rust_chess::parser::parse_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    let squares = |names: &[&str]| names.iter().map(|name| Square::from_str(name)).collect::<HashSet<Square>>();
+    // the pawn attacks diagonally and not the square in front of it, the king defends its pawn
+    assert_eq!(squares(&["d3", "f3", "d1", "d2", "e2", "f2", "f1"]), board.attacked_squares(Color::White));
+    assert_eq!(squares(&["d8", "d7", "e7", "f7", "f8"]), board.attacked_squares(Color::Black));
+
+    // pieces block the lines of sliders
+    let board = rust_chess::parser::parse_fen("k7/8/8/p7/8/2p5/8/R6K w - - 0 1").unwrap();
+    let attacked = board.attacked_squares(Color::White);
+    assert_eq!(true, attacked.contains(&Square::from_str("a5")));
+    assert_eq!(false, attacked.contains(&Square::from_str("a6")));
+    assert_eq!(true, attacked.contains(&Square::from_str("h1")));
+    let attacked = board.attacked_squares(Color::Black);
+    assert_eq!(true, attacked.contains(&Square::from_str("b2")) && attacked.contains(&Square::from_str("d2")));
+    assert_eq!(false, attacked.contains(&Square::from_str("c2")));
+}
+
+#[test]
+fn is_attacked_agrees_with_attacked_squares() {
+    // is_attacked looks outwards from the piece, compare it with the squares the opposing pieces can capture on
+    for fen in ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"] {
+        let board = rust_chess::parser::parse_fen(fen).unwrap();
+        for piece in board.pieces.iter() {
+            let attacked = board.pieces.iter()
+                .filter(|p| p.color != piece.color)
+                .any(|p| p.get_attacked_squares(&board).contains(&piece.square));
+            assert_eq!(attacked, board.is_attacked(*piece), "{} {}", fen, piece.square);
+        }
+    }
 }
 
 
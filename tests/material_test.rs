@@ -0,0 +1,52 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::{Color, Figure};
+use rust_chess::game::STANDARD_FEN;
+use rust_chess::material::{Material, MAX_PHASE};
+use rust_chess::parser::parse_fen;
+
+fn material(fen: &str) -> Material {
+    Material::from_board(&parse_fen(fen).unwrap())
+}
+
+#[test]
+fn counting_pieces() {
+    let start = material(STANDARD_FEN);
+    assert_eq!("KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP", start.signature());
+    assert_eq!(8, start.count(Color::Black, Figure::Pawn));
+    assert_eq!(2, start.count(Color::White, Figure::Rook));
+    assert_eq!(16, start.pieces(Color::White));
+    assert_eq!(0, start.balance());
+    assert_eq!(MAX_PHASE, start.phase());
+    assert_eq!(start, start.flipped());
+
+    let ending = material("8/8/4k3/8/3nb3/8/2PK4/1Q6 b - - 0 1");
+    assert_eq!("KQPvKBN", ending.signature());
+    assert_eq!("KBNvKQP", ending.flipped().signature());
+    assert_eq!(4, ending.balance());
+    assert_eq!(-4, ending.flipped().balance());
+    assert_eq!(6, ending.phase());
+
+    // kings and pawns only, and more queens than the phase allows for
+    assert_eq!(0, material("8/5k2/5p2/8/8/2P5/2K5/8 w - - 0 1").phase());
+    assert_eq!(MAX_PHASE, material("QQQQ3k/QQQQ4/8/8/8/8/8/7K w - - 0 1").phase());
+    assert_eq!("KvK", material("8/8/4k3/8/8/8/4K3/8 w - - 0 1").signature());
+}
+
+#[test]
+fn finding_bishops() {
+    // c1 and f8 are dark, f1 and c8 light
+    let start = material(STANDARD_FEN);
+    assert_eq!(true, start.has_bishop_pair(Color::White));
+    assert_eq!(true, start.has_bishop_pair(Color::Black));
+    assert_eq!(false, start.has_opposite_colored_bishops());
+
+    let same_colors = material("2b1k3/8/8/8/8/8/8/2B1K1B1 w - - 0 1");
+    assert_eq!(false, same_colors.has_bishop_pair(Color::White));
+    assert_eq!(false, same_colors.has_bishop_pair(Color::Black));
+    assert_eq!(false, same_colors.has_opposite_colored_bishops());
+
+    assert_eq!(true, material("4b3/4k3/8/8/8/8/4K3/2B5 w - - 0 1").has_opposite_colored_bishops());
+    assert_eq!(false, material("5b2/4k3/8/8/8/8/4K3/2B5 w - - 0 1").has_opposite_colored_bishops());
+    // the rest of the material does not matter
+    assert_eq!(true, material("3rk1b1/8/8/8/8/8/8/2BQK3 w - - 0 1").has_opposite_colored_bishops());
+}
@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod tests {
     
-    use rust_chess::parser::parse_fen;
+    use rust_chess::parser::{parse_fen, export_fen_position};
     use rust_chess::core::{Board, Piece, Color, Figure, Square};
+    use rust_chess::game::Game;
     #[test]
     fn create_std_board_from_fen() {
         let std_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
@@ -57,15 +58,49 @@ mod tests {
    #[test]
    fn create_test_position_from_fen() {
     let testfen = "r4k1r/1p1n1ppp/2p2n2/p7/P2qP3/2NP1Q2/1PP2RPP/R5K1 w - - 0 1";
-    let board_in_test_position = rust_chess::parser::parse_fen(testfen);
+    let board_in_test_position = parse_fen(testfen).unwrap();
     // https://lichess.org/editor/r4k1r/1p1n1ppp/2p2n2/p7/P2qP3/2NP1Q2/1PP2RPP/R5K1_w_-_-_0_1
 
-    assert_eq!(1, 1);
+    assert_eq!(24, board_in_test_position.pieces.len());
+    assert_eq!(Some(&Piece {color: Color::Black, figure: Figure::Pawn, square: Square::from_str("c6")}),
+        board_in_test_position.check_square_for_piece(&Square::from_str("c6")));
+    assert_eq!(Some(&Piece {color: Color::Black, figure: Figure::Knight, square: Square::from_str("f6")}),
+        board_in_test_position.check_square_for_piece(&Square::from_str("f6")));
+    assert_eq!(Some(&Piece {color: Color::White, figure: Figure::King, square: Square::from_str("g1")}),
+        board_in_test_position.check_square_for_piece(&Square::from_str("g1")));
+
+    assert!(parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1").is_err());
+    assert!(parse_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
+    assert!(parse_fen("rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_err());
    }
 
    #[test]
    fn test_fen_export() {
-       assert_eq!(1,1)
+       let testfen = "r4k1r/1p1n1ppp/2p2n2/p7/P2qP3/2NP1Q2/1PP2RPP/R5K1";
+       assert_eq!(testfen, export_fen_position(&parse_fen(testfen).unwrap()));
+       assert_eq!("8/8/8/8/8/8/8/8", export_fen_position(&Board::new()));
+
+       for fen in ["rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                   "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                   "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                   "r3k3/8/8/8/8/8/8/4K2R b Kq - 12 40"] {
+           assert_eq!(fen, Game::from_fen(fen).unwrap().to_fen());
+       }
+   }
+
+   #[test]
+   fn fen_digits_skip_empty_squares() {
+       // a digit skips exactly as many squares, the piece after it stands on the next one
+       let board = parse_fen("r3k2r/1p5p/7n/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+       for (square, figure) in [("a8", Figure::Rook), ("e8", Figure::King), ("h8", Figure::Rook), ("b7", Figure::Pawn),
+                                ("h7", Figure::Pawn), ("h6", Figure::Knight), ("d5", Figure::Pawn), ("e1", Figure::King)] {
+           assert_eq!(Some(figure), board.check_square_for_piece(&Square::from_str(square)).map(|p| p.figure), "{}", square);
+       }
+       assert_eq!(8, board.pieces.len());
+
+       // ranks with too few or too many squares
+       assert!(parse_fen("r3k1r/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+       assert!(parse_fen("r3k3r/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
    }
 
 
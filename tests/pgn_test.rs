@@ -0,0 +1,166 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::Square;
+use rust_chess::game::{Arrow, Game, GameResult, GameTree, Highlight, MarkColor};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::search::Score;
+
+use std::time::Duration;
+
+const ANNOTATED: &str = r#"[Event "Casual Game"]
+[Site "London"]
+[Date "1851.06.21"]
+[Round "?"]
+[White "Anderssen, Adolf"]
+[Black "Kieseritzky, Lionel"]
+[Result "1-0"]
+[ECO "C33"]
+
+{The Immortal Game} 1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5?! 5. Bxb5 Nf6
+6. Nf3 Qh6 7. d3 Nh5 8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1! cxb5 12. h4 Qg6
+13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1
+(18... Qxa1+ 19. Ke2 Qb2 20. Kd2 {and White still wins}) 19. e5 Qxa1+ 20. Ke2
+Na6 21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+fn san_line(game: &Game) -> Vec<String> {
+    let mut replay = Game::from_fen(&game.start_fen).unwrap();
+    game.moves().iter()
+        .map(|mv| {
+            let san = replay.move_to_san(mv);
+            replay.make_move(*mv);
+            san
+        })
+        .collect()
+}
+
+#[test]
+fn san_moves() {
+    let mut game = Game::from_fen("r3k2r/1P6/8/3pP3/8/2N3N1/8/R3K2R w KQkq d6 0 1").unwrap();
+    for (san, uci) in [("O-O", "e1g1"), ("O-O-O", "e1c1"), ("exd6", "e5d6"), ("Nce4", "c3e4"), ("Nge4", "g3e4"),
+                       ("bxa8=Q+", "b7a8q"), ("b8=N", "b7b8n"), ("Rb1", "a1b1")] {
+        let mv = game.parse_san(san).unwrap();
+        assert_eq!(uci, mv.to_uci());
+        assert_eq!(san, game.move_to_san(&mv));
+    }
+    assert_eq!("e1g1", game.parse_san("0-0").unwrap().to_uci());
+    assert_eq!("b7a8q", game.parse_san("bxa8Q").unwrap().to_uci());
+    assert_eq!("Ambiguous move", game.parse_san("Ne4").unwrap_err());
+    assert_eq!("Illegal move", game.parse_san("Nd4").unwrap_err());
+    assert_eq!("Invalid SAN move notation", game.parse_san("Zz9").unwrap_err());
+
+    // disambiguation by rank and by the full square
+    game = Game::from_fen("8/7k/8/8/Q2Q4/8/8/Q6K w - - 0 1").unwrap();
+    assert_eq!("Q1a3", game.move_to_san(&game.parse_san("Q1a3").unwrap()));
+    assert_eq!("Qa4d1", game.move_to_san(&game.parse_uci_move("a4d1").unwrap()));
+
+    game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    assert_eq!("Ra8#", game.move_to_san(&game.parse_san("Ra8").unwrap()));
+}
+
+#[test]
+fn import_game_with_tags_and_variation() {
+    let game = parse_pgn(ANNOTATED).unwrap();
+    let meta = game.meta.as_ref().unwrap();
+    assert_eq!(Some("Anderssen, Adolf"), meta.tag("White"));
+    assert_eq!(Some("C33"), meta.tag("ECO"));
+    assert_eq!(Some(GameResult::WhiteWin), game.result());
+    assert_eq!(45, game.moves().len());
+    assert_eq!("Be7#", san_line(&game).last().unwrap());
+    assert_eq!(Some(String::from("The Immortal Game")), game.tree().node(GameTree::ROOT).unwrap().meta.comment_after);
+
+    // 4... b5?! and 11. Rg1!
+    let mut replay = game.clone();
+    replay.go_to_start();
+    for _ in 0..8 {
+        replay.forward();
+    }
+    assert_eq!(vec! [6], replay.move_meta().nags);
+    for _ in 0..13 {
+        replay.forward();
+    }
+    assert_eq!(vec! [1], replay.move_meta().nags);
+
+    // the variation branches off before 18... Bxg1
+    for _ in 0..14 {
+        replay.forward();
+    }
+    assert_eq!(1, replay.variations().len());
+    assert_eq!(true, replay.enter_variation(0));
+    replay.go_to_end();
+    assert_eq!(Some(String::from("and White still wins")), replay.move_meta().comment_after);
+    assert_eq!(4, replay.moves().len() - 35);
+}
+
+#[test]
+fn castling_with_zeros() {
+    // the zeros must not be mistaken for a move number, also without a space after the number
+    let game = parse_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 d6 5.d3 Be6 6. Bxe6 fxe6 7. Nc3 Qd7 8.a3 0-0-0 *").unwrap();
+    assert_eq!(vec! ["O-O", "d6", "d3"], san_line(&game)[6..9]);
+    assert_eq!("O-O-O", san_line(&game)[15]);
+}
+
+#[test]
+fn round_trip() {
+    let game = parse_pgn(ANNOTATED).unwrap();
+    let exported = export_pgn(&game);
+    assert_eq!(ANNOTATED, exported);
+    assert_eq!(exported, export_pgn(&parse_pgn(&exported).unwrap()));
+}
+
+#[test]
+fn comment_commands() {
+    let pgn = "1. e4 {[%eval 0.25,18] [%clk 0:04:58.5] best by test} 1... c5 $14 {[%emt 0:00:02] [%csl Gd4,Rc5] [%cal Gg1f3,Bb1c3] [%foo bar]} (1... e5 {[%eval #-3]}) *";
+    let game = parse_pgn(pgn).unwrap();
+
+    let e4 = &game.tree().node(game.tree().node(GameTree::ROOT).unwrap().mainline.unwrap()).unwrap().meta;
+    assert_eq!(Some(Score::Centipawns(25)), e4.evaluation);
+    assert_eq!(Some(18), e4.depth);
+    assert_eq!(Some(Duration::from_millis(298_500)), e4.clock);
+    assert_eq!(Some(String::from("best by test")), e4.comment_after);
+
+    let c5 = game.move_meta();
+    assert_eq!(vec! [14], c5.nags);
+    assert_eq!(Some(Duration::from_secs(2)), c5.elapsed);
+    assert_eq!(vec! [Highlight { color: MarkColor::Green, square: Square::from_str("d4") },
+                     Highlight { color: MarkColor::Red, square: Square::from_str("c5") }], c5.highlights);
+    assert_eq!(Arrow { color: MarkColor::Blue, from: Square::from_str("b1"), to: Square::from_str("c3") }, c5.arrows[1]);
+    // unknown commands are kept as text
+    assert_eq!(Some(String::from("[%foo bar]")), c5.comment_after);
+    assert_eq!(None, game.result());
+
+    let exported = export_pgn(&game);
+    assert!(exported.ends_with("\n\n1. e4 {[%eval 0.25,18] [%clk 0:04:58.5] best by test} 1... c5 $14\n\
+                                {[%emt 0:00:02] [%csl Gd4,Rc5] [%cal Gg1f3,Bb1c3] [%foo bar]} (1... e5\n\
+                                {[%eval #-3]}) *\n"));
+    assert_eq!(exported, export_pgn(&parse_pgn(&exported).unwrap()));
+}
+
+#[test]
+fn setup_position_and_comment_before_variation() {
+    let pgn = "[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n\n40... Kd7 41. e4 ({Slower is} 41. e3 Kd6) 41... Ke6 *";
+    let game = parse_pgn(pgn).unwrap();
+    assert_eq!("8/8/4k3/8/4P3/8/8/4K3 w - - 1 42", game.to_fen());
+
+    let exported = export_pgn(&game);
+    assert!(exported.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n"));
+    assert!(exported.ends_with("\n\n40... Kd7 41. e4 ({Slower is} 41. e3 Kd6) 41... Ke6 *\n"));
+    assert_eq!(exported, export_pgn(&parse_pgn(&exported).unwrap()));
+
+    // several comments in front of the move are joined, their commands go to the move
+    let game = parse_pgn("1. e4 ({Slower is} {[%eval 0.2,12] [%cal Gd2d4] but solid} 1. d4 {[%clk 0:05:00]}) *").unwrap();
+    let d4 = &game.tree().node(game.tree().node(GameTree::ROOT).unwrap().variations[0].start).unwrap().meta;
+    assert_eq!(Some(String::from("Slower is but solid")), d4.comment_before);
+    assert_eq!(None, d4.comment_after);
+    assert_eq!(Some(Score::Centipawns(20)), d4.evaluation);
+    assert_eq!(Some(12), d4.depth);
+    assert_eq!(Arrow { color: MarkColor::Green, from: Square::from_str("d2"), to: Square::from_str("d4") }, d4.arrows[0]);
+    assert_eq!(Some(Duration::from_secs(300)), d4.clock);
+}
+
+#[test]
+fn invalid_pgn() {
+    assert!(parse_pgn("1. e4 e5 2. Ke3 *").is_err());
+    assert!(parse_pgn("1. e4 (1. d4 *").is_err());
+    assert!(parse_pgn("[White \"Unterminated]").is_err());
+    assert!(parse_pgn("(1. e4) *").is_err());
+}
@@ -0,0 +1,107 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::parser::parse_pgn;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// run the play binary with the given options and input on stdin and collect its output once stdin is closed
+fn play(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rust-chess"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(true, output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn playing_moves() {
+    // SAN and both kinds of coordinates
+    let output = play(&["--ascii"], "f3\ne7-e5\ng2g4\nQh4\nfen\n");
+    assert_eq!(true, output.contains("1. f3\n"));
+    assert_eq!(true, output.contains("1... e5\n"));
+    assert_eq!(true, output.contains("2... Qh4#\n"));
+    assert_eq!(true, output.contains("Game over: 0-1 (checkmate)"));
+    assert_eq!(true, output.contains("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"));
+
+    let output = play(&["--ascii"], "e5\nmoves\nmoves g1\n");
+    assert_eq!(true, output.contains("Illegal or unknown move e5"));
+    assert_eq!(true, output.contains("Na3 Nc3 Nf3 Nh3 a3 a4 b3 b4 c3 c4 d3 d4 e3 e4 f3 f4 g3 g4 h3 h4\n"));
+    assert_eq!(true, output.contains(" 3  .  .  .  .  . [.] . [.]\n"));
+    assert_eq!(true, output.contains("\nNf3 Nh3\n"));
+}
+
+#[test]
+fn taking_back_and_flipping() {
+    let output = play(&["--ascii"], "e4\nundo\nfen\nundo\nflip\n");
+    assert_eq!(true, output.contains("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+    assert_eq!(true, output.contains("There is no move to take back."));
+    assert_eq!(true, output.contains(" 1  R  N  B  K  Q  B  N  R \n"));
+
+    // against the computer its reply is taken back too
+    let output = play(&["--black", "random", "--seed", "3"], "e4\nundo\nfen\n");
+    assert_eq!(true, output.contains("1. e4\n"));
+    assert_eq!(true, output.contains("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+}
+
+#[test]
+fn saving_and_loading_games() {
+    let path = std::env::temp_dir().join(format!("rust_chess_play_{}.pgn", std::process::id()));
+    let path = path.to_str().unwrap();
+    let output = play(&[], &format!("d4\nd5\nc4\npgn save {}\n", path));
+    assert_eq!(true, output.contains("Saved the game to"));
+    let game = parse_pgn(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(3, game.moves().len());
+
+    let output = play(&[], &format!("load {}\ndxc4\nfen\n", path));
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(true, output.contains("Loaded 3 moves"));
+    assert_eq!(true, output.contains("rnbqkbnr/ppp1pppp/8/8/2pP4/8/PP2PPPP/RNBQKBNR w KQkq - 0 3"));
+    assert_eq!(true, play(&[], "load /nonexistent/game.pgn\n").contains("/nonexistent/game.pgn: "));
+}
+
+#[test]
+fn resigning_and_draws() {
+    let output = play(&[], "e4\nresign\nd5\npgn\n");
+    assert_eq!(true, output.contains("Black resigns."));
+    assert_eq!(true, output.contains("The game is over"));
+    assert_eq!(true, output.contains("1. e4 1-0"));
+
+    let output = play(&[], "draw\ndecline\ndraw\naccept\n");
+    assert_eq!(true, output.contains("Black declines the draw."));
+    assert_eq!(true, output.contains("Black accepts the draw."));
+    assert_eq!(true, output.contains("Game over: 1/2-1/2"));
+
+    // the computer does not take a draw when it is a queen up
+    let output = play(&["--fen", "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1", "--black", "greedy"], "draw\n");
+    assert_eq!(true, output.contains("Black declines the draw."));
+}
+
+#[test]
+fn computer_opponents() {
+    // the greedy player mates when it can, and takes what is left hanging
+    let output = play(&["--fen", "4k3/8/8/8/8/8/3q2PP/7K b - - 0 1", "--white", "human", "--black", "greedy"], "");
+    assert_eq!(true, output.contains("Game over: 0-1 (checkmate)"));
+    let output = play(&["--fen", "4k3/8/8/8/8/8/R2q4/4K3 b - - 0 1", "--black", "greedy"], "");
+    assert_eq!(true, output.contains("1... Qxa2"));
+
+    // a game between two computers runs to its end on its own
+    let output = play(&["--white", "random", "--black", "random", "--seed", "7", "--fen", "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1"], "");
+    assert_eq!(true, output.contains("Game over: "));
+    // the only prompt comes at the end, when stdin is closed already
+    assert_eq!(1, output.matches("> ").count());
+
+    // a computer to move in a position which is over already
+    let output = play(&["--white", "random", "--fen", "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"], "new\n");
+    assert_eq!(2, output.matches("Game over: 0-1 (checkmate)").count());
+    let output = play(&["--black", "greedy", "--fen", "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"], "");
+    assert_eq!(true, output.contains("Game over: 1/2-1/2 (stalemate)"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rust-chess")).arg("--white").arg("strong").output().unwrap();
+    assert_eq!(false, output.status.success());
+    assert_eq!(true, String::from_utf8(output.stderr).unwrap().contains("unknown player strong"));
+}
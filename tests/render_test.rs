@@ -0,0 +1,70 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::Square;
+use rust_chess::parser::parse_fen;
+use rust_chess::render::BoardRenderer;
+
+use std::collections::HashSet;
+
+#[test]
+fn rendering_boards() {
+    let board = parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    let ascii = BoardRenderer { unicode: false, ..BoardRenderer::default() }.render(&board);
+    let lines: Vec<&str> = ascii.lines().collect();
+    assert_eq!(9, lines.len());
+    assert_eq!(" 8  r  n  b  q  k  b  n  r ", lines[0]);
+    assert_eq!(" 4  .  .  .  .  P  .  .  . ", lines[4]);
+    assert_eq!("    a  b  c  d  e  f  g  h ", lines[8]);
+
+    let unicode = BoardRenderer::default().render(&board);
+    assert_eq!(" 8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜ ", unicode.lines().next().unwrap());
+    assert_eq!(" 4  ·  ·  ·  ·  ♙  ·  ·  · ", unicode.lines().nth(4).unwrap());
+    // the board prints itself the same way
+    assert_eq!(unicode, board.to_string());
+
+    let plain = BoardRenderer { unicode: false, coordinates: false, ..BoardRenderer::default() }.render(&board);
+    assert_eq!(8, plain.lines().count());
+    assert_eq!(" R  N  B  Q  K  B  N  R ", plain.lines().last().unwrap());
+}
+
+#[test]
+fn flipping_boards() {
+    let board = parse_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let text = BoardRenderer { unicode: false, flipped: true, ..BoardRenderer::default() }.render(&board);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(" 1  .  .  .  K  .  .  .  R ", lines[0]);
+    assert_eq!(" 8  .  .  .  k  .  .  .  . ", lines[7]);
+    assert_eq!("    h  g  f  e  d  c  b  a ", lines[8]);
+}
+
+#[test]
+fn highlighting_squares() {
+    let board = parse_fen("4k3/8/8/8/8/8/8/N3K3 w - - 0 1").unwrap();
+    let knight = board.check_square_for_piece(&Square::from_str("a1")).unwrap();
+    let renderer = BoardRenderer { unicode: false, highlights: knight.get_available_squares(&board), ..BoardRenderer::default() };
+    let text = renderer.render(&board);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(" 3  . [.] .  .  .  .  .  . ", lines[5]);
+    assert_eq!(" 2  .  . [.] .  .  .  .  . ", lines[6]);
+    assert_eq!(" 1  N  .  .  .  K  .  .  . ", lines[7]);
+}
+
+#[test]
+fn rendering_colors() {
+    let board = parse_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let renderer = BoardRenderer {
+        ansi_colors: true,
+        highlights: HashSet::from([Square::from_str("e2")]),
+        ..BoardRenderer::default()
+    };
+    let text = renderer.render(&board);
+    let lines: Vec<&str> = text.lines().collect();
+    // every rank ends by resetting the colors, the labels below are not colored
+    assert_eq!(true, lines[..8].iter().all(|line| line.ends_with("\x1b[0m")));
+    assert_eq!(false, lines[8].contains('\x1b'));
+    // a1 is dark, b1 light, and the white king is bright white on the dark e1
+    assert_eq!(true, lines[7].starts_with(" 1 \x1b[48;5;137;30m   \x1b[48;5;180;30m   "));
+    assert_eq!(true, lines[7].contains("\x1b[48;5;137;97m ♚ "));
+    assert_eq!(true, lines[0].contains("\x1b[48;5;180;30m ♚ "));
+    assert_eq!(true, lines[6].contains("\x1b[48;5;107;30m   "));
+    assert_eq!(64, text.matches("\x1b[48;5;").count());
+}
@@ -0,0 +1,144 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::{Board, Color, Figure, Piece, Square};
+use rust_chess::game::Game;
+use rust_chess::parser::parse_fen;
+use rust_chess::retrograde::{unmoves, Dtm, DtmTable};
+use rust_chess::tablebase::Tablebase;
+
+use std::collections::HashSet;
+
+fn probe(table: &DtmTable, fen: &str) -> Option<Dtm> {
+    let game = Game::from_fen(fen).unwrap();
+    table.probe(&game.board, game.current_color())
+}
+
+#[test]
+fn generating_tables() {
+    assert_eq!(true, DtmTable::generate("KPvK").is_err());
+    assert_eq!(true, DtmTable::generate("KQRvKR").is_err());
+    assert_eq!(true, DtmTable::generate("KQ").is_err());
+
+    // the longest mates are known to take 10 moves with the queen and 16 with the rook
+    let queen = DtmTable::generate("KQvK").unwrap();
+    assert_eq!(19, queen.longest_mate());
+    let rook = DtmTable::generate("KRvK").unwrap();
+    assert_eq!("KRvK", rook.material());
+    assert_eq!(31, rook.longest_mate());
+    // there is no mate with a single minor piece
+    assert_eq!(0, DtmTable::generate("KNvK").unwrap().longest_mate());
+}
+
+#[test]
+fn probing_tables() {
+    let table = DtmTable::generate("KQvK").unwrap();
+    assert_eq!(Some(Dtm::Win(1)), probe(&table, "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"));
+    assert_eq!(Some(Dtm::Loss(0)), probe(&table, "1Q5k/8/6K1/8/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(Dtm::Loss(2)), probe(&table, "7k/8/6K1/8/8/8/8/1Q6 b - - 0 1"));
+    // stalemate, and the queen can be taken
+    assert_eq!(Some(Dtm::Draw), probe(&table, "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(Dtm::Draw), probe(&table, "8/8/8/8/8/2k5/1Q6/7K b - - 0 1"));
+    // the black king can not be in check with White to move
+    assert_eq!(None, probe(&table, "1Q5k/8/6K1/8/8/8/8/8 w - - 0 1"));
+    assert_eq!(None, probe(&table, "7k/8/6K1/8/8/8/8/1R6 w - - 0 1"));
+
+    // the same positions with the colors swapped
+    assert_eq!(Some(Dtm::Win(1)), probe(&table, "1q6/8/8/8/8/6k1/8/7K b - - 0 1"));
+    assert_eq!(Some(Dtm::Loss(0)), probe(&table, "8/8/8/8/8/6k1/8/1q5K w - - 0 1"));
+}
+
+#[test]
+#[ignore = "generating a table with four pieces takes over a minute in debug builds, run it with --release"]
+fn tables_with_captures() {
+    let table = DtmTable::generate("KQvKR").unwrap();
+    // the rook takes the queen, which is defended by the king only
+    assert_eq!(Some(Dtm::Draw), probe(&table, "8/8/8/8/8/1k6/8/KQ5r b - - 0 1"));
+    // White takes the rook and mates with the queen
+    let dtm = probe(&table, "3k4/8/3K4/8/8/8/8/Q2r4 w - - 0 1");
+    assert_eq!(true, matches!(dtm, Some(Dtm::Win(_))));
+    assert_eq!(true, table.longest_mate() > 31);
+}
+
+#[test]
+#[ignore = "generating a table with four pieces takes over a minute in debug builds, run it with --release"]
+fn mating_with_bishop_and_knight() {
+    // the longest mate with bishop and knight is known to take 33 moves
+    let table = DtmTable::generate("KBNvK").unwrap();
+    assert_eq!(65, table.longest_mate());
+    assert_eq!(Some(Dtm::Win(1)), probe(&table, "k7/3N4/1K2B3/8/8/8/8/8 w - - 0 1"));
+    assert_eq!(Some(Dtm::Loss(0)), probe(&table, "k7/3N4/1K6/3B4/8/8/8/8 b - - 0 1"));
+}
+
+#[test]
+fn saving_and_loading_tables() {
+    let table = DtmTable::generate("KRvK").unwrap();
+    let bytes = table.to_bytes();
+    // less than five bits per position
+    assert_eq!(true, bytes.len() * 8 < table.len() * 5);
+    assert_eq!(table, DtmTable::from_bytes(&bytes).unwrap());
+
+    let path = std::env::temp_dir().join(format!("rust_chess_retrograde_{}.dtm", std::process::id()));
+    table.save(&path).unwrap();
+    let loaded = DtmTable::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(Some(Dtm::Win(1)), probe(&loaded, "k7/8/1K6/8/8/8/8/7R w - - 0 1"));
+
+    assert_eq!(true, DtmTable::from_bytes(b"RDTM").is_err());
+    assert_eq!(true, DtmTable::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+}
+
+#[test]
+fn taking_back_moves() {
+    let board = parse_fen("8/8/8/8/8/2k5/8/K6R w - - 0 1").unwrap();
+    let from = |color| unmoves(&board, color).iter().map(|(piece, square)| format!("{:?}{}", piece.figure, square)).collect::<HashSet<_>>();
+    // the rook came along the first rank or down the h-file, the king from a2, b1 or b2
+    assert_eq!(6 + 7 + 3, from(Color::White).len());
+    assert_eq!(true, from(Color::White).contains("Rookb1") && from(Color::White).contains("Kingb2"));
+    assert_eq!(8, from(Color::Black).len());
+    // pawns are not taken back
+    let board = parse_fen("8/8/8/8/4P3/2k5/8/K7 b - - 0 1").unwrap();
+    assert_eq!(3, unmoves(&board, Color::White).len());
+}
+
+#[test]
+fn checkmates_agree_with_the_game() {
+    // the solver finds the mates without the move generation of the game, compare the two for all positions with the
+    // black king on a8
+    for (material, figure) in [("KQvK", Figure::Queen), ("KRvK", Figure::Rook)] {
+        let table = DtmTable::generate(material).unwrap();
+        let mut mates = 0;
+        for king in 0..64 {
+            for other in (0..64).filter(|&sq| sq != king && sq != 56) {
+                let square = |index: usize| Square { pos: ((b'a' + (index % 8) as u8) as char, (b'1' + (index / 8) as u8) as char) };
+                let mut board = Board::new();
+                board.add_piece(Piece { color: Color::White, figure: Figure::King, square: square(king) });
+                board.add_piece(Piece { color: Color::White, figure, square: square(other) });
+                board.add_piece(Piece { color: Color::Black, figure: Figure::King, square: square(56) });
+                let Some(dtm) = table.probe(&board, Color::Black) else {
+                    continue;
+                };
+                let fen = format!("{} b - - 0 1", rust_chess::parser::export_fen_position(&board));
+                let mut game = Game::from_fen(&fen).unwrap();
+                assert_eq!(dtm == Dtm::Loss(0), game.in_checkmate_color(Color::Black), "{}", fen);
+                mates += (dtm == Dtm::Loss(0)) as usize;
+            }
+        }
+        assert_eq!(true, mates > 0);
+    }
+}
+
+#[test]
+fn distances_agree_with_the_tablebase() {
+    // without captures on the way, the distance to zero of the Syzygy tables is the distance to mate
+    let tablebase = Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy")).unwrap();
+    let table = DtmTable::generate("KRvK").unwrap();
+    for fen in ["8/8/3k4/8/8/8/8/R6K w - - 0 1", "8/8/8/8/4k3/8/8/K6R w - - 0 1", "8/8/8/4k3/8/8/8/K6R b - - 0 1",
+        "7k/8/6K1/8/8/8/8/5R2 b - - 0 1", "R7/8/8/8/8/8/2k5/K7 w - - 0 1"] {
+        let dtz = tablebase.probe_dtz(&Game::from_fen(fen).unwrap()).unwrap();
+        let dtm = match probe(&table, fen).unwrap() {
+            Dtm::Win(plies) => plies as i32,
+            Dtm::Loss(plies) => -(plies as i32),
+            Dtm::Draw => 0,
+        };
+        assert_eq!(dtz, dtm, "{}", fen);
+    }
+}
@@ -0,0 +1,76 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::{Color, Figure, Square};
+use rust_chess::game::Game;
+use rust_chess::parser::parse_fen;
+
+fn see(fen: &str, square: &str, color: Color) -> i32 {
+    parse_fen(fen).unwrap().see(Square::from_str(square), color).score
+}
+
+fn see_move(fen: &str, san: &str) -> i32 {
+    let game = Game::from_fen(fen).unwrap();
+    game.see(&game.parse_san(san).unwrap()).score
+}
+
+#[test]
+fn exchanging_on_a_square() {
+    // a pawn takes a knight and is taken back
+    assert_eq!(200, see("4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1", "d5", Color::White));
+    // an undefended piece
+    assert_eq!(500, see("4k3/8/8/3r4/8/8/8/3QK3 w - - 0 1", "d5", Color::White));
+    // the rook would be lost for a pawn, so it does not capture
+    assert_eq!(0, see("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1", "e5", Color::White));
+    // nothing to capture with
+    assert_eq!(0, see("4k3/8/8/4p3/8/8/8/4K3 w - - 0 1", "e5", Color::White));
+    // the king may only take pieces which are not defended
+    assert_eq!(100, see("4k3/8/8/4p3/4K3/8/8/8 w - - 0 1", "e5", Color::White));
+    assert_eq!(0, see("4k3/8/3p4/4p3/4K3/8/8/8 w - - 0 1", "e5", Color::White));
+}
+
+#[test]
+fn x_ray_attackers() {
+    // the second rook stands behind the first one and takes back
+    let board = parse_fen("4r1k1/8/8/4p3/8/8/4R3/4RK2 w - - 0 1").unwrap();
+    let exchange = board.see(Square::from_str("e5"), Color::White);
+    assert_eq!(100, exchange.score);
+    let squares = |pieces: &[rust_chess::core::Piece]| pieces.iter().map(|p| p.square.to_string()).collect::<Vec<_>>();
+    assert_eq!(vec! ["e2", "e1"], squares(&exchange.attackers));
+    assert_eq!(vec! ["e8"], squares(&exchange.defenders));
+    // without it the pawn is defended well enough
+    assert_eq!(0, see("4r1k1/8/8/4p3/8/8/4R3/5K2 w - - 0 1", "e5", Color::White));
+
+    // a queen behind a bishop, and a rook behind the defending queen
+    let board = parse_fen("3r2k1/3q4/8/3p4/2B5/1Q6/8/6K1 w - - 0 1").unwrap();
+    let exchange = board.see(Square::from_str("d5"), Color::White);
+    assert_eq!(vec! [Figure::Bishop, Figure::Queen], exchange.attackers.iter().map(|p| p.figure).collect::<Vec<_>>());
+    assert_eq!(vec! [Figure::Queen, Figure::Rook], exchange.defenders.iter().map(|p| p.figure).collect::<Vec<_>>());
+    assert_eq!(0, exchange.score);
+}
+
+#[test]
+fn ordering_pieces() {
+    // both sides capture with their least valuable piece first
+    let board = parse_fen("3qk3/8/2p2n2/3p4/4P3/2N5/8/3QK3 w - - 0 1").unwrap();
+    let exchange = board.see(Square::from_str("d5"), Color::White);
+    let figures = |pieces: &[rust_chess::core::Piece]| pieces.iter().map(|p| p.figure).collect::<Vec<_>>();
+    assert_eq!(vec! [Figure::Pawn, Figure::Knight, Figure::Queen], figures(&exchange.attackers));
+    assert_eq!(vec! [Figure::Pawn, Figure::Knight, Figure::Queen], figures(&exchange.defenders));
+    assert_eq!(0, exchange.score);
+}
+
+#[test]
+fn exchanges_of_moves() {
+    // the queen takes a defended pawn and is lost
+    assert_eq!(-800, see_move("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1", "Qxe5"));
+    assert_eq!(100, see_move("4k3/8/8/4p3/8/8/8/4QK2 w - - 0 1", "Qxe5"));
+    // a quiet move to a square the opponent controls
+    assert_eq!(-300, see_move("4k3/8/3p4/8/8/5N2/8/4K3 w - - 0 1", "Ne5"));
+    assert_eq!(0, see_move("4k3/8/8/8/8/5N2/8/4K3 w - - 0 1", "Ne5"));
+    // en passant
+    assert_eq!(100, see_move("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "exd6"));
+    // a promotion, where the new queen is taken by the rook
+    assert_eq!(800, see_move("6k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8=Q+"));
+    assert_eq!(-100, see_move("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8=Q"));
+    // castling starts no exchange
+    assert_eq!(0, see_move("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "O-O"));
+}
@@ -0,0 +1,35 @@
+//! A scripted stand-in for a UCI engine, used by the tests of `rust_chess::uci_client`.
+//!
+//! Usage: `mock_engine <script>`. The script consists of command lines followed by indented reply lines.
+//! For every line received on stdin the first command which is a prefix of the line is looked up and its replies are printed;
+//! `{}` in a reply is replaced by the received line. Unknown commands are ignored, `quit` ends the process.
+
+use std::io::{self, BufRead};
+
+fn main() {
+    let path = std::env::args().nth(1).expect("usage: mock_engine <script>");
+    let script = std::fs::read_to_string(path).expect("could not read script");
+
+    let mut entries: Vec<(String, Vec<String>)> = vec! [];
+    for line in script.lines().filter(|l| !l.trim().is_empty() && !l.starts_with('#')) {
+        if line.starts_with(char::is_whitespace) {
+            if let Some((_, replies)) = entries.last_mut() {
+                replies.push(line.trim().to_string());
+            }
+        } else {
+            entries.push((line.trim().to_string(), vec! []));
+        }
+    }
+
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap();
+        if line.trim() == "quit" {
+            return;
+        }
+        if let Some((_, replies)) = entries.iter().find(|(command, _)| line.starts_with(command.as_str())) {
+            for reply in replies {
+                println!("{}", reply.replace("{}", &line));
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::Square;
+use rust_chess::parser::parse_fen;
+use rust_chess::svg::{render, Arrow, Circle, SvgOptions};
+
+fn count(svg: &str, pattern: &str) -> usize {
+    svg.matches(pattern).count()
+}
+
+#[test]
+fn rendering_boards() {
+    let board = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    let svg = render(&board, &SvgOptions::default());
+    assert_eq!(true, svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(true, svg.ends_with("</svg>\n"));
+    assert_eq!(true, svg.contains("width=\"400\" height=\"400\""));
+    assert_eq!(64, count(&svg, "class=\"square "));
+    assert_eq!(32, count(&svg, "class=\"square light"));
+    assert_eq!(32, count(&svg, "class=\"piece "));
+    assert_eq!(16, count(&svg, "class=\"piece white"));
+    assert_eq!(true, svg.contains("class=\"piece white K\""));
+    // the labels are on all four sides
+    assert_eq!(32, count(&svg, "class=\"coord\""));
+
+    // a1 is dark and in the bottom left corner, on a board of 8 squares of 45 and a margin of 20
+    assert_eq!(true, svg.contains(r##"<rect x="20" y="335" width="45" height="45" class="square dark a1" fill="#b58863"/>"##));
+    assert_eq!(true, svg.contains(r##"<rect x="335" y="20" width="45" height="45" class="square dark h8" fill="#b58863"/>"##));
+
+    let options = SvgOptions { size: 200, coordinates: false, light_color: String::from("white"), ..SvgOptions::default() };
+    let svg = render(&parse_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap(), &options);
+    assert_eq!(0, count(&svg, "class=\"coord\""));
+    assert_eq!(0, count(&svg, "class=\"piece"));
+    assert_eq!(true, svg.contains("viewBox=\"0 0 360 360\" width=\"200\""));
+    assert_eq!(true, svg.contains(r#"<rect x="45" y="315" width="45" height="45" class="square light b1" fill="white"/>"#));
+}
+
+#[test]
+fn flipping_boards() {
+    let board = parse_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let svg = render(&board, &SvgOptions { flipped: true, coordinates: false, ..SvgOptions::default() });
+    // a1 is in the top right corner
+    assert_eq!(true, svg.contains(r#"<rect x="315" y="0" width="45" height="45" class="square dark a1""#));
+    assert_eq!(true, svg.contains(r#"<rect x="0" y="315" width="45" height="45" class="square dark h8""#));
+    // the white king on e1 is at the top
+    assert_eq!(true, svg.contains(r#"<text x="157.5" y="24.5" class="piece white K""#));
+}
+
+#[test]
+fn highlights_and_marks() {
+    let board = parse_fen("4k3/8/8/8/8/8/4Q3/4K3 b - - 0 1").unwrap();
+    let options = SvgOptions {
+        last_move: Some((Square::from_str("e5"), Square::from_str("e2"))),
+        check: Some(Square::from_str("e8")),
+        arrows: vec! [Arrow::new(Square::from_str("e2"), Square::from_str("e7")), Arrow::new(Square::from_str("a1"), Square::from_str("a1"))],
+        circles: vec! [Circle { square: Square::from_str("d7"), color: String::from("\"><script>") }],
+        ..SvgOptions::default()
+    };
+    let svg = render(&board, &options);
+    assert_eq!(2, count(&svg, "class=\"lastmove "));
+    assert_eq!(true, svg.contains("class=\"lastmove e5\""));
+    assert_eq!(true, svg.contains("<radialGradient id=\"check_gradient\">"));
+    assert_eq!(true, svg.contains(r#"<rect x="200" y="20" width="45" height="45" class="check" fill="url(#check_gradient)"/>"#));
+    // an arrow to its own square is left out
+    assert_eq!(1, count(&svg, "<line "));
+    assert_eq!(1, count(&svg, "<polygon "));
+    assert_eq!(1, count(&svg, "<circle "));
+    // colors can not break out of the attribute
+    assert_eq!(false, svg.contains("<script>"));
+    assert_eq!(true, svg.contains("stroke=\"&quot;&gt;&lt;script&gt;\""));
+
+    let svg = render(&board, &SvgOptions::default());
+    assert_eq!(0, count(&svg, "lastmove") + count(&svg, "check") + count(&svg, "arrow") + count(&svg, "circle"));
+}
@@ -0,0 +1,197 @@
+#![allow(clippy::bool_assert_comparison)]
+use rust_chess::core::{Board, Color, Figure, Piece, Square};
+use rust_chess::game::{Game, GameResult};
+use rust_chess::parser::export_fen_position;
+use rust_chess::retrograde::{Dtm, DtmTable};
+use rust_chess::tablebase::{Tablebase, Wdl};
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+// written by examples/make_syzygy.rs
+fn tablebase() -> Tablebase {
+    Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy")).unwrap()
+}
+
+// the tables with two bishops are too large to keep with the tests, the example writes them when they are needed
+fn generated_tablebase() -> &'static Tablebase {
+    static TABLEBASE: OnceLock<Tablebase> = OnceLock::new();
+    TABLEBASE.get_or_init(|| {
+        let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("syzygy");
+        std::fs::create_dir_all(&directory).unwrap();
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+        let status = Command::new(cargo)
+            .args(["run", "--quiet", "--release", "--example", "make_syzygy", "--manifest-path", concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"), "--"])
+            .arg(&directory)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "could not write the tables");
+        Tablebase::open(directory).unwrap()
+    })
+}
+
+fn wdl(tablebase: &Tablebase, fen: &str) -> Option<Wdl> {
+    tablebase.probe_wdl(&Game::from_fen(fen).unwrap())
+}
+
+fn dtz(tablebase: &Tablebase, fen: &str) -> Option<i32> {
+    tablebase.probe_dtz(&Game::from_fen(fen).unwrap())
+}
+
+// plays the best moves of the tables until White has mated
+fn play_out(tablebase: &Tablebase, fen: &str) {
+    let mut game = Game::from_fen(fen).unwrap();
+    let mut plies = 0;
+    while let Some(best) = tablebase.rank_root_moves(&game).unwrap().first().copied() {
+        assert_eq!(true, best.wdl == Wdl::Win || game.current_color() == Color::Black);
+        assert_eq!(Some(best.dtz), tablebase.probe_dtz(&game));
+        game.make_move(best.move_);
+        plies += 1;
+        assert_eq!(true, plies < 100);
+    }
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result(), "{}", fen);
+    assert_eq!(Color::Black, game.current_color());
+}
+
+#[test]
+fn opening_tablebases() {
+    let tablebase = tablebase();
+    assert_eq!(3, tablebase.max_pieces());
+    assert_eq!(true, Tablebase::open("tests/data/no such directory").is_err());
+
+    // too many pieces, and castling rights
+    assert_eq!(None, tablebase.probe_wdl(&Game::new()));
+    assert_eq!(None, wdl(&tablebase, "8/8/8/8/8/2k5/8/R3K3 w Q - 0 1"));
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "8/8/8/8/8/2k5/8/R3K3 w - - 0 1"));
+    // no table needed for two kings
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "8/8/8/8/8/2k5/8/4K3 w - - 0 1"));
+    assert_eq!(None, wdl(&Tablebase::default(), "8/8/8/8/8/2k5/8/R3K3 w - - 0 1"));
+}
+
+#[test]
+fn probing_results() {
+    let tablebase = tablebase();
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "8/8/8/8/8/2k5/8/KQ6 w - - 0 1"));
+    // the queen can be taken, unless it is defended
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "8/8/8/8/8/2k5/1Q6/7K b - - 0 1"));
+    assert_eq!(Some(Wdl::Loss), wdl(&tablebase, "8/8/8/8/8/2k5/1Q6/K7 b - - 0 1"));
+    // the tables hold the stronger side as White
+    assert_eq!(Some(Wdl::Loss), wdl(&tablebase, "8/8/8/8/8/2K5/1q6/k7 w - - 0 1"));
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "7r/8/8/2K5/8/8/8/k7 b - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "8/8/8/3B4/8/2k5/8/K7 w - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "8/8/8/3n4/8/2K5/8/k7 b - - 0 1"));
+
+    // the king in front of its pawn on the sixth rank wins whoever moves
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"));
+    assert_eq!(Some(Wdl::Loss), wdl(&tablebase, "4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "8/8/8/8/4p3/4k3/8/4K3 b - - 0 1"));
+    // with the pawn still on its square White has a spare tempo to gain the opposition
+    assert_eq!(Some(Wdl::Win), wdl(&tablebase, "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "4k3/8/8/8/8/4P3/8/4K3 b - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "7k/8/8/8/8/8/7P/7K w - - 0 1"));
+    // the king is outside the square of the pawn, or just inside
+    assert_eq!(Some(Wdl::Loss), wdl(&tablebase, "7k/8/8/8/P7/8/8/K7 b - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(&tablebase, "8/8/4k3/8/P7/8/8/7K b - - 0 1"));
+}
+
+#[test]
+fn distances_to_zero() {
+    let tablebase = tablebase();
+    assert_eq!(Some(1), dtz(&tablebase, "7k/8/6K1/8/8/8/8/1Q6 w - - 0 1"));
+    assert_eq!(Some(-1), dtz(&tablebase, "Q6k/8/6K1/8/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(0), dtz(&tablebase, "8/8/8/8/8/2k5/1Q6/7K b - - 0 1"));
+    // a pawn move zeroes
+    assert_eq!(Some(1), dtz(&tablebase, "7k/8/8/8/P7/8/8/K7 w - - 0 1"));
+    assert_eq!(Some(-1), dtz(&tablebase, "8/8/8/p7/8/8/8/k6K b - - 0 1").map(|dtz| -dtz));
+
+    let moves = tablebase.rank_root_moves(&Game::from_fen("7k/8/6K1/8/8/8/8/1Q6 w - - 0 1").unwrap()).unwrap();
+    assert_eq!(("b1b8", 1, Wdl::Win), (moves[0].move_.to_uci().as_str(), moves[0].dtz, moves[0].wdl));
+    assert_eq!(true, moves[1].dtz > 1);
+    // giving away the queen is a draw, and ranked last
+    assert_eq!((Wdl::Draw, 0), moves.last().map(|root| (root.wdl, root.dtz)).unwrap());
+}
+
+#[test]
+fn playing_out_endings() {
+    let tablebase = tablebase();
+    for fen in ["8/8/8/8/4k3/8/8/K6R w - - 0 1", "4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"] {
+        play_out(&tablebase, fen);
+    }
+
+    // without captures and pawn moves, the rook mates in exactly as many plies as the distance to zero
+    let mut game = Game::from_fen("8/8/8/8/4k3/8/8/K6R w - - 0 1").unwrap();
+    let dtz = tablebase.probe_dtz(&game).unwrap();
+    for _ in 0..dtz {
+        let best = tablebase.rank_root_moves(&game).unwrap()[0];
+        game.make_move(best.move_);
+    }
+    assert_eq!(true, game.in_checkmate());
+}
+
+#[test]
+#[ignore = "building and running examples/make_syzygy for the tables with two bishops takes a while"]
+fn identical_pieces() {
+    let tablebase = generated_tablebase();
+    assert_eq!(4, tablebase.max_pieces());
+    // the bishops need squares of both colors, c1 and e3 are dark
+    assert_eq!(Some(Wdl::Win), wdl(tablebase, "8/8/8/4k3/8/8/8/2B1KB2 w - - 0 1"));
+    assert_eq!(Some(Wdl::Loss), wdl(tablebase, "8/8/8/4k3/8/8/8/2B1KB2 b - - 0 1"));
+    assert_eq!(Some(Wdl::Draw), wdl(tablebase, "8/8/8/4k3/8/4B3/8/2B1K3 w - - 0 1"));
+    // Black takes the bishop on d3
+    assert_eq!(Some(Wdl::Draw), wdl(tablebase, "8/8/8/8/3k4/3B4/8/2B1K3 b - - 0 1"));
+    assert_eq!(Some(0), dtz(tablebase, "8/8/8/8/3k4/3B4/8/2B1K3 b - - 0 1"));
+
+    assert_eq!(Some(1), dtz(tablebase, "k7/8/1K1BB3/8/8/8/8/8 w - - 0 1"));
+    assert_eq!(Some(-1), dtz(tablebase, "k7/8/1KBB4/8/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(Wdl::Loss), wdl(tablebase, "k7/8/1KBB4/8/8/8/8/8 b - - 0 1"));
+
+    // the longest mate takes 19 moves, and the table holds Black to move
+    assert_eq!(Some(37), dtz(tablebase, "8/8/8/8/7B/8/3k4/K2B4 w - - 0 1"));
+    let mut game = Game::from_fen("8/8/8/8/7B/8/3k4/K2B4 w - - 0 1").unwrap();
+    game.make_move(tablebase.rank_root_moves(&game).unwrap()[0].move_);
+    assert_eq!(Some(-36), tablebase.probe_dtz(&game));
+    play_out(tablebase, "8/8/8/4k3/8/8/8/2B1KB2 w - - 0 1");
+}
+
+#[test]
+fn longest_mates() {
+    // the longest mates are known to take 10 moves with the queen and 16 with the rook
+    let tablebase = tablebase();
+    assert_eq!(Some(19), dtz(&tablebase, "8/8/8/5k2/8/8/1Q6/K7 w - - 0 1"));
+    assert_eq!(Some(31), dtz(&tablebase, "8/8/8/8/8/2k5/1R6/K7 w - - 0 1"));
+}
+
+#[test]
+#[ignore = "probing every position of two tables takes 20 seconds in debug builds, run it with --release"]
+fn agreeing_with_the_retrograde_solver() {
+    // the tables are written by a solver of their own, compare them with the distances to mate of the library for
+    // all positions with the black king on d5, where the distance to zero is the distance to mate
+    let tablebase = tablebase();
+    let square = |index: usize| Square { pos: ((b'a' + (index % 8) as u8) as char, (b'1' + (index / 8) as u8) as char) };
+    for (material, figure) in [("KQvK", Figure::Queen), ("KRvK", Figure::Rook)] {
+        let table = DtmTable::generate(material).unwrap();
+        for king in (0..64).filter(|&king| king != 35) {
+            for other in (0..64).filter(|&other| other != king && other != 35) {
+                let mut board = Board::new();
+                board.add_piece(Piece { color: Color::White, figure: Figure::King, square: square(king) });
+                board.add_piece(Piece { color: Color::White, figure, square: square(other) });
+                board.add_piece(Piece { color: Color::Black, figure: Figure::King, square: square(35) });
+                for (color, to_move) in [(Color::White, "w"), (Color::Black, "b")] {
+                    let Some(dtm) = table.probe(&board, color) else {
+                        continue;
+                    };
+                    let fen = format!("{} {} - - 0 1", export_fen_position(&board), to_move);
+                    let expected = match dtm {
+                        Dtm::Win(plies) => (Wdl::Win, plies as i32),
+                        Dtm::Loss(0) => (Wdl::Loss, -1),
+                        Dtm::Loss(plies) => (Wdl::Loss, -(plies as i32)),
+                        Dtm::Draw => (Wdl::Draw, 0),
+                    };
+                    assert_eq!(Some(expected), wdl(&tablebase, &fen).zip(dtz(&tablebase, &fen)), "{}", fen);
+                }
+            }
+        }
+    }
+}
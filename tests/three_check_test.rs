@@ -0,0 +1,80 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::Color;
+use rust_chess::game::{Game, GameResult, Termination};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::variant::{KingOfTheHill, ThreeCheck};
+use std::sync::Arc;
+
+#[test]
+fn checks_are_counted() {
+    let mut game = Game::with_variant(Arc::new(ThreeCheck));
+    assert_eq!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0", game.to_fen());
+    assert_eq!(None, Game::new().checks_given(Color::White));
+
+    common::play(&mut game, "e4 e5 Bc4 Nc6 Bxf7+");
+    assert_eq!(Some(1), game.checks_given(Color::White));
+    assert_eq!("r1bqkbnr/pppp1Bpp/2n5/4p3/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 0 3 +1+0", game.to_fen());
+    common::play(&mut game, "Kxf7 Qh5+ Ke7");
+    assert_eq!(None, game.update_result());
+    common::play(&mut game, "Qxe5+");
+    assert_eq!(Some(3), game.checks_given(Color::White));
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result());
+    assert_eq!(Some(Termination::ThirdCheck), game.termination());
+
+    // the counts are taken back when going back in the game
+    game.back();
+    assert_eq!(Some(2), game.checks_given(Color::White));
+
+    // and read from Fen records
+    let game = Game::from_fen_with_variant("4k3/8/8/8/8/8/8/4K2R w K - 0 1 +2+1", Arc::new(ThreeCheck)).unwrap();
+    assert_eq!(Some(1), game.checks_given(Color::Black));
+    assert_eq!(true, Game::from_fen_with_variant("4k3/8/8/8/8/8/8/4K2R w K - 0 1 3+3", Arc::new(ThreeCheck)).is_err());
+    let game = Game::from_fen_with_variant("4k3/8/8/8/8/8/8/4K2R w K - 0 1", Arc::new(ThreeCheck)).unwrap();
+    assert_eq!(Some(0), game.checks_given(Color::White));
+}
+
+#[test]
+fn three_check_pgn() {
+    let mut game = Game::with_variant(Arc::new(ThreeCheck));
+    common::play(&mut game, "e4 e5 Bc4 Nc6 Bxf7+ Kxf7 Qh5+ Ke7 Qxe5+");
+    game.update_result();
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"Three-check\"]\n[Termination \"third check\"]\n"));
+    // the game ending check is marked like a mate
+    assert!(pgn.ends_with("5. Qxe5# 1-0\n"));
+
+    let imported = parse_pgn(&pgn).unwrap();
+    assert_eq!(game.to_fen(), imported.to_fen());
+    assert_eq!(Some(Termination::ThirdCheck), imported.termination());
+
+    // a bare king can not give checks
+    let game = Game::from_fen_with_variant("4k3/8/8/8/8/8/8/2N1K3 w - - 0 1 +0+0", Arc::new(ThreeCheck)).unwrap();
+    assert_eq!(false, game.has_insufficient_material(Color::White));
+    assert_eq!(true, game.has_insufficient_material(Color::Black));
+}
+
+#[test]
+fn king_of_the_hill() {
+    let mut game = Game::with_variant(Arc::new(KingOfTheHill));
+    common::play(&mut game, "e4 e5 Ke2 Ke7 Kd3 Kf6");
+    assert_eq!(None, game.update_result());
+    common::play(&mut game, "Kc4 Kg6 Kd5");
+    assert_eq!(Some(GameResult::WhiteWin), game.update_result());
+    assert_eq!(Some(Termination::KingInCenter), game.termination());
+
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"King of the Hill\"]\n[Termination \"king in the center\"]\n"));
+    let imported = parse_pgn(&pgn).unwrap();
+    assert_eq!(Some(GameResult::WhiteWin), imported.result());
+    assert_eq!(Some(Termination::KingInCenter), imported.termination());
+
+    // a game over can not go on
+    assert_eq!(true, game.get_legal_moves(Color::Black).is_empty());
+}
+
+#[test]
+fn three_check_perft() {
+    let game = Game::with_variant(Arc::new(ThreeCheck));
+    assert_eq!(vec! [20, 400, 8902], (1..=3).map(|depth| common::perft(&game, depth)).collect::<Vec<u64>>());
+}
@@ -0,0 +1,193 @@
+use rust_chess::core::{Figure, Square};
+use rust_chess::game::Game;
+use rust_chess::search::{Score, SearchLimits};
+use rust_chess::uci_client::{Bound, Engine, EngineOption, UciError};
+use rust_chess::variant::Chess960;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+// the scripted stand-in for an external engine is not part of the crate, it is compiled once here for the tests
+fn mock_engine_path() -> &'static Path {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("mock_engine{}", std::env::consts::EXE_SUFFIX));
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "-o"])
+            .arg(&path)
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/support/mock_engine.rs"))
+            .status()
+            .unwrap();
+        assert!(status.success(), "could not compile the mock engine");
+        path
+    })
+}
+
+fn mock_engine(script: &str) -> Engine {
+    let script = format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), script);
+    Engine::spawn(mock_engine_path(), &[&script]).unwrap()
+}
+
+fn move_str(game: &Game, input: &str) -> rust_chess::game::Move {
+    game.generate_move_from_str(input)
+}
+
+#[test]
+fn handshake() {
+    let mut engine = mock_engine("mock_engine.uci");
+    assert_eq!(Some(String::from("Mock Engine 1.0")), engine.name);
+    assert_eq!(Some(String::from("rust_chess tests")), engine.author);
+    assert_eq!(3, engine.options.len());
+    assert_eq!(EngineOption {
+        name: String::from("Hash"), kind: String::from("spin"), default: Some(String::from("16")),
+        min: Some(1), max: Some(1024), vars: vec! [] }, engine.options[0]);
+    assert_eq!(vec! ["Solid", "Normal", "Risky"], engine.options[1].vars);
+    assert_eq!("Clear Hash", engine.options[2].name);
+
+    engine.is_ready().unwrap();
+    engine.new_game().unwrap();
+    engine.quit().unwrap();
+}
+
+#[test]
+fn search_with_info_and_ponder() {
+    let mut engine = mock_engine("mock_engine.uci");
+    let game = Game::new();
+    engine.set_option("Hash", "32").unwrap();
+    engine.set_position(&game).unwrap();
+    let result = engine.go(&SearchLimits { depth: Some(2), ..Default::default() }).unwrap();
+
+    // the mock engine echoes the commands it got
+    assert_eq!(Some(String::from("setoption name Hash value 32")), result.infos[0].string);
+    assert_eq!(Some(String::from("position startpos")), result.infos[1].string);
+
+    let first = &result.infos[2];
+    assert_eq!(Some(1), first.depth);
+    assert_eq!(Some(2), first.seldepth);
+    assert_eq!(Some(Score::Centipawns(20)), first.score);
+    assert_eq!(Some(20), first.nodes);
+    assert_eq!(Some(1000), first.nps);
+    assert_eq!(Some(Duration::from_millis(20)), first.time);
+    assert_eq!(vec! [move_str(&game, "e2-e4")], first.pv);
+
+    // the pv is cut off at the first move which is not legal
+    let second = &result.infos[3];
+    assert_eq!(Some(1), second.multipv);
+    assert_eq!(Some(Score::Centipawns(-15)), second.score);
+    assert_eq!(Some(Bound::Upper), second.bound);
+    assert_eq!(3, second.pv.len());
+    assert_eq!(Square::from_str("e2"), second.pv[2].target_square());
+    assert_eq!(Figure::King, second.pv[2].piece().figure);
+
+    assert_eq!(Some(move_str(&game, "e2-e4")), result.best_move);
+    let mut after_best = game.clone();
+    after_best.move_from_str("e2-e4");
+    assert_eq!(Some(move_str(&after_best, "e7-e5")), result.ponder);
+}
+
+#[test]
+fn position_with_moves_from_fen() {
+    let mut engine = mock_engine("mock_engine_echo.uci");
+    let mut game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    game.move_from_str("e1-g1").move_from_str("e8-d7").move_from_str("b7-b8q");
+    engine.set_position(&game).unwrap();
+    let result = engine.go(&SearchLimits::default()).unwrap();
+    assert_eq!(None, result.best_move);
+    assert_eq!(Some(String::from("position fen 4k3/1P6/8/8/8/8/8/4K2R w K - 0 1 moves e1g1 e8d7 b7b8q")), result.infos[0].string);
+}
+
+#[test]
+fn infinite_search_and_pondering() {
+    let mut engine = mock_engine("mock_engine.uci");
+    let mut game = Game::new();
+    engine.set_position(&game).unwrap();
+    engine.start(&SearchLimits { infinite: true, ..Default::default() }, false).unwrap();
+    let result = engine.stop().unwrap();
+    assert_eq!(Some(move_str(&game, "d2-d4")), result.best_move);
+    assert_eq!(1, result.infos.iter().filter(|info| info.depth == Some(1)).count());
+
+    // ponder on the expected reply, then the opponent plays it
+    game.move_from_str("d2-d4").move_from_str("d7-d5");
+    engine.set_position(&game).unwrap();
+    engine.start(&SearchLimits { wtime: Some(Duration::from_secs(60)), ..Default::default() }, true).unwrap();
+    engine.ponderhit().unwrap();
+    let result = engine.wait().unwrap();
+    assert_eq!(Some(move_str(&game, "g1-f3")), result.best_move);
+    game.move_from_str("g1-f3");
+    assert_eq!(Some(move_str(&game, "b8-c6")), result.ponder);
+}
+
+#[test]
+fn promotion_castling_and_mate_scores() {
+    let mut engine = mock_engine("mock_engine_endgame.uci");
+    let game = Game::from_fen("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    engine.set_position(&game).unwrap();
+
+    let result = engine.go(&SearchLimits { depth: Some(3), ..Default::default() }).unwrap();
+    assert_eq!(Some(Score::Mate(2)), result.infos[0].score);
+    assert_eq!(Some(Bound::Lower), result.infos[0].bound);
+    assert_eq!(3, result.infos[0].pv.len());
+    assert_eq!(Some(Figure::Queen), result.best_move.unwrap().promotion());
+
+    let result = engine.go(&SearchLimits { movetime: Some(Duration::from_millis(100)), ..Default::default() }).unwrap();
+    assert_eq!(Some(move_str(&game, "e1-g1")), result.best_move);
+    assert!(result.best_move.unwrap().flag().is_some());
+    assert_eq!(None, result.ponder);
+
+    let result = engine.go(&SearchLimits { wtime: Some(Duration::from_secs(1)), ..Default::default() }).unwrap();
+    assert_eq!(None, result.best_move);
+
+    assert!(matches!(engine.go(&SearchLimits::default()), Err(UciError::IllegalMove(mv)) if mv == "h1h9"));
+}
+
+#[test]
+fn chess960_castling() {
+    let mut engine = mock_engine("mock_engine_chess960.uci");
+    let mut game = Game::from_fen_with_variant("4k3/8/8/8/8/8/8/RK6 w Q - 0 1", Arc::new(Chess960::default())).unwrap();
+    engine.set_position(&game).unwrap();
+    let result = engine.go(&SearchLimits::default()).unwrap();
+
+    // b1a1 is castling, not a plain king move to c1
+    let castling = game.parse_uci_move_with("b1a1", true).unwrap();
+    assert!(castling.is_castling());
+    assert_eq!(vec! [castling, move_str(&game.simulate_move(castling), "e8-f7")], result.infos[1].pv);
+    assert_eq!(Some(castling), result.best_move);
+    assert_eq!(Some(move_str(&game.simulate_move(castling), "e8-f7")), result.ponder);
+
+    let mut engine = mock_engine("mock_engine_echo.uci");
+    game.make_move(castling).move_from_str("e8-f7");
+    engine.set_position(&game).unwrap();
+    let result = engine.go(&SearchLimits::default()).unwrap();
+    assert_eq!(Some(String::from("position fen 4k3/8/8/8/8/8/8/RK6 w Q - 0 1 moves b1a1 e8f7")), result.infos[0].string);
+}
+
+#[test]
+fn errors() {
+    let mut engine = mock_engine("mock_engine_endgame.uci");
+    assert!(matches!(engine.go(&SearchLimits::default()), Err(UciError::NoPosition)));
+
+    // once the engine has quit, it can not answer anymore
+    engine.timeout = Duration::from_millis(100);
+    engine.send("setoption name Hash value 1").unwrap();
+    engine.send("quit").unwrap();
+    assert!(matches!(engine.is_ready(), Err(UciError::Disconnected) | Err(UciError::Io(_))));
+
+    assert!(matches!(Engine::spawn("./does/not/exist", &[]), Err(UciError::Io(_))));
+
+    // there is no search to wait for before go and after bestmove
+    let mut engine = mock_engine("mock_engine_echo.uci");
+    assert!(matches!(engine.stop(), Err(UciError::NoSearch)));
+    engine.set_position(&Game::new()).unwrap();
+    assert!(matches!(engine.wait(), Err(UciError::NoSearch)));
+    engine.go(&SearchLimits::default()).unwrap();
+    assert!(matches!(engine.wait(), Err(UciError::NoSearch)));
+
+    // the engine never sends its best move
+    engine.timeout = Duration::from_millis(100);
+    let start = std::time::Instant::now();
+    assert!(matches!(engine.go(&SearchLimits { movetime: Some(Duration::from_millis(200)), ..Default::default() }), Err(UciError::Timeout)));
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
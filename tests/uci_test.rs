@@ -0,0 +1,119 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+// run the uci binary with the given input on stdin and collect its output once stdin is closed
+fn run_uci(input: &str) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap().lines().map(String::from).collect()
+}
+
+fn bestmove(output: &[String]) -> String {
+    output.iter()
+        .find_map(|line| line.strip_prefix("bestmove "))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn handshake() {
+    let output = run_uci("uci\nisready\n");
+    assert!(output[0].starts_with("id name rust_chess"));
+    assert!(output.iter().any(|line| line.starts_with("option name Depth type spin")));
+    let uciok = output.iter().position(|line| line == "uciok").unwrap();
+    let readyok = output.iter().position(|line| line == "readyok").unwrap();
+    assert!(uciok < readyok);
+}
+
+#[test]
+fn go_depth_from_startpos_with_moves() {
+    let output = run_uci("ucinewgame\nposition startpos moves e2e4 e7e5 g1f3\ngo depth 2\n");
+    assert_eq!(2, output.iter().filter(|line| line.starts_with("info depth")).count());
+    assert!(output.iter().any(|line| line.starts_with("info depth 2 score cp")));
+
+    // the best move is a legal move for black
+    let best = bestmove(&output);
+    assert_eq!(4, best.len());
+    assert!(["7", "6", "5", "8"].contains(&&best[1..2]));
+}
+
+#[test]
+fn go_finds_mate_in_one() {
+    let output = run_uci("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+    assert!(output.iter().any(|line| line.contains("score mate 1") && line.ends_with("pv a1a8")));
+    assert_eq!("a1a8", bestmove(&output));
+}
+
+#[test]
+fn go_without_legal_moves() {
+    let output = run_uci("position fen 6Rk/6R1/8/8/8/8/8/6K1 b - - 0 1\ngo depth 2\n");
+    assert_eq!("0000", bestmove(&output));
+}
+
+#[test]
+fn position_with_promotion_and_castling() {
+    let output = run_uci("position fen r3k3/1P6/8/8/8/8/8/4K2R w Kq - 0 1 moves b7a8q e8e7 e1g1\ngo depth 1\n");
+    assert!(output.iter().all(|line| !line.starts_with("info string")));
+    assert!(!bestmove(&output).is_empty());
+
+    let output = run_uci("position startpos moves e2e5\n");
+    assert_eq!(vec! [String::from("info string illegal move: e2e5")], output);
+}
+
+#[test]
+fn setoption_depth() {
+    let output = run_uci("setoption name Depth value 1\nposition startpos\ngo\n");
+    assert_eq!(1, output.iter().filter(|line| line.starts_with("info depth")).count());
+
+    let output = run_uci("setoption name Hash value 16\n");
+    assert_eq!(vec! [String::from("info string unknown option: Hash")], output);
+}
+
+#[test]
+fn go_with_time_controls() {
+    for go in ["go movetime 200", "go wtime 3000 btime 3000 winc 0 binc 0"] {
+        let output = run_uci(&format!("position startpos\n{}\n", go));
+        assert!(!bestmove(&output).is_empty());
+    }
+
+    // only white's clock is given, but black is to move
+    let output = run_uci("position startpos moves e2e4\ngo wtime 1000\n");
+    assert_eq!(3, output.iter().filter(|line| line.starts_with("info depth")).count());
+    assert!(!bestmove(&output).is_empty());
+}
+
+#[test]
+fn go_infinite_until_stop() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_uci"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+    stdin.write_all(b"position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo infinite\n").unwrap();
+    // the search finds the mate right away, but must not report it before stop
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert!(line.starts_with("info depth 1 score mate 1"));
+    std::thread::sleep(Duration::from_millis(100));
+    stdin.write_all(b"isready\n").unwrap();
+    line.clear();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!("readyok\n", line);
+
+    stdin.write_all(b"stop\n").unwrap();
+    line.clear();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!("bestmove a1a8\n", line);
+
+    stdin.write_all(b"quit\n").unwrap();
+    assert!(child.wait().unwrap().success());
+}
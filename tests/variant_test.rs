@@ -0,0 +1,82 @@
+#![allow(clippy::bool_assert_comparison)]
+mod common;
+use rust_chess::core::{Color, Figure};
+use rust_chess::game::{Game, GameResult, Move, Termination, STANDARD_FEN};
+use rust_chess::parser::{export_pgn, parse_pgn};
+use rust_chess::variant::{self, Variant};
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+// a variant defined outside the crate: no castling, and whoever loses the queen first loses the game
+struct QueenHunt;
+
+impl Variant for QueenHunt {
+    fn name(&self) -> &str {
+        "Queen Hunt"
+    }
+
+    fn start_fen(&self) -> String {
+        String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1")
+    }
+
+    fn generate_moves(&self, _game: &Game, _color: Color, moves: &mut HashSet<Move>) {
+        moves.retain(|mv| !mv.is_castling());
+    }
+
+    fn game_end(&self, game: &Game) -> Option<(GameResult, Termination)> {
+        let has_queen = |color| game.board.pieces.iter().any(|p| p.color == color && p.figure == Figure::Queen);
+        match (has_queen(Color::White), has_queen(Color::Black)) {
+            (false, _) => Some((GameResult::BlackWin, Termination::VariantRule)),
+            (_, false) => Some((GameResult::WhiteWin, Termination::VariantRule)),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn standard_rules_by_default() {
+    let game = Game::new();
+    assert_eq!("Standard", game.variant().name());
+    assert_eq!(STANDARD_FEN, game.variant().start_fen());
+    assert_eq!("Crazyhouse", Game::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap().variant().name());
+    assert_eq!("Chess960", Game::new_chess960(0).unwrap().variant().name());
+    assert_eq!(true, variant::from_name("Suicide Bughouse").is_none());
+}
+
+#[test]
+fn custom_variant() {
+    let mut game = Game::with_variant(Arc::new(QueenHunt));
+    assert_eq!("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1", game.to_fen());
+    common::play(&mut game, "e4 e5 Qh5 Nf6");
+    assert_eq!(None, game.update_result());
+    common::play(&mut game, "Qxf7+ Kxf7");
+    assert_eq!(Some(GameResult::BlackWin), game.update_result());
+    assert_eq!(Some(Termination::VariantRule), game.termination());
+
+    // the rules stay with the game when navigating the tree
+    game.go_to_start();
+    assert_eq!("Queen Hunt", game.variant().name());
+    let game = Game::from_fen_with_variant("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", Arc::new(QueenHunt)).unwrap();
+    assert_eq!(false, game.get_legal_moves(Color::White).iter().any(|mv| mv.is_castling()));
+    assert_eq!(true, Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap()
+        .get_legal_moves(Color::White).iter().any(|mv| mv.is_castling()));
+}
+
+#[test]
+fn variant_tag_in_pgn() {
+    let game = parse_pgn("[Variant \"Crazyhouse\"]\n\n1. e4 d5 2. exd5 Qxd5 3. P@e4 *").unwrap();
+    assert_eq!("Crazyhouse", game.variant().name());
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"Crazyhouse\"]\n\n1. e4"));
+    assert_eq!(game.to_fen(), parse_pgn(&pgn).unwrap().to_fen());
+
+    // Chess960 games need their starting position
+    let mut game = Game::new_chess960(0).unwrap();
+    common::play(&mut game, "g3");
+    let pgn = export_pgn(&game);
+    assert!(pgn.contains("[Variant \"Chess960\"]\n[SetUp \"1\"]\n[FEN \"bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1\"]\n"));
+    assert_eq!(game.to_fen(), parse_pgn(&pgn).unwrap().to_fen());
+
+    assert!(parse_pgn("[Variant \"Suicide Bughouse\"]\n\n1. e4 *").is_err());
+}